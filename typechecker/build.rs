@@ -0,0 +1,119 @@
+// Generates the table of `MICHELSON_INSTRUCTIONS` from src/lib/instructions.sig,
+// so that the signatures are checked when the crate is built and nothing is
+// parsed when the table is first used. The notation is read by the same
+// module as `parse_signatures`.
+
+use std::env;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+#[allow(dead_code)]
+#[path = "src/lib/signature.rs"]
+mod signature;
+
+use signature::*;
+
+const SIGNATURES: &str = "src/lib/instructions.sig";
+
+// Type variables are read as an `ArgConstraint` in the arguments and input
+// of a signature, and as a `StackResultElem` in its output.
+#[derive(Clone, Copy)]
+enum Kind {
+    Constraint,
+    Result,
+}
+
+fn atomic(name: &str) -> String {
+    format!("MAtomic::{}", variant(&ATOMIC_TYPES, name).unwrap())
+}
+
+fn attributes(names: &[&str]) -> String {
+    let attributes: Vec<String> = names
+        .iter()
+        .map(|a| format!("Attribute::{}", variant(&ATTRIBUTES, a).unwrap()))
+        .collect();
+    format!("vec![{}]", attributes.join(", "))
+}
+
+// One type, as the Rust expression that builds it. `parse_signature` only
+// lets through the type variables that `kind` has.
+fn ty(t: &SigType, kind: Kind) -> String {
+    match t {
+        SigType::Atomic(a) => unreachable!("Bare atomic type {} in a signature", a),
+        SigType::Var(v) => {
+            let elem = match (kind, v) {
+                (Kind::Constraint, Var::Atomic(a)) => {
+                    format!("ArgConstraint::CAtomic({})", atomic(a))
+                }
+                (Kind::Constraint, Var::Wildcard(c, attrs)) => {
+                    format!("ArgConstraint::CWarg({:?}, {})", c, attributes(attrs))
+                }
+                (Kind::Constraint, Var::TypeArg(c, attrs)) => {
+                    format!("ArgConstraint::CTypeArg({:?}, {})", c, attributes(attrs))
+                }
+                (Kind::Constraint, Var::Ref(c)) => format!("ArgConstraint::CTypeArgRef({:?})", c),
+                (Kind::Result, Var::Atomic(a)) => {
+                    format!("StackResultElem::ElemType({})", atomic(a))
+                }
+                (Kind::Result, Var::Ref(c)) => format!("StackResultElem::TRef({:?})", c),
+                (Kind::Result, v) => unreachable!("Type variable {:?} in an output", v),
+            };
+            format!("MType::MWrapped({})", elem)
+        }
+        SigType::App(c, args) => {
+            let args: Vec<String> = args.iter().map(|a| ty(a, kind)).collect();
+            match variant(&UNARY, c) {
+                Some(v) => format!("MType::{}(Arc::new({}))", v, args[0]),
+                None => format!(
+                    "MType::{}(Arc::new(({}, {})))",
+                    variant(&BINARY, c).unwrap(),
+                    args[0],
+                    args[1]
+                ),
+            }
+        }
+    }
+}
+
+fn types(ts: &[SigType], kind: Kind) -> String {
+    let ts: Vec<String> = ts.iter().map(|t| ty(t, kind)).collect();
+    format!("vec![{}]", ts.join(", "))
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=src/lib/signature.rs");
+    println!("cargo:rerun-if-changed={}", SIGNATURES);
+    let src = fs::read_to_string(SIGNATURES).expect("Cannot read the signature table");
+    // The table is ours, so how deep it nests is not bounded here.
+    let signatures = parse_signature_file(&src, usize::MAX).unwrap_or_else(|(offset, e)| {
+        let line = src[..offset].lines().count() + 1;
+        panic!("{}:{}: {}", SIGNATURES, line, e)
+    });
+    let mut instructions: Vec<(String, Vec<String>)> = vec![];
+    for (_, s) in signatures {
+        let def = format!(
+            "InstructionDef {{ args: {}, input_stack: {}, output_stack: {} }}",
+            types(&s.args, Kind::Constraint),
+            types(&s.input, Kind::Constraint),
+            types(&s.output, Kind::Result)
+        );
+        match instructions.iter_mut().find(|(n, _)| *n == s.name) {
+            Some((_, defs)) => defs.push(def),
+            None => instructions.push((s.name, vec![def])),
+        }
+    }
+    let mut out = String::from("BTreeMap::from([\n");
+    for (name, defs) in instructions {
+        let _ = writeln!(
+            out,
+            "    (String::from({:?}), vec![\n        {}\n    ]),",
+            name,
+            defs.join(",\n        ")
+        );
+    }
+    out.push_str("])\n");
+    let path = Path::new(&env::var("OUT_DIR").unwrap()).join("instructions.rs");
+    fs::write(path, out).expect("Cannot write the instruction table");
+}
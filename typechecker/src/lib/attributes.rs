@@ -1,47 +1,69 @@
 use crate::types::ConcreteType;
+use crate::types::MAtomic::*;
 use crate::types::MType::*;
 
 use crate::types::Attribute;
 use crate::types::Attribute::*;
 
-pub fn check_attributes(atrs: &[Attribute], ct: &ConcreteType) -> bool {
-    for atr in atrs {
-        if !check_attribute(atr, ct) {
-            return false;
-        }
+// The attributes of a type constructor, in the order comparable, passable,
+// pushable, storable, packable, big_map value and duplicable, following the
+// tables of the reference documentation. A type has an attribute when its
+// constructor has it and, unless the constructor hides its arguments, every
+// argument has it too.
+type Row = [bool; 7];
+
+const ALL: Row = [true, true, true, true, true, true, true];
+const OPERATION: Row = [false, false, false, false, false, false, true];
+const CONTRACT: Row = [false, true, false, false, true, false, true];
+const TICKET: Row = [false, true, false, true, false, true, false];
+const BIG_MAP: Row = [false, true, false, true, false, false, true];
+const COLLECTION: Row = [false, true, true, true, true, true, true];
+const LAMBDA: Row = [false, true, true, true, true, true, true];
+
+fn column(atr: &Attribute) -> usize {
+    match atr {
+        Comparable => 0,
+        Passable => 1,
+        Pushable => 2,
+        Storable => 3,
+        Packable => 4,
+        BigmapValue => 5,
+        Duplicable => 6,
     }
-    true
 }
 
-pub fn check_attribute(atr: &Attribute, ct: &ConcreteType) -> bool {
+// The row of the outermost constructor of a type, and the arguments that have
+// to share its attributes. The argument of `contract` and the types of a
+// `lambda` do not matter, and the content of a ticket is always comparable.
+fn row(ct: &ConcreteType) -> (&'static Row, Vec<&ConcreteType>) {
     match ct {
-        MWrapped(_) => true,
-
-        MMap(b) => match atr {
-            Comparable => false,
-            _ => check_attribute(atr, &b.1),
-        },
-        MBigMap(b) => match atr {
-            Passable => check_attribute(atr, &b.1),
-            Storable => check_attribute(atr, &b.1),
-            Duplicable => check_attribute(atr, &b.1),
-            _ => false,
-        },
-        MPair(b) => check_attribute(atr, &b.0) && check_attribute(atr, &b.1),
-        MOr(b) => check_attribute(atr, &b.0) && check_attribute(atr, &b.1),
-        MTicket(_) => !matches!(atr, Comparable | Duplicable | Pushable | Passable),
-        MList(b) => match atr {
-            Comparable => false,
-            _ => check_attribute(atr, b.as_ref()),
-        },
-        MSet(b) => match atr {
-            Comparable => false,
-            _ => check_attribute(atr, b.as_ref()),
-        },
+        MWrapped(MOperation) => (&OPERATION, vec![]),
+        MWrapped(_) => (&ALL, vec![]),
+        MContract(_) => (&CONTRACT, vec![]),
+        MTicket(_) => (&TICKET, vec![]),
+        MLambda(_) => (&LAMBDA, vec![]),
+        MBigMap(b) => (&BIG_MAP, vec![&b.0, &b.1]),
+        MOption(t) => (&ALL, vec![t.as_ref()]),
+        MPair(b) | MOr(b) => (&ALL, vec![&b.0, &b.1]),
+        MList(t) | MSet(t) => (&COLLECTION, vec![t.as_ref()]),
+        MMap(b) => (&COLLECTION, vec![&b.0, &b.1]),
+    }
+}
 
-        MOption(b) => check_attribute(atr, b.as_ref()),
-        MContract(_) => !matches!(atr, Comparable | Storable | Pushable | BigmapValue),
+pub fn check_attributes(atrs: &[Attribute], ct: &ConcreteType) -> bool {
+    atrs.iter().all(|atr| check_attribute(atr, ct))
+}
 
-        MLambda(_) => !matches!(atr, Comparable),
+// The arguments still to check are kept on a work stack rather than the call
+// stack, so that deeply nested types are fine.
+pub fn check_attribute(atr: &Attribute, ct: &ConcreteType) -> bool {
+    let mut todo = vec![ct];
+    while let Some(ct) = todo.pop() {
+        let (row, args) = row(ct);
+        if !row[column(atr)] {
+            return false;
+        }
+        todo.extend(args);
     }
+    true
 }
@@ -0,0 +1,243 @@
+use serde_json::Value;
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::fs;
+use std::panic;
+use std::path::Path;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread;
+
+use crate::cache::typecheck_contract_cached;
+use crate::cache::TcCache;
+use crate::instructions::InstructionRegistry;
+use crate::limits::Limits;
+use crate::parsers::try_parse_contract_with;
+use crate::printer::micheline_to_source;
+use crate::protocol::Protocol;
+use crate::typechecker::typecheck_contract_with;
+use crate::types::Diagnostic;
+use crate::types::ErrorKind;
+use crate::types::TcSession;
+
+/// A script of a batch, named after the file or the JSONL line it was read
+/// from.
+#[derive(Debug, Clone)]
+pub struct Script {
+    pub name: String,
+    pub source: String,
+}
+
+/// The diagnostics of one script of a batch, none when it typechecks.
+#[derive(Debug, Clone)]
+pub struct ScriptResult {
+    pub name: String,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// How many scripts a batch had, how many were rejected, and how many were
+/// rejected for each kind of error. A script counts once for each kind of
+/// error it has.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct BatchSummary {
+    pub scripts: usize,
+    pub rejected: usize,
+    pub by_kind: BTreeMap<ErrorKind, usize>,
+}
+
+/// Collects the scripts of `paths`: the `.tz` files under directories, the
+/// lines of `.jsonl` files and any other file as a script of its own.
+/// Directories are walked in name order, without following links.
+pub fn collect_scripts(paths: &[String]) -> Result<Vec<Script>, String> {
+    let mut scripts = vec![];
+    let mut todo: Vec<_> = paths
+        .iter()
+        .rev()
+        .map(|p| Path::new(p).to_path_buf())
+        .collect();
+    while let Some(path) = todo.pop() {
+        let name = path.display().to_string();
+        let metadata = fs::symlink_metadata(&path).map_err(|e| format!("{}: {}", name, e))?;
+        if metadata.is_dir() {
+            let mut entries = vec![];
+            for entry in fs::read_dir(&path).map_err(|e| format!("{}: {}", name, e))? {
+                let path = entry.map_err(|e| format!("{}: {}", name, e))?.path();
+                if path.is_dir() || path.extension().is_some_and(|e| e == "tz" || e == "jsonl") {
+                    entries.push(path);
+                }
+            }
+            entries.sort();
+            todo.extend(entries.into_iter().rev());
+            continue;
+        }
+        let source = fs::read_to_string(&path).map_err(|e| format!("{}: {}", name, e))?;
+        if path.extension().is_some_and(|e| e == "jsonl") {
+            scripts.extend(jsonl_scripts(&name, &source)?);
+        } else {
+            scripts.push(Script { name, source });
+        }
+    }
+    Result::Ok(scripts)
+}
+
+// Each line is an object with the script under `script`, either as Michelson
+// source or as Micheline JSON, possibly wrapped with its storage in an object
+// with a `code` field as indexers serve it. The script is named after the
+// `name` or `address` field when there is one.
+fn jsonl_scripts(file: &str, src: &str) -> Result<Vec<Script>, String> {
+    let mut scripts = vec![];
+    for (n, line) in src.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let at = |e: String| format!("{}:{}: {}", file, n + 1, e);
+        let entry: Value = serde_json::from_str(line).map_err(|e| at(e.to_string()))?;
+        let name = match entry.get("name").or_else(|| entry.get("address")) {
+            Some(Value::String(s)) => s.clone(),
+            _ => format!("{}:{}", file, n + 1),
+        };
+        let source = match entry.get("script") {
+            Some(Value::String(s)) => s.clone(),
+            Some(v) => micheline_to_source(v.get("code").unwrap_or(v)).map_err(at)?,
+            None => return Result::Err(at(String::from("Missing script field"))),
+        };
+        scripts.push(Script { name, source });
+    }
+    Result::Ok(scripts)
+}
+
+/// Typechecks a script on its own, as of `protocol` and within `limits`,
+/// skipping it when `cache` knows it. A panic is reported as an internal
+/// error of the script rather than stopping the batch.
+pub fn typecheck_script(
+    protocol: Protocol,
+    instructions: &InstructionRegistry,
+    limits: &Limits,
+    cache: Option<&Arc<TcCache>>,
+    script: &Script,
+) -> ScriptResult {
+    let diagnostics = panic::catch_unwind(|| {
+        let mut session = TcSession {
+            limits: *limits,
+            ..TcSession::default()
+        };
+        match try_parse_contract_with(&script.source, limits) {
+            Result::Ok(contract) => match cache.filter(|c| c.protocol() == protocol) {
+                Some(cache) => typecheck_contract_cached(
+                    cache,
+                    instructions,
+                    &contract,
+                    &script.source,
+                    &mut session,
+                ),
+                None => {
+                    typecheck_contract_with(protocol, instructions, &contract, &mut session);
+                }
+            },
+            Result::Err(d) => session.diagnostics.push(d),
+        }
+        session.diagnostics
+    })
+    .unwrap_or_else(|e| {
+        vec![Diagnostic {
+            location: None,
+            kind: ErrorKind::Internal,
+            message: format!("Internal error: {}", panic_message(&*e)),
+        }]
+    });
+    ScriptResult {
+        name: script.name.clone(),
+        diagnostics,
+    }
+}
+
+fn panic_message(e: &(dyn Any + Send)) -> &str {
+    match (e.downcast_ref::<&str>(), e.downcast_ref::<String>()) {
+        (Some(s), _) => s,
+        (_, Some(s)) => s,
+        _ => "panic",
+    }
+}
+
+// Nesting within the limits of the parser fits in 2MB of stack, the least
+// threads get by default; workers get as much as a main thread.
+const WORKER_STACK_SIZE: usize = 8 << 20;
+
+/// Typechecks `scripts` on `threads` threads, which share the instruction
+/// table of `protocol`. There is one result per script, in the order of the
+/// scripts; a script whose worker died has an internal error.
+pub fn typecheck_batch(
+    protocol: Protocol,
+    limits: &Limits,
+    cache: Option<&Arc<TcCache>>,
+    threads: usize,
+    scripts: &[Script],
+) -> Vec<ScriptResult> {
+    let instructions = InstructionRegistry::for_protocol(protocol);
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        let mut workers = vec![];
+        for _ in 0..threads.max(1) {
+            let sender = sender.clone();
+            let (instructions, next) = (&instructions, &next);
+            // A worker that cannot be started leaves its share to the others.
+            let worker = thread::Builder::new()
+                .stack_size(WORKER_STACK_SIZE)
+                .spawn_scoped(scope, move || loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(script) = scripts.get(i) else {
+                        return;
+                    };
+                    let result = typecheck_script(protocol, instructions, limits, cache, script);
+                    let _ = sender.send((i, result));
+                });
+            workers.extend(worker);
+        }
+        // Joined here, the panic of a worker does not reach the caller.
+        for worker in workers {
+            let _ = worker.join();
+        }
+    });
+    drop(sender);
+    let mut results: Vec<Option<ScriptResult>> = vec![None; scripts.len()];
+    for (i, result) in receiver {
+        results[i] = Some(result);
+    }
+    results
+        .into_iter()
+        .zip(scripts)
+        .map(|(result, script)| {
+            result.unwrap_or_else(|| ScriptResult {
+                name: script.name.clone(),
+                diagnostics: vec![Diagnostic {
+                    location: None,
+                    kind: ErrorKind::Internal,
+                    message: String::from("Internal error: the script was not typechecked"),
+                }],
+            })
+        })
+        .collect()
+}
+
+pub fn summarize(results: &[ScriptResult]) -> BatchSummary {
+    let mut summary = BatchSummary {
+        scripts: results.len(),
+        ..BatchSummary::default()
+    };
+    for result in results {
+        if result.diagnostics.is_empty() {
+            continue;
+        }
+        summary.rejected += 1;
+        let mut kinds: Vec<ErrorKind> = result.diagnostics.iter().map(|d| d.kind).collect();
+        kinds.sort();
+        kinds.dedup();
+        for kind in kinds {
+            *summary.by_kind.entry(kind).or_default() += 1;
+        }
+    }
+    summary
+}
@@ -0,0 +1,217 @@
+use serde_json::json;
+use serde_json::Value;
+use sha2::Digest;
+use sha2::Sha256;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use crate::instructions::InstructionRegistry;
+use crate::limits::Limit;
+use crate::limits::Limits;
+use crate::printer::print_contract;
+use crate::protocol::Protocol;
+use crate::typechecker::typecheck_contract_with;
+use crate::types::Contract;
+use crate::types::Diagnostic;
+use crate::types::ErrorKind;
+use crate::types::SomeValue;
+use crate::types::TcSession;
+
+// Bumped whenever the layout of the entries changes.
+const FORMAT_VERSION: &str = "1";
+
+// Names the temporary files entries are written to before they are renamed,
+// so that threads writing the same entry do not clobber each other.
+static NEXT_TEMPORARY: AtomicUsize = AtomicUsize::new(0);
+
+/// A cache of typechecking verdicts on disk, one file per script, named
+/// after a hash of its code in canonical layout. The hash also covers
+/// the version of the crate, the protocol, the instruction table and the
+/// limits, so that a verdict is only reused where it would be found again.
+///
+/// Only diagnostics are kept, not typed code, so the cache is only consulted
+/// for whole scripts, which are checked for their diagnostics alone. Lambdas
+/// are typechecked with the script they are in, as typing a value must give
+/// its code. A cache must be used with the instruction table it was opened
+/// with.
+#[derive(Debug)]
+pub struct TcCache {
+    dir: PathBuf,
+    protocol: Protocol,
+    fingerprint: String,
+}
+
+/// What the cache knows of a script.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheEntry {
+    // Hash of the source the diagnostics were found in. Their locations are
+    // only meaningful in that source.
+    pub source_hash: String,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl CacheEntry {
+    /// Whether the diagnostics hold for `source`, which has the same code in
+    /// canonical layout as the source they were found in.
+    pub fn applies_to(&self, source: &str) -> bool {
+        self.source_hash == digest(&[source])
+            || self.diagnostics.iter().all(|d| d.location.is_none())
+    }
+}
+
+/// The SHA-256 of `parts`, in hexadecimal. Each part is prefixed with its
+/// length, so that moving text from one part to the next changes the hash.
+pub fn digest(parts: &[&str]) -> String {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update((part.len() as u64).to_le_bytes());
+        hasher.update(part.as_bytes());
+    }
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+impl TcCache {
+    /// Opens the cache in `dir`, creating it if needed, for typechecking as
+    /// of `protocol` with `instructions`.
+    pub fn open(
+        dir: impl AsRef<Path>,
+        protocol: Protocol,
+        instructions: &InstructionRegistry,
+    ) -> Result<TcCache, String> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+        let fingerprint = digest(&[
+            env!("CARGO_PKG_VERSION"),
+            FORMAT_VERSION,
+            protocol.name(),
+            &format!("{:?}", instructions.iter().collect::<Vec<_>>()),
+        ]);
+        Result::Ok(TcCache {
+            dir,
+            protocol,
+            fingerprint,
+        })
+    }
+
+    pub fn protocol(&self) -> Protocol {
+        self.protocol
+    }
+
+    // The timeout is left out, as verdicts reached in time are kept whatever
+    // the time limit, and the others are not kept at all.
+    fn limits_key(limits: &Limits) -> String {
+        format!(
+            "{:?}",
+            Limits {
+                timeout: None,
+                ..*limits
+            }
+        )
+    }
+
+    pub fn contract_key(&self, contract: &Contract<SomeValue>, limits: &Limits) -> String {
+        digest(&[
+            &self.fingerprint,
+            "contract",
+            &Self::limits_key(limits),
+            &print_contract(contract),
+        ])
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+
+    /// The entry of `key`, if any. An entry that cannot be read is missing.
+    pub fn get(&self, key: &str) -> Option<CacheEntry> {
+        let contents = fs::read_to_string(self.path(key)).ok()?;
+        let entry: Value = serde_json::from_str(&contents).ok()?;
+        let mut diagnostics = vec![];
+        for d in entry.get("diagnostics")?.as_array()? {
+            diagnostics.push(Diagnostic {
+                location: match d.get("location")? {
+                    Value::Null => None,
+                    l => Some(l.as_u64()? as usize),
+                },
+                kind: kind_from_name(d.get("kind")?.as_str()?)?,
+                message: d.get("message")?.as_str()?.to_string(),
+            });
+        }
+        Some(CacheEntry {
+            source_hash: entry.get("source_hash")?.as_str()?.to_string(),
+            diagnostics,
+        })
+    }
+
+    /// Records the entry of `key`. The cache only saves work, so an entry
+    /// that cannot be written is left out.
+    pub fn put(&self, key: &str, entry: &CacheEntry) {
+        let diagnostics: Vec<Value> = entry
+            .diagnostics
+            .iter()
+            .map(|d| {
+                json!({
+                    "location": d.location,
+                    "kind": d.kind.to_string(),
+                    "message": d.message,
+                })
+            })
+            .collect();
+        let contents = json!({ "source_hash": entry.source_hash, "diagnostics": diagnostics });
+        let temporary = self.dir.join(format!(
+            "{}.{}.{}.tmp",
+            key,
+            std::process::id(),
+            NEXT_TEMPORARY.fetch_add(1, Ordering::Relaxed)
+        ));
+        if fs::write(&temporary, contents.to_string()).is_err()
+            || fs::rename(&temporary, self.path(key)).is_err()
+        {
+            let _ = fs::remove_file(&temporary);
+        }
+    }
+}
+
+fn kind_from_name(name: &str) -> Option<ErrorKind> {
+    [ErrorKind::Syntax, ErrorKind::Type, ErrorKind::Internal]
+        .into_iter()
+        .chain(Limit::ALL.into_iter().map(ErrorKind::LimitExceeded))
+        .find(|kind| kind.to_string() == name)
+}
+
+/// Like `typecheck_contract_with`, for its diagnostics only, which are taken
+/// from `cache` when the contract is found there and recorded there
+/// otherwise.
+pub fn typecheck_contract_cached(
+    cache: &Arc<TcCache>,
+    instructions: &InstructionRegistry,
+    contract: &Contract<SomeValue>,
+    source: &str,
+    session: &mut TcSession,
+) {
+    let key = cache.contract_key(contract, &session.limits);
+    if let Some(entry) = cache.get(&key).filter(|e| e.applies_to(source)) {
+        session.diagnostics.extend(entry.diagnostics);
+        return;
+    }
+    let before = session.diagnostics.len();
+    typecheck_contract_with(cache.protocol, instructions, contract, session);
+    // Whether time runs out depends on more than the code.
+    if session.usage.exceeded != Some(Limit::Time) {
+        cache.put(
+            &key,
+            &CacheEntry {
+                source_hash: digest(&[source]),
+                diagnostics: session.diagnostics[before..].to_vec(),
+            },
+        );
+    }
+}
@@ -0,0 +1,470 @@
+use num_bigint::BigInt;
+use std::sync::Arc;
+
+use crate::micheline::Micheline;
+use crate::micheline::Node;
+use crate::types::ArgValue;
+use crate::types::ArgValue::*;
+use crate::types::AtomicValue::*;
+use crate::types::CompositeValue;
+use crate::types::CompositeValue::*;
+use crate::types::CompoundInstruction;
+use crate::types::CompoundInstruction::*;
+use crate::types::ConcreteType;
+use crate::types::Contract;
+use crate::types::Diagnostic;
+use crate::types::ErrorKind;
+use crate::types::Instruction;
+use crate::types::LocatedInstruction;
+use crate::types::MAtomic;
+use crate::types::MAtomic::*;
+use crate::types::MType::*;
+use crate::types::SomeValue;
+use crate::types::SomeValue::*;
+use crate::types::TypeAnnotations;
+
+// Gives Micheline nodes their meaning as types, values and instructions,
+// whatever format they were read from. Errors are syntax errors located at
+// the node they are found in.
+
+pub(crate) const ATOMIC_TYPES: [MAtomic; 14] = [
+    MChainId, MBytes, MAddress, MNat, MInt, MString, MBool, MKey, MKeyHash, MMutez, MTimestamp,
+    MUnit, MOperation, MSignature,
+];
+
+// Instructions with arguments of their own, which are not type or value
+// arguments.
+const COMPOUND_INSTRUCTIONS: [&str; 20] = [
+    "FAIL",
+    "FAILWITH",
+    "SELF",
+    "IF",
+    "IF_CONS",
+    "IF_SOME",
+    "IF_NONE",
+    "IF_LEFT",
+    "MAP",
+    "ITER",
+    "LOOP",
+    "LOOP_LEFT",
+    "DIP",
+    "PAIR",
+    "DROP",
+    "UNPAIR",
+    "DUP",
+    "DIG",
+    "DUG",
+    "LAMBDA_REC",
+];
+
+fn error(node: &Node, message: String) -> Diagnostic {
+    Diagnostic {
+        location: Some(node.location),
+        kind: ErrorKind::Syntax,
+        message,
+    }
+}
+
+fn prim(node: &Node) -> Option<(&str, &[Node])> {
+    match &node.expr {
+        Micheline::Prim { name, args, .. } => Some((name, args)),
+        _ => None,
+    }
+}
+
+// Instructions are named in capitals, while data constructors are
+// capitalized.
+fn is_instruction(node: &Node) -> bool {
+    prim(node).is_some_and(|(name, _)| {
+        name.starts_with(|c: char| c.is_ascii_uppercase())
+            && name
+                .chars()
+                .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+    })
+}
+
+/// A number literal, which is unbounded as in Michelson. Those of type `mutez`
+/// and `timestamp` are held in 64 bits, and the typechecker rejects the ones
+/// beyond as out of range.
+pub fn elaborate_number(node: &Node) -> Result<BigInt, Diagnostic> {
+    match &node.expr {
+        Micheline::Int(i) => i
+            .parse()
+            .map_err(|_| error(node, format!("Invalid number literal {}", i))),
+        _ => Result::Err(error(
+            node,
+            format!("Expecting a number but found {}", node),
+        )),
+    }
+}
+
+fn elaborate_count(node: &Node) -> Result<usize, Diagnostic> {
+    usize::try_from(&elaborate_number(node)?).map_err(|_| {
+        error(
+            node,
+            format!("Expecting a natural number but found {}", node),
+        )
+    })
+}
+
+pub fn elaborate_string(node: &Node) -> Result<String, Diagnostic> {
+    match &node.expr {
+        Micheline::String(s) => Result::Ok(s.clone()),
+        _ => Result::Err(error(
+            node,
+            format!("Expecting a string but found {}", node),
+        )),
+    }
+}
+
+pub fn elaborate_type(node: &Node) -> Result<ConcreteType, Diagnostic> {
+    Result::Ok(elaborate_annotated_type(node)?.0)
+}
+
+/// Like `elaborate_type`, but keeps the annotations, for the toplevel types
+/// of contracts.
+pub fn elaborate_annotated_type(
+    node: &Node,
+) -> Result<(ConcreteType, TypeAnnotations), Diagnostic> {
+    let Micheline::Prim { name, args, annots } = &node.expr else {
+        return Result::Err(error(node, format!("Expecting a type but found {}", node)));
+    };
+    let mut elems = args
+        .iter()
+        .map(elaborate_annotated_type)
+        .collect::<Result<Vec<_>, _>>()?;
+    let arity = match name.as_str() {
+        "list" | "set" | "ticket" | "option" | "contract" => 1,
+        // Right combs of more than two elements; the pairs nested in the comb
+        // have no annotations of their own.
+        "pair" if elems.len() > 2 => {
+            let (mut ty, mut annotations) = elems.remove(elems.len() - 1);
+            while let Some((t, ta)) = elems.pop() {
+                let location = ta.location;
+                ty = MPair(Arc::new((t, ty)));
+                annotations = TypeAnnotations {
+                    location,
+                    annotations: vec![],
+                    args: vec![ta, annotations],
+                };
+            }
+            annotations.location = node.location;
+            annotations.annotations = annots.clone();
+            return Result::Ok((ty, annotations));
+        }
+        "pair" | "or" | "map" | "big_map" | "lambda" => 2,
+        _ => 0,
+    };
+    if elems.len() != arity {
+        return Result::Err(error(
+            node,
+            format!(
+                "Type {} expects {} arguments but has {}",
+                name,
+                arity,
+                elems.len()
+            ),
+        ));
+    }
+    let (types, args): (Vec<_>, Vec<_>) = elems.into_iter().unzip();
+    let ty = match (name.as_str(), &types[..]) {
+        ("list", [t]) => MList(Arc::new(t.clone())),
+        ("set", [t]) => MSet(Arc::new(t.clone())),
+        ("ticket", [t]) => MTicket(Arc::new(t.clone())),
+        ("option", [t]) => MOption(Arc::new(t.clone())),
+        ("contract", [t]) => MContract(Arc::new(t.clone())),
+        ("pair", [t1, t2]) => MPair(Arc::new((t1.clone(), t2.clone()))),
+        ("or", [t1, t2]) => MOr(Arc::new((t1.clone(), t2.clone()))),
+        ("map", [t1, t2]) => MMap(Arc::new((t1.clone(), t2.clone()))),
+        ("big_map", [t1, t2]) => MBigMap(Arc::new((t1.clone(), t2.clone()))),
+        ("lambda", [t1, t2]) => MLambda(Arc::new((t1.clone(), t2.clone()))),
+        _ => match ATOMIC_TYPES.iter().find(|a| a.to_string() == *name) {
+            Some(a) => MWrapped(a.clone()),
+            None => return Result::Err(error(node, format!("Unknown type {}", name))),
+        },
+    };
+    Result::Ok((
+        ty,
+        TypeAnnotations {
+            location: node.location,
+            annotations: annots.clone(),
+            args,
+        },
+    ))
+}
+
+/// Elaborates a value whose type is not known, in which sequences are kept
+/// as they were read until the typechecker gives them a type.
+pub fn elaborate_value(node: &Node) -> Result<SomeValue, Diagnostic> {
+    value(node, None)
+}
+
+/// Elaborates a value of type `t`, which tells what its sequences hold: the
+/// elements of a list, a set or a pair, the `Elt`s of a map or the code of a
+/// lambda. The type only guides elaboration; whether the value has it is for
+/// the typechecker to tell.
+pub fn elaborate_typed_value(node: &Node, t: &ConcreteType) -> Result<SomeValue, Diagnostic> {
+    value(node, Some(t))
+}
+
+fn value(node: &Node, t: Option<&ConcreteType>) -> Result<SomeValue, Diagnostic> {
+    let composite = |v| Result::Ok(Composite(Box::new(v)));
+    let (name, args) = match &node.expr {
+        Micheline::Int(_) => return Result::Ok(Atomic(AVNumber(elaborate_number(node)?))),
+        Micheline::String(s) => return Result::Ok(Atomic(AVString(s.clone()))),
+        Micheline::Bytes(b) => return Result::Ok(Atomic(AVBytes(b.clone()))),
+        Micheline::Seq(items) => return composite(elaborate_sequence(node, items, t)?),
+        Micheline::Prim { name, args, .. } => (name.as_str(), &args[..]),
+    };
+    let (left, right) = match t {
+        Some(MOr(b)) => (Some(&b.0), Some(&b.1)),
+        _ => (None, None),
+    };
+    match (name, args) {
+        ("Unit", []) => Result::Ok(Atomic(AVUnit)),
+        ("True", []) => Result::Ok(Atomic(AVBool(true))),
+        ("False", []) => Result::Ok(Atomic(AVBool(false))),
+        ("None", []) => composite(CVNone),
+        ("Some", [v]) => composite(CVSome(value(
+            v,
+            match t {
+                Some(MOption(a)) => Some(a.as_ref()),
+                _ => None,
+            },
+        )?)),
+        ("Left", [v]) => composite(CVLeft(value(v, left)?)),
+        ("Right", [v]) => composite(CVRight(value(v, right)?)),
+        // `Pair a b c` is a right comb, `Pair a (Pair b c)`.
+        ("Pair", [_, _, ..]) => {
+            let mut values = comb(args, t)?;
+            let last = values.pop().unwrap_or(Atomic(AVUnit));
+            Result::Ok(
+                values
+                    .into_iter()
+                    .rev()
+                    .fold(last, |acc, v| Composite(Box::new(CVPair(v, acc)))),
+            )
+        }
+        _ => Result::Err(error(node, format!("Unexpected {} in a value", node))),
+    }
+}
+
+// The elements of a comb, each with its type in the comb of type `t`, as far
+// as `t` is one.
+fn comb(nodes: &[Node], mut t: Option<&ConcreteType>) -> Result<Vec<SomeValue>, Diagnostic> {
+    let mut values = vec![];
+    for (k, node) in nodes.iter().enumerate() {
+        let element = match t {
+            Some(MPair(b)) if k + 1 < nodes.len() => {
+                t = Some(&b.1);
+                Some(&b.0)
+            }
+            _ if k + 1 < nodes.len() => {
+                t = None;
+                None
+            }
+            _ => t,
+        };
+        values.push(value(node, element)?);
+    }
+    Result::Ok(values)
+}
+
+fn elaborate_sequence(
+    node: &Node,
+    items: &[Node],
+    t: Option<&ConcreteType>,
+) -> Result<CompositeValue, Diagnostic> {
+    match t {
+        Some(MList(a) | MSet(a)) => Result::Ok(CVSeq(
+            items
+                .iter()
+                .map(|i| value(i, Some(a)))
+                .collect::<Result<_, _>>()?,
+        )),
+        Some(MMap(b) | MBigMap(b)) => {
+            let mut elts = vec![];
+            for item in items {
+                match prim(item) {
+                    Some(("Elt", [k, v])) => {
+                        elts.push((value(k, Some(&b.0))?, value(v, Some(&b.1))?))
+                    }
+                    _ => {
+                        return Result::Err(error(
+                            item,
+                            format!("Expecting Elt but found {}", item),
+                        ))
+                    }
+                }
+            }
+            Result::Ok(CKVList(elts))
+        }
+        Some(MLambda(_)) => Result::Ok(CVLambda(elaborate_code(items)?)),
+        // `{ a ; b ; c }` is `Pair a b c`.
+        Some(MPair(_)) => Result::Ok(CVSeq(comb(items, t)?)),
+        _ => Result::Ok(CVRaw(node.clone())),
+    }
+}
+
+// Lowercase primitives are types, everything else is a value.
+fn is_type(node: &Node) -> bool {
+    prim(node).is_some_and(|(name, _)| name.starts_with(|c: char| c.is_ascii_lowercase()))
+}
+
+fn elaborate_arg(node: &Node) -> Result<ArgValue<SomeValue>, Diagnostic> {
+    if is_type(node) {
+        Result::Ok(TypeArg(elaborate_type(node)?))
+    } else {
+        Result::Ok(ValueArg(elaborate_value(node)?))
+    }
+}
+
+// The type of the value that follows `args` among the arguments of an
+// instruction, for the instructions of Michelson that take a value after the
+// types it has. The values of other instructions are given a type when they
+// are typechecked.
+fn value_type(name: &str, args: &[ArgValue<SomeValue>]) -> Option<ConcreteType> {
+    match (name, args) {
+        ("PUSH", [TypeArg(t)]) => Some(t.clone()),
+        ("LAMBDA", [TypeArg(t1), TypeArg(t2)]) => Some(MLambda(Arc::new((t1.clone(), t2.clone())))),
+        _ => None,
+    }
+}
+
+/// Elaborates `{ ... }` as the block of code it holds.
+pub fn elaborate_block(node: &Node) -> Result<Vec<LocatedInstruction<SomeValue>>, Diagnostic> {
+    match &node.expr {
+        Micheline::Seq(items) => elaborate_code(items),
+        _ => Result::Err(error(
+            node,
+            format!("Expecting a block of code but found {}", node),
+        )),
+    }
+}
+
+pub fn elaborate_code(nodes: &[Node]) -> Result<Vec<LocatedInstruction<SomeValue>>, Diagnostic> {
+    nodes
+        .iter()
+        .map(|node| {
+            Result::Ok(LocatedInstruction {
+                location: node.location,
+                instruction: elaborate_instruction(node)?,
+            })
+        })
+        .collect()
+}
+
+pub fn elaborate_instruction(node: &Node) -> Result<CompoundInstruction<SomeValue>, Diagnostic> {
+    if let Micheline::Seq(items) = &node.expr {
+        return Result::Ok(SEQ(elaborate_code(items)?));
+    }
+    let Some((name, args)) = prim(node).filter(|_| is_instruction(node)) else {
+        return Result::Err(error(
+            node,
+            format!("Expecting an instruction but found {}", node),
+        ));
+    };
+    // The count of instructions such as `DROP 2`, when it is given.
+    let count = |default| match args {
+        [n] => elaborate_count(n),
+        _ => Result::Ok(default),
+    };
+    let instruction = match (name, args) {
+        ("FAIL", []) => FAIL,
+        ("FAILWITH", []) => FAILWITH,
+        ("SELF", []) => SELF,
+        ("IF", [t, f]) => IF(elaborate_block(t)?, elaborate_block(f)?),
+        ("IF_CONS", [t, f]) => IF_CONS(elaborate_block(t)?, elaborate_block(f)?),
+        ("IF_SOME", [t, f]) => IF_SOME(elaborate_block(t)?, elaborate_block(f)?),
+        ("IF_NONE", [t, f]) => IF_NONE(elaborate_block(t)?, elaborate_block(f)?),
+        ("IF_LEFT", [t, f]) => IF_LEFT(elaborate_block(t)?, elaborate_block(f)?),
+        ("MAP", [b]) => MAP(elaborate_block(b)?),
+        ("ITER", [b]) => ITER(elaborate_block(b)?),
+        ("LOOP", [b]) => LOOP(elaborate_block(b)?),
+        ("LOOP_LEFT", [b]) => LOOP_LEFT(elaborate_block(b)?),
+        ("DIP", [b]) => DIP(1, elaborate_block(b)?),
+        ("DIP", [n, b]) => DIP(elaborate_count(n)?, elaborate_block(b)?),
+        ("PAIR", [] | [_]) => PAIR(count(2)?),
+        ("UNPAIR", [] | [_]) => UNPAIR(count(2)?),
+        ("DROP", [] | [_]) => DROP(count(1)?),
+        ("DUP", [] | [_]) => DUP(count(1)?),
+        ("DIG", [] | [_]) => DIG(count(1)?),
+        ("DUG", [] | [_]) => DUG(count(1)?),
+        ("GET", [n]) => GET(elaborate_count(n)?),
+        ("UPDATE", [n]) => UPDATE(elaborate_count(n)?),
+        ("LAMBDA_REC", [t1, t2, b]) => LAMBDA_REC(
+            elaborate_type(t1)?,
+            elaborate_type(t2)?,
+            elaborate_block(b)?,
+        ),
+        // `DIIP { ... }` is `DIP 2 { ... }`, and so on.
+        (_, [b]) if name.len() > 3 && name[1..name.len() - 1].chars().all(|c| c == 'I') => {
+            DIP(name.len() - 2, elaborate_block(b)?)
+        }
+        _ if COMPOUND_INSTRUCTIONS.contains(&name)
+            || name.len() > 3 && name[1..name.len() - 1].chars().all(|c| c == 'I') =>
+        {
+            return Result::Err(error(
+                node,
+                format!("Wrong number of arguments for {}", name),
+            ));
+        }
+        _ => {
+            let mut elaborated = vec![];
+            for arg in args {
+                let arg = match value_type(name, &elaborated) {
+                    Some(t) if !is_type(arg) => ValueArg(elaborate_typed_value(arg, &t)?),
+                    _ => elaborate_arg(arg)?,
+                };
+                elaborated.push(arg);
+            }
+            Other(Instruction {
+                name: name.to_string(),
+                args: elaborated,
+            })
+        }
+    };
+    Result::Ok(instruction)
+}
+
+/// Elaborates the toplevel of a contract, its `parameter`, `storage` and
+/// `code` sections, in any order.
+pub fn elaborate_contract(nodes: &[Node]) -> Result<Contract<SomeValue>, Diagnostic> {
+    let (mut parameter, mut storage, mut code) = (None, None, None);
+    for node in nodes {
+        let (name, duplicate) = match prim(node) {
+            Some((name @ "parameter", [t])) => (
+                name,
+                parameter.replace(elaborate_annotated_type(t)?).is_some(),
+            ),
+            Some((name @ "storage", [t])) => (
+                name,
+                storage.replace(elaborate_annotated_type(t)?).is_some(),
+            ),
+            Some((name @ "code", [b])) => (name, code.replace(elaborate_block(b)?).is_some()),
+            _ => {
+                return Result::Err(error(
+                    node,
+                    format!("Expecting a contract section but found {}", node),
+                ))
+            }
+        };
+        if duplicate {
+            return Result::Err(error(node, format!("Duplicate section {}", name)));
+        }
+    }
+    let missing = |name| Diagnostic {
+        location: None,
+        kind: ErrorKind::Syntax,
+        message: format!("Missing section {}", name),
+    };
+    let (parameter, parameter_annotations) = parameter.ok_or_else(|| missing("parameter"))?;
+    let (storage, storage_annotations) = storage.ok_or_else(|| missing("storage"))?;
+    Result::Ok(Contract {
+        parameter,
+        storage,
+        code: code.ok_or_else(|| missing("code"))?,
+        parameter_annotations,
+        storage_annotations,
+    })
+}
@@ -0,0 +1,118 @@
+// Binary forms of the base58check encoded values of Michelson. These are the
+// forms the protocol compares, so `COMPARE` on addresses, keys and the like is
+// defined in terms of them.
+
+// Base58 prefix, length of the payload after it, and tag of the payload in the
+// binary form.
+type Prefix = (&'static [u8], usize, u8);
+
+const KEY_HASH_PREFIXES: [Prefix; 4] = [
+    (&[6, 161, 159], 20, 0), // tz1
+    (&[6, 161, 161], 20, 1), // tz2
+    (&[6, 161, 164], 20, 2), // tz3
+    (&[6, 161, 166], 20, 3), // tz4
+];
+
+// Addresses of contracts are tagged after implicit accounts, whose tag is 0.
+const CONTRACT_PREFIXES: [Prefix; 3] = [
+    (&[2, 90, 121], 20, 1),      // KT1
+    (&[1, 128, 120, 31], 20, 2), // txr1
+    (&[6, 124, 117], 20, 3),     // sr1
+];
+
+const KEY_PREFIXES: [Prefix; 4] = [
+    (&[13, 15, 37, 217], 32, 0),  // edpk
+    (&[3, 254, 226, 86], 33, 1),  // sppk
+    (&[3, 178, 139, 127], 33, 2), // p2pk
+    (&[6, 149, 135, 204], 48, 3), // BLpk
+];
+
+// Signatures have no tag, the generic `sig` prefix has the same binary form as
+// the curve specific ones.
+const SIGNATURE_PREFIXES: [Prefix; 5] = [
+    (&[9, 245, 205, 134, 18], 64, 0), // edsig
+    (&[13, 115, 101, 19, 63], 64, 0), // spsig1
+    (&[54, 240, 44, 52], 64, 0),      // p2sig
+    (&[4, 130, 43], 64, 0),           // sig
+    (&[40, 171, 64, 207], 96, 0),     // BLsig
+];
+
+const CHAIN_ID_PREFIXES: [Prefix; 1] = [(&[87, 82, 0], 4, 0)]; // Net
+
+fn decode(s: &str, prefixes: &[Prefix]) -> Option<(u8, Vec<u8>)> {
+    let bytes = bs58::decode(s).with_check(None).into_vec().ok()?;
+    prefixes.iter().find_map(|(prefix, len, tag)| {
+        bytes
+            .strip_prefix(*prefix)
+            .filter(|payload| payload.len() == *len)
+            .map(|payload| (*tag, payload.to_vec()))
+    })
+}
+
+pub fn key_hash_to_bytes(s: &str) -> Option<Vec<u8>> {
+    let (tag, mut payload) = decode(s, &KEY_HASH_PREFIXES)?;
+    payload.insert(0, tag);
+    Some(payload)
+}
+
+/// Binary form of an address, and its entrypoint if it has one.
+pub fn address_to_bytes(s: &str) -> Option<(Vec<u8>, String)> {
+    let (address, entrypoint) = match s.split_once('%') {
+        Some((a, e)) if !e.is_empty() => (a, e),
+        Some(_) => return None,
+        None => (s, ""),
+    };
+    let bytes = match key_hash_to_bytes(address) {
+        Some(mut b) => {
+            b.insert(0, 0);
+            b
+        }
+        None => {
+            let (tag, mut payload) = decode(address, &CONTRACT_PREFIXES)?;
+            payload.insert(0, tag);
+            payload.push(0);
+            payload
+        }
+    };
+    Some((bytes, entrypoint.to_string()))
+}
+
+pub fn key_to_bytes(s: &str) -> Option<Vec<u8>> {
+    let (tag, mut payload) = decode(s, &KEY_PREFIXES)?;
+    payload.insert(0, tag);
+    Some(payload)
+}
+
+pub fn signature_to_bytes(s: &str) -> Option<Vec<u8>> {
+    decode(s, &SIGNATURE_PREFIXES).map(|(_, payload)| payload)
+}
+
+pub fn chain_id_to_bytes(s: &str) -> Option<Vec<u8>> {
+    decode(s, &CHAIN_ID_PREFIXES).map(|(_, payload)| payload)
+}
+
+pub fn bytes_to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+pub fn hex_to_bytes(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Timestamps in the RFC 3339 form used by the reference implementation.
+pub fn timestamp_to_string(seconds: i64) -> Option<String> {
+    chrono::DateTime::from_timestamp(seconds, 0)
+        .map(|t| t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
+}
+
+pub fn timestamp_from_string(s: &str) -> Option<i64> {
+    chrono::DateTime::parse_from_rfc3339(s)
+        .ok()
+        .map(|t| t.timestamp())
+}
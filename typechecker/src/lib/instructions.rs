@@ -1,197 +1,136 @@
 use std::collections::BTreeMap;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::LazyLock;
 
-use crate::parsers::parse_constraints;
-use crate::parsers::parse_stack_results;
+use crate::parsers::constraints_from_signature;
+use crate::parsers::stack_results_from_signature;
+use crate::parsers::MAX_NESTING_DEPTH;
+use crate::signature::parse_signature_file;
+use crate::types::ArgConstraint;
+use crate::types::Attribute;
+use crate::types::Diagnostic;
+use crate::types::ErrorKind;
 use crate::types::InstructionDef;
+use crate::types::MAtomic;
+use crate::types::MType;
+use crate::types::StackResultElem;
 
-macro_rules! mk_instr {
-    ($n:expr, $arg: expr, $is: expr, $os: expr) => {
-        (
-            String::from($n),
-            vec![InstructionDef {
-                args: parse_constraints($arg),
-                input_stack: parse_constraints($is),
-                output_stack: parse_stack_results($os),
-            }],
-        )
-    };
+/// Signatures of the instructions that are typechecked from a table, by
+/// name. An instruction can have several variants, which are tried in order.
+#[derive(Debug, Clone)]
+pub struct InstructionRegistry {
+    instructions: BTreeMap<String, Vec<InstructionDef>>,
+    // Tables with the same version have the same signatures: a table gets a
+    // new version when it is made or changed, and keeps it when cloned.
+    version: u64,
 }
 
-macro_rules! mk_instr_poly {
-    ($n:expr, $(($arg: expr, $is: expr, $os: expr)),*) => {
-        (String::from($n), vec![$(InstructionDef {args: parse_constraints($arg), input_stack: parse_constraints($is), output_stack: parse_stack_results($os)} ), *])
-    };
+static NEXT_VERSION: AtomicU64 = AtomicU64::new(0);
+
+fn next_version() -> u64 {
+    NEXT_VERSION.fetch_add(1, Ordering::Relaxed)
+}
+
+impl Default for InstructionRegistry {
+    fn default() -> Self {
+        InstructionRegistry {
+            instructions: BTreeMap::new(),
+            version: next_version(),
+        }
+    }
+}
+
+impl InstructionRegistry {
+    /// A registry with the signatures of `MICHELSON_INSTRUCTIONS`, to extend
+    /// or override.
+    pub fn with_defaults() -> InstructionRegistry {
+        MICHELSON_INSTRUCTIONS.clone()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Vec<InstructionDef>> {
+        self.instructions.get(name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Vec<InstructionDef>)> {
+        self.instructions.iter()
+    }
+
+    pub(crate) fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Adds a variant to an instruction, tried after its existing ones.
+    pub fn add(&mut self, name: &str, def: InstructionDef) {
+        self.version = next_version();
+        self.instructions
+            .entry(name.to_string())
+            .or_default()
+            .push(def);
+    }
+
+    /// Replaces every variant of an instruction.
+    pub fn set(&mut self, name: &str, defs: Vec<InstructionDef>) {
+        self.version = next_version();
+        self.instructions.insert(name.to_string(), defs);
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Vec<InstructionDef>> {
+        self.version = next_version();
+        self.instructions.remove(name)
+    }
+
+    /// Loads a signature file, see `parse_signatures`. The instructions it
+    /// defines replace any existing definition of the same name.
+    pub fn load(&mut self, src: &str) -> Result<(), Diagnostic> {
+        let mut loaded: BTreeMap<String, Vec<InstructionDef>> = BTreeMap::new();
+        for (name, def) in parse_signatures(src)? {
+            loaded.entry(name).or_default().push(def);
+        }
+        self.version = next_version();
+        self.instructions.extend(loaded);
+        Result::Ok(())
+    }
 }
 
-lazy_static! {
-    pub static ref MICHELSON_INSTRUCTIONS: BTreeMap<String, Vec<InstructionDef>> =
-        BTreeMap::from([
-            mk_instr!(
-                "APPLY",
-                "",
-                "<w|a>;lambda (pair <r|a> <w|b>) <w|c>",
-                "lambda <r|b> <r|c>"
-            ),
-            mk_instr!("CAR", "", "pair <w|a> <w|b>", "<r|a>"),
-            mk_instr!("CDR", "", "pair <w|a> <w|b>", "<r|b>"),
-            mk_instr!("HASH_KEY", "", "<a|key>", "<a|key_hash>"),
-            mk_instr!("IMPLICIT_ACCOUNT", "", "<a|key_hash>", "contract <a|unit>"),
-            mk_instr!("SWAP", "", "<w|a>;<w|b>", "<r|b>;<r|a>"),
-            mk_instr!("NIL", "<t|a>", "", "list <r|a>"),
-            mk_instr!("CAST", "", "", ""),
-            mk_instr!(
-                "VIEW",
-                "<a|string>;<t|a>",
-                "<w|b>;<a|address>",
-                "option <r|a>"
-            ),
-            mk_instr!("SENDER", "", "", "<a|address>"),
-            mk_instr!("EMPTY_BIG_MAP", "<t|a>;<t|b>", "", "big_map <r|a> <r|b>"),
-            mk_instr_poly!(
-                "NOT",
-                ("", "<a|bool>", "<a|bool>"),
-                ("", "<a|nat>", "<a|int>"),
-                ("", "<a|int>", "<a|int>"),
-                ("", "<a|bytes>", "<a|bytes>")
-            ),
-            mk_instr_poly!(
-                "MEM",
-                ("", "<w|k>;set <r|k>", "<a|bool>"),
-                ("", "<w|k>;map <r|k> <w|b>", "<a|bool>"),
-                ("", "<w|k>;big_map <r|k> <w|b>", "<a|bool>")
-            ),
-            mk_instr_poly!(
-                "MUL",
-                ("", "<a|int>;<a|nat>", "<a|int>"),
-                ("", "<a|nat>;<a|mutez>", "<a|mutez>"),
-                ("", "<a|mutez>;<a|nat>", "<a|mutez>"),
-                ("", "<a|int>;<a|int>", "<a|int>"),
-                ("", "<a|nat>;<a|nat>", "<a|nat>"),
-                ("", "<a|nat>;<a|int>", "<a|int>")
-            ),
-            mk_instr_poly!(
-                "SIZE",
-                ("", "set <w|b>", "<a|nat>"),
-                ("", "map <w|a> <w|b>", "<a|nat>"),
-                ("", "list <w|a>", "<a|nat>"),
-                ("", "<a|string>", "<a|nat>"),
-                ("", "<a|bytes>", "<a|nat>")
-            ),
-            mk_instr_poly!(
-                "ADD",
-                ("", "<a|mutez>;<a|mutez>", "<a|mutez>"),
-                ("", "<a|int>;<a|timestamp>", "<a|timestamp>"),
-                ("", "<a|timestamp>;<a|int>", "<a|timestamp>"),
-                ("", "<a|int>;<a|nat>", "<a|int>"),
-                ("", "<a|nat>;<a|int>", "<a|int>"),
-                ("", "<a|nat>;<a|nat>", "<a|nat>"),
-                ("", "<a|int>;<a|int>", "<a|int>")
-            ),
-            mk_instr_poly!(
-                "AND",
-                ("", "<a|bool>;<a|bool>", "<a|bool>"),
-                ("", "<a|nat>;<a|nat>", "<a|nat>"),
-                ("", "<a|int>;<a|nat>", "<a|nat>"),
-                ("", "<a|bytes>;<a|bytes>", "<a|bytes>")
-            ),
-            mk_instr_poly!(
-                "SUB",
-                ("", "<a|nat>;<a|nat>", "<a|int>"),
-                ("", "<a|int>;<a|int>", "<a|int>"),
-                ("", "<a|int>;<a|nat>", "<a|int>"),
-                ("", "<a|nat>;<a|int>", "<a|int>"),
-                ("", "<a|timestamp>;<a|int>", "<a|timestamp>"),
-                ("", "<a|timestamp>;<a|timestamp>", "<a|int>"),
-                ("", "<a|mutez>;<a|mutez>", "option <a|mutez>")
-            ),
-            mk_instr_poly!(
-                "OR",
-                ("", "<a|bool>;<a|bool>", "<a|bool>"),
-                ("", "<a|nat>;<a|nat>", "<a|nat>"),
-                ("", "<a|bytes>;<a|bytes>", "<a|bytes>")
-            ),
-            mk_instr_poly!(
-                "XOR",
-                ("", "<a|bool>;<a|bool>", "<a|bool>"),
-                ("", "<a|nat>;<a|nat>", "<a|nat>"),
-                ("", "<a|bytes>;<a|bytes>", "<a|bytes>")
-            ),
-            mk_instr_poly!(
-                "GET",
-                ("", "<w|k>;map <r|k> <w|v>", "option <r|v>"),
-                ("", "<w|k>;big_map <r|k> <w|v>", "option <r|v>")
-            ),
-            mk_instr_poly!(
-                "EDIV",
-                ("", "<a|nat>;<a|nat>", "option (pair <a|nat> <a|nat>)"),
-                ("", "<a|nat>;<a|int>", "option (pair <a|int> <a|nat>)"),
-                ("", "<a|int>;<a|nat>", "option (pair <a|int> <a|nat>)"),
-                ("", "<a|int>;<a|int>", "option (pair <a|int> <a|nat>)"),
-                ("", "<a|mutez>;<a|nat>", "option (pair <a|mutez> <a|mutez>)"),
-                ("", "<a|mutez>;<a|mutez>", "option (pair <a|nat> <a|mutez>)")
-            ),
-            mk_instr_poly!("INT", ("", "<a|nat>", "<a|int>")),
-            mk_instr!("SOME", "", "<w|a>", "option <r|a>"),
-            mk_instr!("NONE", "<t|a>", "", "option <r|a>"),
-            mk_instr_poly!(
-                "UPDATE",
-                ("", "<w|k>;option <w|v>;map <r|k> <r|v>", "map <r|k> <r|v>"),
-                (
-                    "",
-                    "<w|k>;option <w|v>;big_map <r|k> <r|v>",
-                    "big_map <r|k> <r|v>"
-                ),
-                ("", "<w|k>;<a|bool>;set <r|k>", "set <r|k>")
-            ),
-            mk_instr!("CONS", "", "<w|a>;list <r|a>", "list <r|a>"),
-            mk_instr!("LEFT", "<t|a>", "<w|b>", "or <r|b> <r|a>"),
-            mk_instr!("RIGHT", "<t|a>", "<w|b>", "or <r|a> <r|b>"),
-            mk_instr!(
-                "CONTRACT",
-                "<t|a>",
-                "<a|address>",
-                "option (contract <r|a>)"
-            ),
-            mk_instr!("BLAKE2B", "", "<a|bytes>", "<a|bytes>"),
-            mk_instr!("PUSH", "<t|a=>pushable>;<r|a>", "", "<r|a>"),
-            mk_instr!("ADDRESS", "", "contract <w|a>", "<a|address>"),
-            mk_instr!("CHAIN_ID", "", "", "<a|chain_id>"),
-            mk_instr!("EQ", "", "<a|int>", "<a|bool>"),
-            mk_instr!("GE", "", "<a|int>", "<a|bool>"),
-            mk_instr!("GT", "", "<a|int>", "<a|bool>"),
-            mk_instr!("NEQ", "", "<a|int>", "<a|bool>"),
-            mk_instr!("ISNAT", "", "<a|int>", "option <a|nat>"),
-            mk_instr!("LEVEL", "", "", "<a|nat>"),
-            mk_instr!("COMPARE", "", "<w|a=>comparable>;<r|a>", "<a|int>"),
-            mk_instr!("LT", "", "<a|int>", "<a|bool>"),
-            mk_instr!(
-                "CHECK_SIGNATURE",
-                "",
-                "<a|key>;<a|signature>;<a|bytes>",
-                "<a|bool>"
-            ),
-            mk_instr!("PACK", "", "<w|a>", "<a|bytes>"),
-            mk_instr!("UNPACK", "<t|a>", "<a|bytes>", "option <r|a>"),
-            mk_instr!("SELF_ADDRESS", "", "", "<a|address>"),
-            mk_instr!("SOURCE", "", "", "<a|address>"),
-            mk_instr!("AMOUNT", "", "", "<a|mutez>"),
-            mk_instr!("UNIT", "", "", "<a|unit>"),
-            mk_instr!(
-                "TRANSFER_TOKENS",
-                "",
-                "<w|a>;<a|mutez>;contract <r|a>",
-                "<a|operation>"
-            ),
-            mk_instr!("SET_DELEGATE", "", "option <a|key_hash>", "<a|operation>"),
-            mk_instr!(
-                "LAMBDA",
-                "<t|a>;<t|b>;lambda <r|a> <r|b>",
-                "",
-                "lambda <r|a> <r|b>"
-            ),
-            mk_instr!("EXEC", "", "<w|a>;lambda <r|a> <w|b>", "<r|b>"),
-            mk_instr!("ASSERT", "", "<a|bool>", ""),
-            mk_instr!("ASSERT_CMPEQ", "", "<w|a>;<w|b>", ""),
-            mk_instr!("ASSERT_CMPLE", "", "<w|a>;<w|b>", ""),
-        ]);
+/// Parses instruction signatures written in the notation of
+/// src/lib/instructions.sig, one variant per line:
+///
+/// ```text
+/// # Comments start with a hash.
+/// NAME <t|a> :: <w|b=>comparable>;list <r|b> -> option <r|a>
+/// ```
+///
+/// The type arguments before `::` can be left out, and either stack can be
+/// empty.
+pub fn parse_signatures(src: &str) -> Result<Vec<(String, InstructionDef)>, Diagnostic> {
+    let error = |location: usize, message: String| Diagnostic {
+        location: Some(location),
+        kind: ErrorKind::Syntax,
+        message,
+    };
+    let signatures = parse_signature_file(src, MAX_NESTING_DEPTH).map_err(|(l, e)| error(l, e))?;
+    signatures
+        .into_iter()
+        .map(|(l, s)| {
+            let def = InstructionDef {
+                args: constraints_from_signature(&s.args).map_err(|e| error(l, e))?,
+                input_stack: constraints_from_signature(&s.input).map_err(|e| error(l, e))?,
+                output_stack: stack_results_from_signature(&s.output).map_err(|e| error(l, e))?,
+            };
+            Result::Ok((s.name, def))
+        })
+        .collect()
 }
+
+/// The signatures of the instructions of Michelson, from
+/// src/lib/instructions.sig. They are parsed and checked by the build
+/// script, which generates the code that builds the table; types share their
+/// parts through `Arc`s, so the table is built when it is first used rather
+/// than being a constant.
+pub static MICHELSON_INSTRUCTIONS: LazyLock<InstructionRegistry> =
+    LazyLock::new(|| InstructionRegistry {
+        instructions: include!(concat!(env!("OUT_DIR"), "/instructions.rs")),
+        version: next_version(),
+    });
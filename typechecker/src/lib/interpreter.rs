@@ -0,0 +1,721 @@
+use num_bigint::BigInt;
+use num_bigint::Sign;
+use num_traits::Euclid;
+use num_traits::Zero;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::types::ArgValue;
+use crate::types::CompoundInstruction::*;
+use crate::types::Contract;
+use crate::types::Instruction;
+use crate::types::LocatedInstruction;
+use crate::types::MValue;
+use crate::types::MValue::*;
+use crate::types::MAX_MUTEZ;
+
+// Execution of typechecked code. Only code that went through the typechecker
+// is run, so values of the wrong shape are reported as `Malformed` instead of
+// being checked for at every step.
+
+/// Chain state visible to the code being run, and what is left of the bounds
+/// on running it. The instructions that read an address or chain id left out
+/// cannot be run.
+#[derive(Debug, Clone)]
+pub struct RunContext {
+    pub amount: i64,
+    pub balance: i64,
+    pub level: u32,
+    // Seconds since the epoch.
+    pub now: i64,
+    pub self_address: Option<String>,
+    pub source: Option<String>,
+    pub sender: Option<String>,
+    pub chain_id: Option<String>,
+    // Steps that can still be taken, one for each instruction and block run,
+    // and how much deeper blocks and lambdas can still nest. Running past
+    // either stops with `RuntimeError::Exhausted`.
+    pub fuel: u64,
+    pub depth: usize,
+}
+
+/// Steps a run can take by default, enough for any test but bounding loops
+/// that do not end.
+pub const DEFAULT_FUEL: u64 = 1_000_000;
+
+/// How deep blocks and lambdas can nest while running, within what the native
+/// stack of a 2MB thread survives.
+pub const MAX_RUN_DEPTH: usize = 100;
+
+impl Default for RunContext {
+    fn default() -> Self {
+        RunContext {
+            amount: 0,
+            balance: 0,
+            level: 0,
+            now: 0,
+            self_address: None,
+            source: None,
+            sender: None,
+            chain_id: None,
+            fuel: DEFAULT_FUEL,
+            depth: MAX_RUN_DEPTH,
+        }
+    }
+}
+
+/// The overflow and underflow errors of the reference implementation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowKind {
+    MutezOverflow,
+    MutezUnderflow,
+    GeneralOverflow,
+}
+
+impl std::fmt::Display for OverflowKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuntimeError {
+    Failed(MValue),
+    // The kind of overflow and the operands it happened on.
+    Overflow(OverflowKind, BigInt, BigInt),
+    Unsupported(String),
+    Malformed(String),
+    // The run ran out of fuel or depth.
+    Exhausted(String),
+}
+
+impl std::fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RuntimeError::Failed(v) => write!(f, "Script failed with {:?}", v),
+            RuntimeError::Overflow(kind, x, y) => write!(f, "{} {} {}", kind, x, y),
+            RuntimeError::Unsupported(i) => write!(f, "Instruction {} cannot be run", i),
+            RuntimeError::Malformed(i) => write!(f, "Unexpected stack for {}", i),
+            RuntimeError::Exhausted(r) => write!(f, "Run stopped: out of {}", r),
+        }
+    }
+}
+
+// The top of the stack is the last element.
+type Stack = Vec<MValue>;
+
+fn pop(stack: &mut Stack, name: &str) -> Result<MValue, RuntimeError> {
+    stack
+        .pop()
+        .ok_or_else(|| RuntimeError::Malformed(name.to_string()))
+}
+
+fn malformed<T>(name: &str) -> Result<T, RuntimeError> {
+    Result::Err(RuntimeError::Malformed(name.to_string()))
+}
+
+fn from_context(field: &Option<String>, name: &str) -> Result<String, RuntimeError> {
+    field
+        .clone()
+        .ok_or_else(|| RuntimeError::Unsupported(name.to_string()))
+}
+
+fn bool_of(v: MValue, name: &str) -> Result<bool, RuntimeError> {
+    match v {
+        VBool(b) => Result::Ok(b),
+        _ => malformed(name),
+    }
+}
+
+fn overflow(kind: OverflowKind, x: &BigInt, y: &BigInt) -> RuntimeError {
+    RuntimeError::Overflow(kind, x.clone(), y.clone())
+}
+
+fn int_of(v: &MValue) -> Option<BigInt> {
+    match v {
+        VNat(n) => Some(BigInt::from(n.clone())),
+        VMutez(n) => Some(BigInt::from(*n)),
+        VInt(i) => Some(i.clone()),
+        VTimestamp(t) => Some(BigInt::from(*t)),
+        _ => None,
+    }
+}
+
+fn to_nat(n: BigInt) -> Option<MValue> {
+    n.to_biguint().map(VNat)
+}
+
+fn to_mutez(n: BigInt) -> Option<MValue> {
+    i64::try_from(&n)
+        .ok()
+        .filter(|n| (0..=MAX_MUTEZ).contains(n))
+        .map(VMutez)
+}
+
+fn to_timestamp(n: BigInt) -> Option<MValue> {
+    i64::try_from(&n).ok().map(VTimestamp)
+}
+
+// Numbers are unbounded, so only mutez and timestamps can overflow.
+fn arithmetic(name: &str, a: MValue, b: MValue) -> Result<MValue, RuntimeError> {
+    let (x, y) = match (int_of(&a), int_of(&b)) {
+        (Some(x), Some(y)) => (x, y),
+        _ => return malformed(name),
+    };
+    let general = || overflow(OverflowKind::GeneralOverflow, &x, &y);
+    let mutez = || overflow(OverflowKind::MutezOverflow, &x, &y);
+    match (name, &a, &b) {
+        ("ADD", VNat(n), VNat(m)) => Result::Ok(VNat(n + m)),
+        ("ADD", VMutez(_), VMutez(_)) => to_mutez(&x + &y).ok_or_else(mutez),
+        ("ADD", VTimestamp(_), _) | ("ADD", _, VTimestamp(_)) => {
+            to_timestamp(&x + &y).ok_or_else(general)
+        }
+        ("SUB", VTimestamp(_), VTimestamp(_)) => Result::Ok(VInt(&x - &y)),
+        ("SUB", VTimestamp(_), _) => to_timestamp(&x - &y).ok_or_else(general),
+        ("ADD", _, _) => Result::Ok(VInt(&x + &y)),
+        ("SUB", VNat(_) | VInt(_), _) => Result::Ok(VInt(&x - &y)),
+        ("SUB", VMutez(_), VMutez(_)) => {
+            to_mutez(&x - &y).ok_or_else(|| overflow(OverflowKind::MutezUnderflow, &x, &y))
+        }
+        ("SUB_MUTEZ", VMutez(_), VMutez(_)) => Result::Ok(match to_mutez(&x - &y) {
+            Some(v) => VSome(Box::new(v)),
+            None => VNone,
+        }),
+        ("MUL", VNat(n), VNat(m)) => Result::Ok(VNat(n * m)),
+        ("MUL", VMutez(_), _) | ("MUL", _, VMutez(_)) => to_mutez(&x * &y).ok_or_else(mutez),
+        ("MUL", _, _) => Result::Ok(VInt(&x * &y)),
+        ("EDIV", _, _) => {
+            if y.is_zero() {
+                return Result::Ok(VNone);
+            }
+            let (q, r) = (x.div_euclid(&y), x.rem_euclid(&y));
+            let qr = match (&a, &b) {
+                (VNat(_), VNat(_)) => (to_nat(q), to_nat(r)),
+                (VMutez(_), VMutez(_)) => (to_nat(q), to_mutez(r)),
+                (VMutez(_), _) => (to_mutez(q), to_mutez(r)),
+                _ => (Some(VInt(q)), to_nat(r)),
+            };
+            match qr {
+                (Some(q), Some(r)) => Result::Ok(VSome(Box::new(VPair(Box::new((q, r)))))),
+                _ => Result::Err(general()),
+            }
+        }
+        _ => malformed(name),
+    }
+}
+
+fn bitwise(name: &str, a: MValue, b: MValue) -> Result<MValue, RuntimeError> {
+    match (name, a, b) {
+        ("AND", VBool(x), VBool(y)) => Result::Ok(VBool(x && y)),
+        ("OR", VBool(x), VBool(y)) => Result::Ok(VBool(x || y)),
+        ("XOR", VBool(x), VBool(y)) => Result::Ok(VBool(x ^ y)),
+        ("AND", VNat(x), VNat(y)) => Result::Ok(VNat(x & y)),
+        // Negative ints are taken in two's complement, and the nat keeps the
+        // result positive.
+        ("AND", VInt(x), VNat(y)) => match (x & BigInt::from(y)).to_biguint() {
+            Some(n) => Result::Ok(VNat(n)),
+            None => malformed(name),
+        },
+        ("OR", VNat(x), VNat(y)) => Result::Ok(VNat(x | y)),
+        ("XOR", VNat(x), VNat(y)) => Result::Ok(VNat(x ^ y)),
+        _ => malformed(name),
+    }
+}
+
+fn compare_result(name: &str, v: MValue) -> Result<MValue, RuntimeError> {
+    let sign = match v {
+        VInt(i) => i.sign(),
+        _ => return malformed(name),
+    };
+    Result::Ok(VBool(match name {
+        "EQ" => sign == Sign::NoSign,
+        "NEQ" => sign != Sign::NoSign,
+        "LT" => sign == Sign::Minus,
+        "GT" => sign == Sign::Plus,
+        _ => sign != Sign::Minus,
+    }))
+}
+
+fn ordering_to_int(o: Ordering) -> MValue {
+    VInt(BigInt::from(match o {
+        Ordering::Less => -1,
+        Ordering::Equal => 0,
+        Ordering::Greater => 1,
+    }))
+}
+
+fn value_arg(i: &Instruction<MValue>, n: usize) -> Result<MValue, RuntimeError> {
+    match i.args.get(n) {
+        Some(ArgValue::ValueArg(v)) => Result::Ok(v.clone()),
+        _ => malformed(&i.name),
+    }
+}
+
+fn unpair(v: MValue, name: &str) -> Result<(MValue, MValue), RuntimeError> {
+    match v {
+        VPair(b) => Result::Ok(*b),
+        _ => malformed(name),
+    }
+}
+
+fn run_other(
+    i: &Instruction<MValue>,
+    stack: &mut Stack,
+    ctx: &mut RunContext,
+) -> Result<(), RuntimeError> {
+    let name = i.name.as_str();
+    match name {
+        "PUSH" => stack.push(value_arg(i, 1)?),
+        "LAMBDA" => stack.push(value_arg(i, 2)?),
+        "UNIT" => stack.push(VUnit),
+        "NIL" => stack.push(VList(vec![])),
+        "NONE" => stack.push(VNone),
+        "EMPTY_SET" => stack.push(VSet(BTreeSet::new())),
+        "EMPTY_MAP" => stack.push(VMap(BTreeMap::new())),
+        "EMPTY_BIG_MAP" => stack.push(VBigMap(BTreeMap::new())),
+        "AMOUNT" => stack.push(VMutez(ctx.amount)),
+        "BALANCE" => stack.push(VMutez(ctx.balance)),
+        "NOW" => stack.push(VTimestamp(ctx.now)),
+        "SELF_ADDRESS" => stack.push(VAddress(from_context(&ctx.self_address, name)?)),
+        "SOURCE" => stack.push(VAddress(from_context(&ctx.source, name)?)),
+        "SENDER" => stack.push(VAddress(from_context(&ctx.sender, name)?)),
+        "CHAIN_ID" => stack.push(VChainId(from_context(&ctx.chain_id, name)?)),
+        "LEVEL" => stack.push(VNat(ctx.level.into())),
+        "CAST" => {}
+        "SWAP" => {
+            let a = pop(stack, name)?;
+            let b = pop(stack, name)?;
+            stack.push(a);
+            stack.push(b);
+        }
+        "SOME" => {
+            let v = pop(stack, name)?;
+            stack.push(VSome(Box::new(v)));
+        }
+        "LEFT" => {
+            let v = pop(stack, name)?;
+            stack.push(VLeft(Box::new(v)));
+        }
+        "RIGHT" => {
+            let v = pop(stack, name)?;
+            stack.push(VRight(Box::new(v)));
+        }
+        "CAR" | "CDR" => {
+            let (a, b) = unpair(pop(stack, name)?, name)?;
+            stack.push(if name == "CAR" { a } else { b });
+        }
+        "ADD" | "SUB" | "SUB_MUTEZ" | "MUL" | "EDIV" => {
+            let a = pop(stack, name)?;
+            let b = pop(stack, name)?;
+            stack.push(arithmetic(name, a, b)?);
+        }
+        "AND" | "OR" | "XOR" => {
+            let a = pop(stack, name)?;
+            let b = pop(stack, name)?;
+            stack.push(bitwise(name, a, b)?);
+        }
+        "NOT" => match pop(stack, name)? {
+            VBool(b) => stack.push(VBool(!b)),
+            VNat(n) => stack.push(VInt(!BigInt::from(n))),
+            VInt(n) => stack.push(VInt(!n)),
+            _ => return malformed(name),
+        },
+        "INT" => match pop(stack, name)? {
+            VNat(n) => stack.push(VInt(n.into())),
+            _ => return malformed(name),
+        },
+        "ISNAT" => match pop(stack, name)? {
+            VInt(n) => stack.push(match n.to_biguint() {
+                Some(n) => VSome(Box::new(VNat(n))),
+                None => VNone,
+            }),
+            _ => return malformed(name),
+        },
+        "COMPARE" => {
+            let a = pop(stack, name)?;
+            let b = pop(stack, name)?;
+            stack.push(ordering_to_int(a.cmp(&b)));
+        }
+        "EQ" | "NEQ" | "LT" | "GT" | "GE" => {
+            let v = pop(stack, name)?;
+            stack.push(compare_result(name, v)?);
+        }
+        "CONS" => {
+            let v = pop(stack, name)?;
+            match pop(stack, name)? {
+                VList(mut l) => {
+                    l.insert(0, v);
+                    stack.push(VList(l));
+                }
+                _ => return malformed(name),
+            }
+        }
+        "SIZE" => {
+            let n = match pop(stack, name)? {
+                VList(l) => l.len(),
+                VSet(s) => s.len(),
+                VMap(m) => m.len(),
+                VString(s) => s.len(),
+                VBytes(b) => b.len(),
+                _ => return malformed(name),
+            };
+            stack.push(VNat(n.into()));
+        }
+        "MEM" => {
+            let k = pop(stack, name)?;
+            let found = match pop(stack, name)? {
+                VSet(s) => s.contains(&k),
+                VMap(m) | VBigMap(m) => m.contains_key(&k),
+                _ => return malformed(name),
+            };
+            stack.push(VBool(found));
+        }
+        "GET" => {
+            let k = pop(stack, name)?;
+            match pop(stack, name)? {
+                VMap(m) | VBigMap(m) => stack.push(match m.get(&k) {
+                    Some(v) => VSome(Box::new(v.clone())),
+                    None => VNone,
+                }),
+                _ => return malformed(name),
+            }
+        }
+        "UPDATE" => {
+            let k = pop(stack, name)?;
+            let v = pop(stack, name)?;
+            match (pop(stack, name)?, v) {
+                (VSet(mut s), VBool(b)) => {
+                    if b {
+                        s.insert(k);
+                    } else {
+                        s.remove(&k);
+                    }
+                    stack.push(VSet(s));
+                }
+                (VMap(mut m), v) => {
+                    update_map(&mut m, k, v);
+                    stack.push(VMap(m));
+                }
+                (VBigMap(mut m), v) => {
+                    update_map(&mut m, k, v);
+                    stack.push(VBigMap(m));
+                }
+                _ => return malformed(name),
+            }
+        }
+        "ASSERT" => {
+            if !bool_of(pop(stack, name)?, name)? {
+                return Result::Err(RuntimeError::Failed(VUnit));
+            }
+        }
+        "ASSERT_CMPEQ" | "ASSERT_CMPLE" => {
+            let a = pop(stack, name)?;
+            let b = pop(stack, name)?;
+            let holds = match name {
+                "ASSERT_CMPEQ" => a == b,
+                _ => a <= b,
+            };
+            if !holds {
+                return Result::Err(RuntimeError::Failed(VUnit));
+            }
+        }
+        _ => return Result::Err(RuntimeError::Unsupported(name.to_string())),
+    }
+    Result::Ok(())
+}
+
+fn update_map(m: &mut BTreeMap<MValue, MValue>, k: MValue, v: MValue) {
+    match v {
+        VSome(v) => {
+            m.insert(k, *v);
+        }
+        _ => {
+            m.remove(&k);
+        }
+    }
+}
+
+fn run_lambda(
+    code: &[LocatedInstruction<MValue>],
+    arg: MValue,
+    ctx: &mut RunContext,
+) -> Result<MValue, RuntimeError> {
+    let mut stack = vec![arg];
+    run_block(code, &mut stack, ctx)?;
+    pop(&mut stack, "EXEC")
+}
+
+fn get_n(v: MValue, n: usize) -> Result<MValue, RuntimeError> {
+    if n == 0 {
+        return Result::Ok(v);
+    }
+    let (a, b) = unpair(v, "GET")?;
+    if n == 1 {
+        Result::Ok(a)
+    } else {
+        get_n(b, n - 2)
+    }
+}
+
+fn update_n(v: MValue, n: usize, new: MValue) -> Result<MValue, RuntimeError> {
+    if n == 0 {
+        return Result::Ok(new);
+    }
+    let (a, b) = unpair(v, "UPDATE")?;
+    if n == 1 {
+        Result::Ok(VPair(Box::new((new, b))))
+    } else {
+        Result::Ok(VPair(Box::new((a, update_n(b, n - 2, new)?))))
+    }
+}
+
+fn run_one(
+    LocatedInstruction {
+        location,
+        instruction,
+    }: &LocatedInstruction<MValue>,
+    stack: &mut Stack,
+    ctx: &mut RunContext,
+) -> Result<(), RuntimeError> {
+    spend(ctx)?;
+    let name = instruction.name();
+    match instruction {
+        // EXEC is run here rather than in `run_other`, whose frame is large,
+        // so that recursive lambdas can nest deeper.
+        Other(i) if i.name == "EXEC" => {
+            let arg = pop(stack, name)?;
+            match pop(stack, name)? {
+                VLambda(code) => stack.push(run_lambda(&code, arg, ctx)?),
+                _ => return malformed(name),
+            }
+        }
+        Other(i) => run_other(i, stack, ctx)?,
+        FAIL => return Result::Err(RuntimeError::Failed(VUnit)),
+        FAILWITH => return Result::Err(RuntimeError::Failed(pop(stack, name)?)),
+        SELF => return Result::Err(RuntimeError::Unsupported(name.to_string())),
+        DROP(n) => {
+            if stack.len() < *n {
+                return malformed(name);
+            }
+            stack.truncate(stack.len() - n);
+        }
+        DUP(n) => match stack.len().checked_sub(*n).and_then(|k| stack.get(k)) {
+            Some(v) if *n > 0 => stack.push(v.clone()),
+            _ => return malformed(name),
+        },
+        DIG(n) => match stack.len().checked_sub(n + 1) {
+            Some(k) => {
+                let v = stack.remove(k);
+                stack.push(v);
+            }
+            None => return malformed(name),
+        },
+        DUG(n) => match stack.len().checked_sub(n + 1) {
+            Some(k) => {
+                let v = pop(stack, name)?;
+                stack.insert(k, v);
+            }
+            None => return malformed(name),
+        },
+        SEQ(body) => run_block(body, stack, ctx)?,
+        DIP(n, body) => {
+            if stack.len() < *n {
+                return malformed(name);
+            }
+            let top = stack.split_off(stack.len() - n);
+            run_block(body, stack, ctx)?;
+            stack.extend(top);
+        }
+        PAIR(n) => {
+            let mut items = vec![];
+            for _ in 0..*n {
+                items.push(pop(stack, name)?);
+            }
+            // Right combs are built from the deepest element up.
+            let mut v = pop(&mut items, name)?;
+            while let Some(item) = items.pop() {
+                v = VPair(Box::new((item, v)));
+            }
+            stack.push(v);
+        }
+        UNPAIR(n) => {
+            let mut v = pop(stack, name)?;
+            let mut items = vec![];
+            for _ in 1..*n {
+                let (a, b) = unpair(v, name)?;
+                items.push(a);
+                v = b;
+            }
+            stack.push(v);
+            while let Some(a) = items.pop() {
+                stack.push(a);
+            }
+        }
+        GET(n) => {
+            let v = pop(stack, name)?;
+            stack.push(get_n(v, *n)?);
+        }
+        UPDATE(n) => {
+            let new = pop(stack, name)?;
+            let v = pop(stack, name)?;
+            stack.push(update_n(v, *n, new)?);
+        }
+        IF(t, f) => {
+            let branch = if bool_of(pop(stack, name)?, name)? {
+                t
+            } else {
+                f
+            };
+            run_block(branch, stack, ctx)?;
+        }
+        IF_CONS(c, n) => match pop(stack, name)? {
+            VList(mut l) => {
+                if l.is_empty() {
+                    run_block(n, stack, ctx)?;
+                } else {
+                    let head = l.remove(0);
+                    stack.push(VList(l));
+                    stack.push(head);
+                    run_block(c, stack, ctx)?;
+                }
+            }
+            _ => return malformed(name),
+        },
+        IF_NONE(n, s) | IF_SOME(s, n) => match pop(stack, name)? {
+            VSome(v) => {
+                stack.push(*v);
+                run_block(s, stack, ctx)?;
+            }
+            VNone => run_block(n, stack, ctx)?,
+            _ => return malformed(name),
+        },
+        IF_LEFT(l, r) => match pop(stack, name)? {
+            VLeft(v) => {
+                stack.push(*v);
+                run_block(l, stack, ctx)?;
+            }
+            VRight(v) => {
+                stack.push(*v);
+                run_block(r, stack, ctx)?;
+            }
+            _ => return malformed(name),
+        },
+        LOOP(body) => {
+            while bool_of(pop(stack, name)?, name)? {
+                run_block(body, stack, ctx)?;
+            }
+        }
+        LOOP_LEFT(body) => loop {
+            match pop(stack, name)? {
+                VLeft(v) => {
+                    stack.push(*v);
+                    run_block(body, stack, ctx)?;
+                }
+                VRight(v) => {
+                    stack.push(*v);
+                    break;
+                }
+                _ => return malformed(name),
+            }
+        },
+        ITER(body) => {
+            let items: Vec<MValue> = match pop(stack, name)? {
+                VList(l) => l,
+                VSet(s) => s.into_iter().collect(),
+                VMap(m) => m
+                    .into_iter()
+                    .map(|(k, v)| VPair(Box::new((k, v))))
+                    .collect(),
+                _ => return malformed(name),
+            };
+            for item in items {
+                stack.push(item);
+                run_block(body, stack, ctx)?;
+            }
+        }
+        MAP(body) => {
+            let mut map_item = |item: MValue, stack: &mut Stack| {
+                stack.push(item);
+                run_block(body, stack, ctx)?;
+                pop(stack, name)
+            };
+            let result = match pop(stack, name)? {
+                VList(l) => VList(
+                    l.into_iter()
+                        .map(|i| map_item(i, stack))
+                        .collect::<Result<_, _>>()?,
+                ),
+                VSome(v) => VSome(Box::new(map_item(*v, stack)?)),
+                VNone => VNone,
+                VMap(m) => {
+                    let mut out = BTreeMap::new();
+                    for (k, v) in m {
+                        let nv = map_item(VPair(Box::new((k.clone(), v))), stack)?;
+                        out.insert(k, nv);
+                    }
+                    VMap(out)
+                }
+                _ => return malformed(name),
+            };
+            stack.push(result);
+        }
+        LAMBDA_REC(t1, t2, body) => {
+            // The lambda pushes itself under its argument before running the
+            // body, which is what the body of a recursive lambda expects.
+            let located = |instruction| LocatedInstruction {
+                location: *location,
+                instruction,
+            };
+            let mut code = vec![
+                located(LAMBDA_REC(t1.clone(), t2.clone(), body.clone())),
+                located(Other(Instruction {
+                    name: "SWAP".to_string(),
+                    args: vec![],
+                })),
+            ];
+            code.extend(body.iter().cloned());
+            stack.push(VLambda(code));
+        }
+    }
+    Result::Ok(())
+}
+
+pub fn run_block(
+    instructions: &[LocatedInstruction<MValue>],
+    stack: &mut Vec<MValue>,
+    ctx: &mut RunContext,
+) -> Result<(), RuntimeError> {
+    spend(ctx)?;
+    if ctx.depth == 0 {
+        return Result::Err(RuntimeError::Exhausted("depth".to_string()));
+    }
+    ctx.depth -= 1;
+    let result = instructions
+        .iter()
+        .try_for_each(|instruction| run_one(instruction, stack, ctx));
+    ctx.depth += 1;
+    result
+}
+
+fn spend(ctx: &mut RunContext) -> Result<(), RuntimeError> {
+    match ctx.fuel.checked_sub(1) {
+        Some(fuel) => {
+            ctx.fuel = fuel;
+            Result::Ok(())
+        }
+        None => Result::Err(RuntimeError::Exhausted("fuel".to_string())),
+    }
+}
+
+/// Runs a typechecked contract and returns the final `pair (list operation)
+/// storage` value.
+pub fn run_contract(
+    contract: &Contract<MValue>,
+    parameter: MValue,
+    storage: MValue,
+    ctx: &mut RunContext,
+) -> Result<MValue, RuntimeError> {
+    let mut stack = vec![VPair(Box::new((parameter, storage)))];
+    run_block(&contract.code, &mut stack, ctx)?;
+    pop(&mut stack, "code")
+}
@@ -0,0 +1,157 @@
+use std::time::Duration;
+use std::time::Instant;
+
+use crate::types::Diagnostic;
+use crate::types::ErrorKind;
+use crate::types::MType;
+use crate::types::MType::*;
+use crate::types::TcSession;
+
+/// Bounds on the resources spent on untrusted input. Sources are checked
+/// against the first two when they are parsed, and code against the others
+/// while it is typechecked. The defaults do not limit anything, beyond the
+/// limits of the protocol on type sizes and stack depths, which always apply.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    pub max_source_size: usize, // In bytes.
+    // Of string, bytes and number literals, in bytes of source.
+    pub max_literal_size: usize,
+    // Instructions typechecked in the session, those of lambdas included.
+    pub max_instructions: usize,
+    pub max_type_size: usize, // In type constructors.
+    pub max_stack_depth: usize,
+    // Units of work, one per instruction, overload tried and value checked.
+    pub fuel: u64,
+    pub timeout: Option<Duration>,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_source_size: usize::MAX,
+            max_literal_size: usize::MAX,
+            max_instructions: usize::MAX,
+            max_type_size: usize::MAX,
+            max_stack_depth: usize::MAX,
+            fuel: u64::MAX,
+            timeout: None,
+        }
+    }
+}
+
+/// The limit that stopped a session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Ord, PartialOrd, Hash)]
+pub enum Limit {
+    SourceSize,
+    Nesting,
+    LiteralSize,
+    Instructions,
+    TypeSize,
+    StackDepth,
+    Fuel,
+    Time,
+}
+
+impl Limit {
+    pub const ALL: [Limit; 8] = [
+        Limit::SourceSize,
+        Limit::Nesting,
+        Limit::LiteralSize,
+        Limit::Instructions,
+        Limit::TypeSize,
+        Limit::StackDepth,
+        Limit::Fuel,
+        Limit::Time,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Limit::SourceSize => "source size",
+            Limit::Nesting => "nesting",
+            Limit::LiteralSize => "literal size",
+            Limit::Instructions => "instruction count",
+            Limit::TypeSize => "type size",
+            Limit::StackDepth => "stack depth",
+            Limit::Fuel => "fuel",
+            Limit::Time => "time",
+        }
+    }
+}
+
+/// What a session has spent so far.
+#[derive(Debug, Clone, Default)]
+pub struct Usage {
+    pub instructions: usize,
+    pub fuel: u64,
+    pub exceeded: Option<Limit>,
+    started: Option<Instant>,
+}
+
+// How often, in units of fuel, the clock is read.
+const TIME_CHECK_INTERVAL: u64 = 256;
+
+/// Whether `t` has more than `max` type constructors, counting at most
+/// `max + 1` of them.
+pub fn type_size_exceeds<T>(t: &MType<T>, max: usize) -> bool {
+    let mut todo = vec![t];
+    let mut size = 0;
+    while let Some(t) = todo.pop() {
+        size += 1;
+        if size > max {
+            return true;
+        }
+        match t {
+            MPair(b) | MOr(b) | MLambda(b) | MMap(b) | MBigMap(b) => {
+                todo.push(&b.0);
+                todo.push(&b.1);
+            }
+            MList(t) | MSet(t) | MTicket(t) | MOption(t) | MContract(t) => todo.push(t),
+            MWrapped(_) => {}
+        }
+    }
+    false
+}
+
+impl TcSession {
+    /// Whether a limit was hit, after which nothing more is checked.
+    pub fn aborted(&self) -> bool {
+        self.usage.exceeded.is_some()
+    }
+
+    // Only the first limit hit is reported, as everything stops there.
+    pub(crate) fn exceed(&mut self, limit: Limit, location: Option<usize>, message: String) {
+        if self.usage.exceeded.is_none() {
+            self.usage.exceeded = Some(limit);
+            self.diagnostics.push(Diagnostic {
+                location,
+                kind: ErrorKind::LimitExceeded(limit),
+                message,
+            });
+        }
+    }
+
+    // Spends `units` of fuel, and reads the clock when a time limit is set.
+    pub(crate) fn burn(&mut self, units: u64, location: Option<usize>) -> Result<(), String> {
+        let before = self.usage.fuel;
+        self.usage.fuel = before.saturating_add(units);
+        if self.usage.fuel > self.limits.fuel {
+            let message = format!(
+                "Typechecking needs more than {} units of fuel",
+                self.limits.fuel
+            );
+            self.exceed(Limit::Fuel, location, message.clone());
+            return Result::Err(message);
+        }
+        if let Some(timeout) = self.limits.timeout {
+            let started = *self.usage.started.get_or_insert_with(Instant::now);
+            if before / TIME_CHECK_INTERVAL != self.usage.fuel / TIME_CHECK_INTERVAL
+                && started.elapsed() > timeout
+            {
+                let message = format!("Typechecking takes more than {:?}", timeout);
+                self.exceed(Limit::Time, location, message.clone());
+                return Result::Err(message);
+            }
+        }
+        Result::Ok(())
+    }
+}
@@ -0,0 +1,473 @@
+use sha2::Digest;
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::mem::discriminant;
+use std::ops::Range;
+
+use crate::micheline::Micheline;
+use crate::micheline::Node;
+use crate::protocol::Protocol;
+use crate::types::ArgValue;
+use crate::types::AtomicValue;
+use crate::types::CompositeValue;
+use crate::types::CompoundInstruction;
+use crate::types::ConcreteStack;
+use crate::types::ConcreteType;
+use crate::types::Diagnostic;
+use crate::types::LocatedInstruction;
+use crate::types::MType;
+use crate::types::MValue;
+use crate::types::SomeValue;
+use crate::types::TcEnv;
+
+/// Results of the blocks typechecked in a session, the bodies of lambdas as
+/// well as those of `IF`, `DIP`, `MAP` and the other compound instructions,
+/// so that a block met again with the same input stack, as generated code
+/// repeats them, is only typechecked once.
+///
+/// A result is reused for the same block in the same environment: the same
+/// protocol, parameter type and instruction table. Blocks are the same when
+/// they only differ in where they are, and the typed code and diagnostics of
+/// a result are moved to the locations of the block it is reused for.
+#[derive(Debug, Default)]
+pub struct BlockMemo {
+    entries: HashMap<[u8; 32], Vec<Memo>>,
+    // The digests of the code being typechecked, while it is: that of the
+    // outermost block, then that of the blocks elaborated from values while
+    // typechecking it, which are not in it. And the instruction being
+    // typechecked: the block it is in and its index.
+    code: Vec<BlockDigests>,
+    at: Option<(usize, usize)>,
+}
+
+#[derive(Debug)]
+struct Memo {
+    locations: Vec<usize>,
+    protocol: Protocol,
+    self_type: Option<ConcreteType>,
+    instructions: u64,
+    input: ConcreteStack,
+    output: ConcreteStack,
+    typed: Vec<LocatedInstruction<MValue>>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// The output stack, typed code and diagnostics of a block.
+pub(crate) type Memoized = (
+    ConcreteStack,
+    Vec<LocatedInstruction<MValue>>,
+    Vec<Diagnostic>,
+);
+
+// The blocks of some code, the outermost one first and each block before the
+// blocks nested in it, in the instructions and in the values they have.
+// Every block is hashed once: its digest is that of its own instructions,
+// with locations left out, followed by the digests of its nested blocks.
+#[derive(Debug)]
+struct BlockDigests {
+    blocks: Vec<BlockDigest>,
+    // The locations of the instructions, those of a block and of the blocks
+    // nested in it being together.
+    locations: Vec<usize>,
+}
+
+#[derive(Debug)]
+struct BlockDigest {
+    code: [u8; 32],
+    len: usize,
+    // The blocks nested in it, with the index of the instruction they are in.
+    nested: Vec<(usize, usize)>,
+    locations: Range<usize>,
+}
+
+// Feeds what is hashed to SHA-256, whose digest is long enough that blocks
+// with the same one can be taken to be the same.
+struct CodeHasher(Sha256);
+
+impl Hasher for CodeHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.update(bytes);
+    }
+
+    // Only the digest is used.
+    fn finish(&self) -> u64 {
+        0
+    }
+}
+
+type Block = [LocatedInstruction<SomeValue>];
+
+impl BlockDigests {
+    fn of(block: &Block) -> BlockDigests {
+        let mut digests = BlockDigests {
+            blocks: vec![],
+            locations: vec![],
+        };
+        let mut hashers = vec![];
+        let mut pending: Vec<(&Block, Option<(usize, usize)>)> = vec![(block, None)];
+        while let Some((block, parent)) = pending.pop() {
+            let index = digests.blocks.len();
+            if let Some((parent, instruction)) = parent {
+                digests.blocks[parent].nested.push((instruction, index));
+            }
+            let mut hasher = CodeHasher(Sha256::new());
+            let mut nested = vec![];
+            let start = digests.locations.len();
+            hash_block(block, &mut hasher, &mut nested, &mut digests.locations);
+            hashers.push(hasher);
+            digests.blocks.push(BlockDigest {
+                code: [0; 32],
+                len: block.len(),
+                nested: vec![],
+                locations: start..digests.locations.len(),
+            });
+            pending.extend(
+                nested
+                    .into_iter()
+                    .rev()
+                    .map(|(instruction, b)| (b, Some((index, instruction)))),
+            );
+        }
+        // Nested blocks come after the block they are in, so they are done
+        // first from the end.
+        for (index, CodeHasher(mut hasher)) in hashers.into_iter().enumerate().rev() {
+            let mut end = digests.blocks[index].locations.end;
+            for &(_, n) in &digests.blocks[index].nested {
+                hasher.update(digests.blocks[n].code);
+                end = digests.blocks[n].locations.end;
+            }
+            let digest = &mut digests.blocks[index];
+            digest.code = hasher.finalize().into();
+            digest.locations.end = end;
+        }
+        digests
+    }
+
+    // The block nested in instruction `instruction` of block `parent` that
+    // has the instructions of `block`. Nested blocks of an instruction start
+    // at different locations, except empty ones, which are not told apart
+    // and have nothing to typecheck anyway.
+    fn nested(&self, parent: usize, instruction: usize, block: &Block) -> Option<usize> {
+        let first = block.first()?.location;
+        let mut candidates = self.blocks[parent].nested.iter().filter(|(i, n)| {
+            *i == instruction
+                && self.blocks[*n].len == block.len()
+                && self.locations[self.blocks[*n].locations.start] == first
+        });
+        match (candidates.next(), candidates.next()) {
+            (Some((_, n)), None) => Some(*n),
+            _ => None,
+        }
+    }
+
+    fn locations(&self, block: usize) -> &[usize] {
+        &self.locations[self.blocks[block].locations.clone()]
+    }
+}
+
+// Hashes the instructions of a block, and collects their locations and the
+// blocks nested in them, which are hashed on their own.
+fn hash_block<'c>(
+    block: &'c Block,
+    h: &mut CodeHasher,
+    nested: &mut Vec<(usize, &'c Block)>,
+    locations: &mut Vec<usize>,
+) {
+    use CompoundInstruction::*;
+    block.len().hash(h);
+    for (
+        index,
+        LocatedInstruction {
+            location,
+            instruction,
+        },
+    ) in block.iter().enumerate()
+    {
+        locations.push(*location);
+        discriminant(instruction).hash(h);
+        match instruction {
+            IF(a, b) | IF_CONS(a, b) | IF_SOME(a, b) | IF_NONE(a, b) | IF_LEFT(a, b) => {
+                nested.push((index, a));
+                nested.push((index, b));
+            }
+            PAIR(n) | UNPAIR(n) | DUP(n) | DIG(n) | DUG(n) | DROP(n) | GET(n) | UPDATE(n) => {
+                n.hash(h)
+            }
+            DIP(n, b) => {
+                n.hash(h);
+                nested.push((index, b));
+            }
+            MAP(b) | ITER(b) | LOOP(b) | LOOP_LEFT(b) | SEQ(b) => nested.push((index, b)),
+            LAMBDA_REC(t1, t2, b) => {
+                hash_type(t1, h);
+                hash_type(t2, h);
+                nested.push((index, b));
+            }
+            SELF | FAIL | FAILWITH => {}
+            Other(i) => {
+                i.name.hash(h);
+                i.args.len().hash(h);
+                for arg in &i.args {
+                    discriminant(arg).hash(h);
+                    match arg {
+                        ArgValue::TypeArg(t) => hash_type(t, h),
+                        ArgValue::ValueArg(v) => hash_value(v, h, index, nested, locations),
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn hash_value<'c>(
+    value: &'c SomeValue,
+    h: &mut CodeHasher,
+    index: usize,
+    nested: &mut Vec<(usize, &'c Block)>,
+    locations: &mut Vec<usize>,
+) {
+    let mut pending = vec![value];
+    while let Some(value) = pending.pop() {
+        discriminant(value).hash(h);
+        match value {
+            SomeValue::Atomic(a) => {
+                discriminant(a).hash(h);
+                match a {
+                    AtomicValue::AVNumber(n) => n.hash(h),
+                    AtomicValue::AVString(s) => s.hash(h),
+                    AtomicValue::AVBool(b) => b.hash(h),
+                    AtomicValue::AVBytes(b) => b.hash(h),
+                    AtomicValue::AVUnit => {}
+                }
+            }
+            SomeValue::Composite(cv) => {
+                discriminant(cv.as_ref()).hash(h);
+                match cv.as_ref() {
+                    CompositeValue::CVPair(a, b) => pending.extend([b, a]),
+                    CompositeValue::CVSeq(vs) => {
+                        vs.len().hash(h);
+                        pending.extend(vs.iter().rev());
+                    }
+                    CompositeValue::CVLambda(is) => nested.push((index, is)),
+                    CompositeValue::CVRaw(node) => hash_node(node, h, locations),
+                    CompositeValue::CKVList(kvs) => {
+                        kvs.len().hash(h);
+                        pending.extend(kvs.iter().rev().flat_map(|(k, v)| [v, k]));
+                    }
+                    CompositeValue::CVLeft(v)
+                    | CompositeValue::CVRight(v)
+                    | CompositeValue::CVSome(v) => pending.push(v),
+                    CompositeValue::CVNone => {}
+                }
+            }
+        }
+    }
+}
+
+// Sequences read before their type was known are hashed as they were read,
+// with the locations of their nodes, which the code elaborated from them has.
+fn hash_node(node: &Node, h: &mut CodeHasher, locations: &mut Vec<usize>) {
+    let mut pending = vec![node];
+    while let Some(node) = pending.pop() {
+        locations.push(node.location);
+        discriminant(&node.expr).hash(h);
+        match &node.expr {
+            Micheline::Int(s) | Micheline::String(s) => s.hash(h),
+            Micheline::Bytes(b) => b.hash(h),
+            Micheline::Prim { name, args, annots } => {
+                name.hash(h);
+                annots.hash(h);
+                args.len().hash(h);
+                pending.extend(args.iter().rev());
+            }
+            Micheline::Seq(items) => {
+                items.len().hash(h);
+                pending.extend(items.iter().rev());
+            }
+        }
+    }
+}
+
+fn hash_type(t: &ConcreteType, h: &mut CodeHasher) {
+    use MType::*;
+    discriminant(t).hash(h);
+    match t {
+        MTicket(a) | MOption(a) | MContract(a) | MList(a) | MSet(a) => hash_type(a, h),
+        MPair(ab) | MOr(ab) | MLambda(ab) | MBigMap(ab) | MMap(ab) => {
+            hash_type(&ab.0, h);
+            hash_type(&ab.1, h);
+        }
+        MWrapped(a) => a.hash(h),
+    }
+}
+
+// What holds locations in typed code.
+enum Located<'a> {
+    Block(&'a mut Vec<LocatedInstruction<MValue>>),
+    Value(&'a mut MValue),
+}
+
+// Moves the locations of typed code by `moved`, which has where each
+// location goes.
+fn relocate(typed: &mut Vec<LocatedInstruction<MValue>>, moved: &HashMap<usize, usize>) {
+    use CompoundInstruction::*;
+    use MValue::*;
+    let mut pending = vec![Located::Block(typed)];
+    while let Some(next) = pending.pop() {
+        match next {
+            Located::Block(block) => {
+                for LocatedInstruction {
+                    location,
+                    instruction,
+                } in block.iter_mut()
+                {
+                    if let Some(to) = moved.get(location) {
+                        *location = *to;
+                    }
+                    match instruction {
+                        IF(a, b)
+                        | IF_CONS(a, b)
+                        | IF_SOME(a, b)
+                        | IF_NONE(a, b)
+                        | IF_LEFT(a, b) => {
+                            pending.push(Located::Block(a));
+                            pending.push(Located::Block(b));
+                        }
+                        DIP(_, b)
+                        | MAP(b)
+                        | ITER(b)
+                        | LOOP(b)
+                        | LOOP_LEFT(b)
+                        | SEQ(b)
+                        | LAMBDA_REC(_, _, b) => pending.push(Located::Block(b)),
+                        Other(i) => pending.extend(i.args.iter_mut().filter_map(|a| match a {
+                            ArgValue::ValueArg(v) => Some(Located::Value(v)),
+                            ArgValue::TypeArg(_) => None,
+                        })),
+                        _ => {}
+                    }
+                }
+            }
+            Located::Value(value) => match value {
+                VLambda(b) => pending.push(Located::Block(b)),
+                VPair(ab) => {
+                    let (a, b) = ab.as_mut();
+                    pending.push(Located::Value(a));
+                    pending.push(Located::Value(b));
+                }
+                VLeft(v) | VRight(v) | VSome(v) => pending.push(Located::Value(v)),
+                VList(vs) => pending.extend(vs.iter_mut().map(Located::Value)),
+                // Lambdas are not comparable, so they are not keys.
+                VMap(m) | VBigMap(m) => pending.extend(m.values_mut().map(Located::Value)),
+                _ => {}
+            },
+        }
+    }
+}
+
+impl BlockMemo {
+    /// Starts typechecking a block, and gives where it is in the digests of
+    /// the code being typechecked, if it can be memoized. A block that is not
+    /// nested in the instruction being typechecked is hashed then, with the
+    /// blocks nested in it, and `leave` is to be called with `true` once it
+    /// is done.
+    pub(crate) fn enter(&mut self, block: &Block) -> (Option<usize>, bool) {
+        let found = match (self.code.last(), self.at) {
+            (Some(code), Some((parent, i))) => code.nested(parent, i, block),
+            _ => None,
+        };
+        match found {
+            Some(_) => (found, false),
+            None => {
+                self.code.push(BlockDigests::of(block));
+                self.at = None;
+                (Some(0), true)
+            }
+        }
+    }
+
+    pub(crate) fn leave(&mut self, hashed: bool) {
+        if hashed {
+            self.code.pop();
+            self.at = None;
+        }
+    }
+
+    /// The instruction being typechecked, as the index of the block it is in
+    /// and its index in the block.
+    pub(crate) fn at(&self) -> Option<(usize, usize)> {
+        self.at
+    }
+
+    pub(crate) fn set_at(&mut self, at: Option<(usize, usize)>) {
+        self.at = at;
+    }
+
+    /// The block nested in instruction `instruction` of block `parent`.
+    pub(crate) fn nested(&self, parent: usize, instruction: usize, block: &Block) -> Option<usize> {
+        self.code.last()?.nested(parent, instruction, block)
+    }
+
+    pub(crate) fn find(
+        &self,
+        block: usize,
+        tcenv: &TcEnv,
+        input: &ConcreteStack,
+    ) -> Option<Memoized> {
+        let code = self.code.last()?;
+        let memo = self
+            .entries
+            .get(&code.blocks[block].code)?
+            .iter()
+            .find(|m| {
+                m.protocol == tcenv.protocol
+                    && m.self_type == tcenv.self_type
+                    && m.instructions == tcenv.instructions.version()
+                    && m.input == *input
+            })?;
+        let locations = code.locations(block);
+        let mut typed = memo.typed.clone();
+        let mut diagnostics = memo.diagnostics.clone();
+        if memo.locations != locations {
+            let moved: HashMap<usize, usize> = memo
+                .locations
+                .iter()
+                .copied()
+                .zip(locations.iter().copied())
+                .collect();
+            relocate(&mut typed, &moved);
+            for d in &mut diagnostics {
+                if let Some(to) = d.location.and_then(|l| moved.get(&l)) {
+                    d.location = Some(*to);
+                }
+            }
+        }
+        Some((memo.output.clone(), typed, diagnostics))
+    }
+
+    pub(crate) fn insert(
+        &mut self,
+        block: usize,
+        tcenv: &TcEnv,
+        input: ConcreteStack,
+        (output, typed, diagnostics): Memoized,
+    ) {
+        let Some(code) = self.code.last() else { return };
+        let memo = Memo {
+            locations: code.locations(block).to_vec(),
+            protocol: tcenv.protocol,
+            self_type: tcenv.self_type.clone(),
+            instructions: tcenv.instructions.version(),
+            input,
+            output,
+            typed,
+            diagnostics,
+        };
+        self.entries
+            .entry(code.blocks[block].code)
+            .or_default()
+            .push(memo);
+    }
+}
@@ -0,0 +1,463 @@
+use serde_json::Value;
+
+use crate::encoding::bytes_to_hex;
+use crate::encoding::hex_to_bytes;
+use crate::parsers::MAX_NESTING_DEPTH;
+
+// The generic syntax of Michelson: whatever the format a script comes in,
+// text, JSON or binary, it is read into these nodes, and only then given a
+// meaning as types, values and instructions by `elaborate`.
+
+/// A Micheline expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Micheline {
+    // In decimal, of any size.
+    Int(String),
+    String(String),
+    Bytes(Vec<u8>),
+    Prim {
+        name: String,
+        args: Vec<Node>,
+        annots: Vec<String>,
+    },
+    Seq(Vec<Node>),
+}
+
+/// A Micheline expression and its offset in the source it was read from:
+/// bytes of text or of the binary encoding, and nothing for JSON, where
+/// every node is at 0.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node {
+    pub location: usize,
+    pub expr: Micheline,
+}
+
+impl Node {
+    pub fn new(location: usize, expr: Micheline) -> Node {
+        Node { location, expr }
+    }
+
+    /// Reads Micheline JSON, as `octez-client` and indexers serve scripts.
+    pub fn from_json(v: &Value) -> Result<Node, String> {
+        let expr = match v {
+            Value::Array(items) => Micheline::Seq(
+                items
+                    .iter()
+                    .map(Node::from_json)
+                    .collect::<Result<_, _>>()?,
+            ),
+            Value::Object(o) => {
+                let string = |key: &str| o.get(key).and_then(|v| v.as_str());
+                if let Some(i) = string("int") {
+                    let digits = i.strip_prefix('-').unwrap_or(i);
+                    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+                        return Result::Err(format!("Invalid int {}", i));
+                    }
+                    Micheline::Int(i.to_string())
+                } else if let Some(s) = string("string") {
+                    Micheline::String(s.to_string())
+                } else if let Some(b) = string("bytes") {
+                    Micheline::Bytes(hex_to_bytes(b).ok_or_else(|| format!("Invalid bytes {}", b))?)
+                } else if let Some(p) = string("prim") {
+                    let list = |key: &str| match o.get(key) {
+                        None => Result::Ok(&[][..]),
+                        Some(Value::Array(items)) => Result::Ok(&items[..]),
+                        Some(v) => {
+                            Result::Err(format!("Expecting a list of {} but found {}", key, v))
+                        }
+                    };
+                    let mut annots = vec![];
+                    for a in list("annots")? {
+                        match a.as_str() {
+                            Some(a) => annots.push(a.to_string()),
+                            None => return Result::Err(format!("Invalid annotation {}", a)),
+                        }
+                    }
+                    Micheline::Prim {
+                        name: p.to_string(),
+                        args: list("args")?
+                            .iter()
+                            .map(Node::from_json)
+                            .collect::<Result<_, _>>()?,
+                        annots,
+                    }
+                } else {
+                    return Result::Err(format!("Unexpected Micheline node {}", v));
+                }
+            }
+            _ => return Result::Err(format!("Unexpected Micheline node {}", v)),
+        };
+        Result::Ok(Node::new(0, expr))
+    }
+
+    /// As Micheline JSON, the other way round from `from_json`.
+    pub fn to_json(&self) -> Value {
+        match &self.expr {
+            Micheline::Int(i) => serde_json::json!({ "int": i }),
+            Micheline::String(s) => serde_json::json!({ "string": s }),
+            Micheline::Bytes(b) => serde_json::json!({ "bytes": bytes_to_hex(b) }),
+            Micheline::Prim { name, args, annots } => {
+                let mut o = serde_json::Map::new();
+                o.insert(String::from("prim"), Value::String(name.clone()));
+                if !args.is_empty() {
+                    o.insert(
+                        String::from("args"),
+                        Value::Array(args.iter().map(Node::to_json).collect()),
+                    );
+                }
+                if !annots.is_empty() {
+                    o.insert(String::from("annots"), serde_json::json!(annots));
+                }
+                Value::Object(o)
+            }
+            Micheline::Seq(items) => Value::Array(items.iter().map(Node::to_json).collect()),
+        }
+    }
+
+    /// Reads the binary encoding of Micheline, as in the scripts of
+    /// operations. Packed values start with a `0x05` byte, which is not part
+    /// of the encoding.
+    pub fn from_binary(bytes: &[u8]) -> Result<Node, String> {
+        let mut reader = Reader { bytes, position: 0 };
+        let node = reader.node(0)?;
+        if reader.position < bytes.len() {
+            return Result::Err(format!("Unexpected bytes at {}", reader.position));
+        }
+        Result::Ok(node)
+    }
+}
+
+// Primitives of the binary encoding, by code.
+const PRIMITIVES: [&str; 157] = [
+    "parameter",
+    "storage",
+    "code",
+    "False",
+    "Elt",
+    "Left",
+    "None",
+    "Pair",
+    "Right",
+    "Some",
+    "True",
+    "Unit",
+    "PACK",
+    "UNPACK",
+    "BLAKE2B",
+    "SHA256",
+    "SHA512",
+    "ABS",
+    "ADD",
+    "AMOUNT",
+    "AND",
+    "BALANCE",
+    "CAR",
+    "CDR",
+    "CHECK_SIGNATURE",
+    "COMPARE",
+    "CONCAT",
+    "CONS",
+    "CREATE_ACCOUNT",
+    "CREATE_CONTRACT",
+    "IMPLICIT_ACCOUNT",
+    "DIP",
+    "DROP",
+    "DUP",
+    "EDIV",
+    "EMPTY_MAP",
+    "EMPTY_SET",
+    "EQ",
+    "EXEC",
+    "FAILWITH",
+    "GE",
+    "GET",
+    "GT",
+    "HASH_KEY",
+    "IF",
+    "IF_CONS",
+    "IF_LEFT",
+    "IF_NONE",
+    "INT",
+    "LAMBDA",
+    "LE",
+    "LEFT",
+    "LOOP",
+    "LSL",
+    "LSR",
+    "LT",
+    "MAP",
+    "MEM",
+    "MUL",
+    "NEG",
+    "NEQ",
+    "NIL",
+    "NONE",
+    "NOT",
+    "NOW",
+    "OR",
+    "PAIR",
+    "PUSH",
+    "RIGHT",
+    "SIZE",
+    "SOME",
+    "SOURCE",
+    "SENDER",
+    "SELF",
+    "STEPS_TO_QUOTA",
+    "SUB",
+    "SWAP",
+    "TRANSFER_TOKENS",
+    "SET_DELEGATE",
+    "UNIT",
+    "UPDATE",
+    "XOR",
+    "ITER",
+    "LOOP_LEFT",
+    "ADDRESS",
+    "CONTRACT",
+    "ISNAT",
+    "CAST",
+    "RENAME",
+    "bool",
+    "contract",
+    "int",
+    "key",
+    "key_hash",
+    "lambda",
+    "list",
+    "map",
+    "big_map",
+    "nat",
+    "option",
+    "or",
+    "pair",
+    "set",
+    "signature",
+    "string",
+    "bytes",
+    "mutez",
+    "timestamp",
+    "unit",
+    "operation",
+    "address",
+    "SLICE",
+    "DIG",
+    "DUG",
+    "EMPTY_BIG_MAP",
+    "APPLY",
+    "chain_id",
+    "CHAIN_ID",
+    "LEVEL",
+    "SELF_ADDRESS",
+    "never",
+    "NEVER",
+    "UNPAIR",
+    "VOTING_POWER",
+    "TOTAL_VOTING_POWER",
+    "KECCAK",
+    "SHA3",
+    "PAIRING_CHECK",
+    "bls12_381_g1",
+    "bls12_381_g2",
+    "bls12_381_fr",
+    "sapling_state",
+    "sapling_transaction_deprecated",
+    "SAPLING_EMPTY_STATE",
+    "SAPLING_VERIFY_UPDATE",
+    "ticket",
+    "TICKET_DEPRECATED",
+    "READ_TICKET",
+    "SPLIT_TICKET",
+    "JOIN_TICKETS",
+    "GET_AND_UPDATE",
+    "chest",
+    "chest_key",
+    "OPEN_CHEST",
+    "VIEW",
+    "view",
+    "constant",
+    "SUB_MUTEZ",
+    "tx_rollup_l2_address",
+    "MIN_BLOCK_TIME",
+    "sapling_transaction",
+    "EMIT",
+    "Lambda_rec",
+    "LAMBDA_REC",
+    "TICKET",
+    "BYTES",
+    "NAT",
+];
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], String> {
+        let end = self.position.saturating_add(n);
+        let taken = self
+            .bytes
+            .get(self.position..end)
+            .ok_or_else(|| format!("Unexpected end of bytes at {}", self.position))?;
+        self.position = end;
+        Result::Ok(taken)
+    }
+
+    fn byte(&mut self) -> Result<u8, String> {
+        Result::Ok(self.take(1)?[0])
+    }
+
+    // Lengths are 4 bytes, big-endian.
+    fn sized(&mut self) -> Result<&'a [u8], String> {
+        let length = self.take(4)?;
+        self.take(u32::from_be_bytes([length[0], length[1], length[2], length[3]]) as usize)
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let at = self.position;
+        let bytes = self.sized()?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| format!("Invalid string at {}", at))
+    }
+
+    fn primitive(&mut self) -> Result<String, String> {
+        let at = self.position;
+        let code = self.byte()?;
+        PRIMITIVES
+            .get(code as usize)
+            .map(|p| p.to_string())
+            .ok_or_else(|| format!("Unknown primitive {} at {}", code, at))
+    }
+
+    fn annots(&mut self) -> Result<Vec<String>, String> {
+        Result::Ok(
+            self.string()?
+                .split_whitespace()
+                .map(|a| a.to_string())
+                .collect(),
+        )
+    }
+
+    // Integers are in groups of 7 bits, least significant first, each with a
+    // bit that tells whether more follow; the first group has 6 bits and the
+    // sign.
+    fn int(&mut self) -> Result<String, String> {
+        let first = self.byte()?;
+        let mut groups = vec![first & 0x3f];
+        let mut more = first & 0x80 != 0;
+        while more {
+            let b = self.byte()?;
+            groups.push(b & 0x7f);
+            more = b & 0x80 != 0;
+        }
+        // The decimal digits, in base 10^9 and least significant first.
+        let mut limbs: Vec<u64> = vec![0];
+        let shift = |limbs: &mut Vec<u64>, bits: u32, add: u8| {
+            let mut carry = add as u64;
+            for limb in limbs.iter_mut() {
+                let v = (*limb << bits) + carry;
+                *limb = v % 1_000_000_000;
+                carry = v / 1_000_000_000;
+            }
+            while carry > 0 {
+                limbs.push(carry % 1_000_000_000);
+                carry /= 1_000_000_000;
+            }
+        };
+        for g in groups[1..].iter().rev() {
+            shift(&mut limbs, 7, *g);
+        }
+        shift(&mut limbs, 6, groups[0]);
+        let mut digits = limbs.last().map_or(String::new(), |l| l.to_string());
+        for limb in limbs.iter().rev().skip(1) {
+            digits.push_str(&format!("{:09}", limb));
+        }
+        if first & 0x40 != 0 && digits != "0" {
+            digits.insert(0, '-');
+        }
+        Result::Ok(digits)
+    }
+
+    // The nodes of a sequence or of the arguments of a primitive, which are
+    // prefixed with their size in bytes.
+    fn items(&mut self, depth: usize) -> Result<Vec<Node>, String> {
+        let start = self.position + 4;
+        let end = start + self.sized()?.len();
+        let mut items = Reader {
+            bytes: &self.bytes[..end],
+            position: start,
+        };
+        let mut nodes = vec![];
+        while items.position < end {
+            nodes.push(items.node(depth + 1)?);
+        }
+        Result::Ok(nodes)
+    }
+
+    fn node(&mut self, depth: usize) -> Result<Node, String> {
+        let location = self.position;
+        if depth > MAX_NESTING_DEPTH {
+            return Result::Err(format!(
+                "Nesting exceeds the limit of {} levels at {}",
+                MAX_NESTING_DEPTH, location
+            ));
+        }
+        let tag = self.byte()?;
+        let expr = match tag {
+            0 => Micheline::Int(self.int()?),
+            1 => Micheline::String(self.string()?),
+            2 => Micheline::Seq(self.items(depth)?),
+            3..=8 => {
+                let name = self.primitive()?;
+                let mut args = vec![];
+                for _ in 0..(tag - 3) / 2 {
+                    args.push(self.node(depth + 1)?);
+                }
+                let annots = if tag % 2 == 0 { self.annots()? } else { vec![] };
+                Micheline::Prim { name, args, annots }
+            }
+            9 => Micheline::Prim {
+                name: self.primitive()?,
+                args: self.items(depth)?,
+                annots: self.annots()?,
+            },
+            10 => Micheline::Bytes(self.sized()?.to_vec()),
+            _ => return Result::Err(format!("Unknown tag {} at {}", tag, location)),
+        };
+        Result::Ok(Node::new(location, expr))
+    }
+}
+
+// As Michelson source: applications are wrapped in parentheses when they are
+// arguments, and sequences in braces.
+impl std::fmt::Display for Node {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.expr {
+            Micheline::Int(i) => write!(f, "{}", i),
+            Micheline::String(s) => write!(f, "{}", Value::String(s.clone())),
+            Micheline::Bytes(b) => write!(f, "0x{}", bytes_to_hex(b)),
+            Micheline::Prim { name, args, annots } => {
+                write!(f, "{}", name)?;
+                for a in annots {
+                    write!(f, " {}", a)?;
+                }
+                for arg in args {
+                    match &arg.expr {
+                        Micheline::Prim { args, .. } if !args.is_empty() => {
+                            write!(f, " ({})", arg)?
+                        }
+                        _ => write!(f, " {}", arg)?,
+                    }
+                }
+                Result::Ok(())
+            }
+            Micheline::Seq(items) => {
+                write!(f, "{{")?;
+                for (k, item) in items.iter().enumerate() {
+                    write!(f, "{}{}", if k > 0 { " ; " } else { " " }, item)?;
+                }
+                write!(f, " }}")
+            }
+        }
+    }
+}
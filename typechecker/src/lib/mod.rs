@@ -1,11 +1,23 @@
-#[macro_use]
-extern crate lazy_static;
 pub mod attributes;
+pub mod encoding;
 pub mod types;
 pub mod instructions;
 pub mod typechecker;
 pub mod parsers;
-pub mod parser;
+pub mod signature;
+pub mod micheline;
+pub mod micheline_parser;
+pub mod elaborate;
+pub mod printer;
+pub mod interpreter;
+pub mod tzt;
+pub mod protocol;
+pub mod stack;
+pub mod limits;
+pub mod batch;
+pub mod cache;
+pub mod memo;
+pub mod visit;
 
 pub use crate::types::MValue::*;
 pub use crate::types::MType::*;
@@ -14,17 +26,26 @@ pub use crate::types::MAtomic::*;
 pub use crate::types::AtomicValue::*;
 pub use crate::types::SomeValue::*;
 pub use crate::types::CompositeValue::*;
-pub use crate::types::SeqItem::*;
 pub use crate::types::Attribute::*;
 pub use crate::types::StackDerived::*;
 pub use crate::types::StackResultElem::*;
-pub use crate::types::StackDerived::*;
 pub use crate::types::StackState::*;
 pub use crate::types::CompoundInstruction::*;
-pub use crate::instructions::MICHELSON_INSTRUCTIONS;
+pub use crate::instructions::*;
 pub use crate::types::StackCompResult::*;
 pub use crate::attributes::*;
 pub use crate::types::*;
 pub use crate::typechecker::*;
-pub use crate::parser::*;
 pub use crate::parsers::*;
+pub use crate::micheline::*;
+pub use crate::elaborate::*;
+pub use crate::printer::*;
+pub use crate::interpreter::*;
+pub use crate::tzt::*;
+pub use crate::protocol::*;
+pub use crate::stack::*;
+pub use crate::limits::*;
+pub use crate::batch::*;
+pub use crate::cache::*;
+pub use crate::memo::*;
+pub use crate::visit::*;
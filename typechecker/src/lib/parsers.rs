@@ -1,84 +1,378 @@
+use lalrpop_util::ParseError;
 use regex::Regex;
-use std::collections::VecDeque;
-
-use crate::parser::ConstraintParser;
-use crate::parser::ContractParser;
-use crate::parser::MDynListParser;
-use crate::parser::StackResultElemParser;
-use crate::types::map_mtype;
-use crate::types::mdyn_to_concrete;
-use crate::types::ArgConstraint;
+use std::sync::Arc;
+
+use crate::elaborate::elaborate_block;
+use crate::elaborate::elaborate_code;
+use crate::elaborate::elaborate_contract;
+use crate::elaborate::elaborate_typed_value;
+use crate::elaborate::elaborate_value;
+use crate::elaborate::ATOMIC_TYPES;
+use crate::instructions::MICHELSON_INSTRUCTIONS;
+use crate::limits::Limit;
+use crate::limits::Limits;
+use crate::micheline::Micheline;
+use crate::micheline::Node;
+use crate::micheline_parser::ItemsParser;
+use crate::micheline_parser::ScriptParser;
+use crate::protocol::Protocol;
+use crate::signature::parse_types;
+use crate::signature::SigType;
+use crate::signature::Var;
+use crate::stack::SharedStack;
+use crate::typechecker::typecheck_data;
+use crate::types::ArgConstraint::*;
+use crate::types::Attribute;
+use crate::types::Attribute::*;
+use crate::types::ConcreteStack;
+use crate::types::ConcreteType;
 use crate::types::Constraint;
 use crate::types::Contract;
-use crate::types::DynMType;
-use crate::types::DynMType::*;
+use crate::types::Diagnostic;
+use crate::types::ErrorKind;
+use crate::types::LocatedInstruction;
 use crate::types::MAtomic;
 use crate::types::MType;
+use crate::types::MType::*;
+use crate::types::MValue;
 use crate::types::SomeValue;
 use crate::types::StackResult;
-use crate::types::StackResultElem;
+use crate::types::StackResultElem::*;
 use crate::types::StackState;
 use crate::types::StackState::*;
+use crate::types::TcEnv;
 
-pub fn parse_mdyn_to<T, F: Fn(&MType<DynMType>) -> T>(cs: &str, cb: F) -> Vec<T> {
-    if cs.is_empty() {
-        Vec::new()
-    } else {
-        match MDynListParser::new().parse(cs) {
-            Result::Ok(s) => return s.iter().map(cb).collect(),
-            Result::Err(s) => panic!("{} when parsing {}", s, cs),
-        }
+pub fn parse_constraints(cs: &str) -> Vec<Constraint> {
+    try_parse_constraints(cs).unwrap_or_else(|e| panic!("{}", e))
+}
+
+pub fn parse_contract(src: &str) -> Contract<SomeValue> {
+    match try_parse_contract(src) {
+        Result::Ok(s) => s,
+        Result::Err(s) => panic!("{}", s.message),
     }
 }
 
-pub fn dynm_to_arg_constraint(d: DynMType) -> ArgConstraint {
-    match d {
-        DMDyn(s) => match ConstraintParser::new().parse(&s) {
-            Result::Ok(s) => s,
-            Result::Err(e) => panic!("Parsing of ArgConstraint failed!: {} : {}", e, s),
-        },
-        _ => panic!("Unexpected enum variant during constraint parsing"),
+pub fn try_parse_contract(src: &str) -> Result<Contract<SomeValue>, Diagnostic> {
+    try_parse_contract_with(src, &Limits::default())
+}
+
+/// Like `try_parse_contract`, for sources that have to be within `limits`.
+pub fn try_parse_contract_with(
+    src: &str,
+    limits: &Limits,
+) -> Result<Contract<SomeValue>, Diagnostic> {
+    elaborate_contract(&read_micheline(src, limits, true)?)
+}
+
+/// Reads Micheline text within `limits`, as the expressions at its toplevel,
+/// separated by `;` as in contracts and tzt files. What the expressions mean
+/// is left to `elaborate`.
+pub fn parse_micheline(src: &str, limits: &Limits) -> Result<Vec<Node>, Diagnostic> {
+    read_micheline(src, limits, false)
+}
+
+// Contracts end without a `;`.
+fn read_micheline(src: &str, limits: &Limits, contract: bool) -> Result<Vec<Node>, Diagnostic> {
+    let src_sans_comments = strip_comments(src);
+    check_source(&src_sans_comments, limits)?;
+    let result = if contract {
+        ScriptParser::new().parse(&src_sans_comments)
+    } else {
+        ItemsParser::new().parse(&src_sans_comments)
+    };
+    let nodes = result.map_err(|e| Diagnostic {
+        location: parse_error_location(&e),
+        kind: ErrorKind::Syntax,
+        message: e.to_string(),
+    })?;
+    nodes.iter().try_for_each(check_depth)?;
+    Result::Ok(nodes)
+}
+
+/// Parses a block of code on its own, either as `{ ... }` or as a bare
+/// instruction list.
+pub fn try_parse_code(src: &str) -> Result<Vec<LocatedInstruction<SomeValue>>, Diagnostic> {
+    try_parse_code_with(src, &Limits::default())
+}
+
+/// Like `try_parse_code`, for sources that have to be within `limits`.
+pub fn try_parse_code_with(
+    src: &str,
+    limits: &Limits,
+) -> Result<Vec<LocatedInstruction<SomeValue>>, Diagnostic> {
+    match &parse_micheline(src, limits)?[..] {
+        [block @ Node {
+            expr: Micheline::Seq(_),
+            ..
+        }] => elaborate_block(block),
+        instructions => elaborate_code(instructions),
     }
 }
 
-pub fn dynm_to_stack_result(d: DynMType) -> StackResultElem {
-    match d {
-        DMDyn(s) => match StackResultElemParser::new().parse(&s) {
-            Result::Ok(s) => s,
-            Result::Err(s) => panic!("Parsing of stack result failed! {:?}", s),
-        },
-        _ => panic!("Unexpected enum variant during stack result parsing"),
+fn parse_single_value(src: &str) -> Result<Node, Diagnostic> {
+    match <[Node; 1]>::try_from(parse_micheline(src, &Limits::default())?) {
+        Result::Ok([value]) => Result::Ok(value),
+        Result::Err(_) => Result::Err(Diagnostic {
+            location: None,
+            kind: ErrorKind::Syntax,
+            message: String::from("Expecting a single value"),
+        }),
     }
 }
 
-pub fn mdyn_to_constraint(m: &MType<DynMType>) -> Constraint {
-    map_mtype(m, &|x| dynm_to_arg_constraint(x.clone()))
+/// Parses a single value, untyped.
+pub fn try_parse_some_value(src: &str) -> Result<SomeValue, Diagnostic> {
+    elaborate_value(&parse_single_value(src)?)
 }
 
-pub fn mdyn_to_stack_result(m: &MType<DynMType>) -> StackResult {
-    map_mtype(m, &|x| dynm_to_stack_result(x.clone()))
+/// Parses a value and typechecks it against `target`, for values such as
+/// parameters and storages that come from outside of any code.
+pub fn parse_value(src: &str, target: &ConcreteType) -> Result<MValue, Diagnostic> {
+    let value = elaborate_typed_value(&parse_single_value(src)?, target)?;
+    let tcenv = TcEnv {
+        self_type: None,
+        instructions: &MICHELSON_INSTRUCTIONS,
+        protocol: Protocol::LATEST,
+    };
+    typecheck_data(&tcenv, &value, target).map_err(|message| Diagnostic {
+        location: None,
+        kind: ErrorKind::Type,
+        message,
+    })
 }
 
-pub fn parse_constraints(cs: &str) -> Vec<Constraint> {
-    parse_mdyn_to(cs, mdyn_to_constraint)
+/// How deeply code, types and values may nest, in nodes of the trees they
+/// parse to. Deeper sources are rejected, since these trees are walked
+/// recursively, by the printer, the interpreter and their destructors among
+/// others, and lambdas nest the typechecking of their bodies. Everything
+/// within the limit is checked and run on a thread with a 2MB stack.
+pub const MAX_NESTING_DEPTH: usize = 100;
+
+/// Checks a source, with comments stripped, against the limits that apply
+/// before it is parsed: its size, the size of its literals and, whatever the
+/// limits, its nesting depth.
+pub fn check_source(src: &str, limits: &Limits) -> Result<(), Diagnostic> {
+    if src.len() > limits.max_source_size {
+        return Result::Err(Diagnostic {
+            location: None,
+            kind: ErrorKind::LimitExceeded(Limit::SourceSize),
+            message: format!(
+                "Source of {} bytes exceeds the limit of {} bytes",
+                src.len(),
+                limits.max_source_size
+            ),
+        });
+    }
+    check_nesting(src)?;
+    if limits.max_literal_size == usize::MAX {
+        return Result::Ok(());
+    }
+    // Strings come first so that the digits they contain are not matched.
+    let literal = Regex::new(r#""[^"]*"|0x[0-9a-fA-F]*|[+-]?[0-9]+"#).unwrap();
+    for m in literal.find_iter(src) {
+        let size = if m.as_str().starts_with('"') {
+            m.len() - 2
+        } else {
+            m.len()
+        };
+        if size > limits.max_literal_size {
+            return Result::Err(Diagnostic {
+                location: Some(m.start()),
+                kind: ErrorKind::LimitExceeded(Limit::LiteralSize),
+                message: format!(
+                    "Literal of {} bytes exceeds the limit of {} bytes",
+                    size, limits.max_literal_size
+                ),
+            });
+        }
+    }
+    Result::Ok(())
 }
 
-pub fn parse_contract(src: &str) -> Contract<SomeValue> {
-    let re = Regex::new(r"#[^\n\r]*[\n\r]*").unwrap();
-    let src_sans_comments = re.replace_all(src, "");
-    //println!("{}", src_sans_comments);
-    match ContractParser::new().parse(&src_sans_comments) {
-        Result::Ok(s) => {
-            s
+/// Checks that `src`, with comments stripped, nests at most
+/// `MAX_NESTING_DEPTH` brackets deep, before it is parsed. Brackets in
+/// string literals do not count.
+pub fn check_nesting(src: &str) -> Result<(), Diagnostic> {
+    let mut depth = 0;
+    let mut in_string = false;
+    for (i, c) in src.char_indices() {
+        if in_string {
+            in_string = c != '"';
+            continue;
+        }
+        match c {
+            '{' | '(' => depth += 1,
+            '}' | ')' => depth = usize::saturating_sub(depth, 1),
+            '"' => in_string = true,
+            _ => {}
+        }
+        if depth > MAX_NESTING_DEPTH {
+            return Result::Err(nesting_error(i));
+        }
+    }
+    Result::Ok(())
+}
+
+/// Checks that what `node` elaborates to nests at most `MAX_NESTING_DEPTH`
+/// levels deep. Right combs such as `Pair a b c` count as the pairs they
+/// stand for, which brackets alone do not bound.
+pub fn check_depth(node: &Node) -> Result<(), Diagnostic> {
+    check_depth_from(node, 1)
+}
+
+fn check_depth_from(node: &Node, depth: usize) -> Result<(), Diagnostic> {
+    if depth > MAX_NESTING_DEPTH {
+        return Result::Err(nesting_error(node.location));
+    }
+    match &node.expr {
+        Micheline::Seq(items) => items
+            .iter()
+            .try_for_each(|item| check_depth_from(item, depth + 1)),
+        Micheline::Prim { name, args, .. } => {
+            let comb = matches!(name.as_str(), "Pair" | "pair") && args.len() > 2;
+            args.iter().enumerate().try_for_each(|(i, arg)| {
+                let nested = if comb { i.min(args.len() - 2) } else { 0 };
+                check_depth_from(arg, depth + 1 + nested)
+            })
         }
-        Result::Err(s) => panic!("{}", s),
+        _ => Result::Ok(()),
+    }
+}
+
+fn nesting_error(location: usize) -> Diagnostic {
+    Diagnostic {
+        location: Some(location),
+        kind: ErrorKind::LimitExceeded(Limit::Nesting),
+        message: format!("Nesting exceeds the limit of {} levels", MAX_NESTING_DEPTH),
+    }
+}
+
+// Comments are blanked out instead of removed so that the locations recorded
+// by the parser are still byte offsets into the original source.
+pub fn strip_comments(src: &str) -> String {
+    let re = Regex::new(r"#[^\n\r]*").unwrap();
+    re.replace_all(src, |c: &regex::Captures| " ".repeat(c[0].len()))
+        .into_owned()
+}
+
+pub fn parse_error_location<T, E>(e: &ParseError<usize, T, E>) -> Option<usize> {
+    match e {
+        ParseError::InvalidToken { location } => Some(*location),
+        ParseError::UnrecognizedEof { location, .. } => Some(*location),
+        ParseError::UnrecognizedToken { token, .. } => Some(token.0),
+        ParseError::ExtraToken { token } => Some(token.0),
+        ParseError::User { .. } => None,
     }
 }
 
 pub fn parse_stack_results(cs: &str) -> Vec<StackResult> {
-    parse_mdyn_to(cs, mdyn_to_stack_result)
+    try_parse_stack_results(cs).unwrap_or_else(|e| panic!("{}", e))
+}
+
+fn atomic_type(name: &str) -> Result<MAtomic, String> {
+    match ATOMIC_TYPES.iter().find(|a| a.to_string() == name) {
+        Some(a) => Result::Ok(a.clone()),
+        None => Result::Err(format!("Unknown type {}", name)),
+    }
+}
+
+fn attributes(names: &[&str]) -> Result<Vec<Attribute>, String> {
+    names
+        .iter()
+        .map(|name| match *name {
+            "comparable" => Result::Ok(Comparable),
+            "passable" => Result::Ok(Passable),
+            "pushable" => Result::Ok(Pushable),
+            "storable" => Result::Ok(Storable),
+            "packable" => Result::Ok(Packable),
+            "bigmapvalue" => Result::Ok(BigmapValue),
+            "duplicable" => Result::Ok(Duplicable),
+            _ => Result::Err(format!("Unknown attribute {}", name)),
+        })
+        .collect()
+}
+
+// Builds a type read by the `signature` module, with `leaf` for its atomic
+// types and type variables.
+fn from_signature<T: Clone>(
+    t: &SigType,
+    leaf: &impl Fn(&SigType) -> Result<MType<T>, String>,
+) -> Result<MType<T>, String> {
+    let SigType::App(c, args) = t else {
+        return leaf(t);
+    };
+    let args = args
+        .iter()
+        .map(|a| from_signature(a, leaf))
+        .collect::<Result<Vec<_>, _>>()?;
+    let pair = |a: &MType<T>, b: &MType<T>| Arc::new((a.clone(), b.clone()));
+    Result::Ok(match (*c, &args[..]) {
+        ("list", [a]) => MList(Arc::new(a.clone())),
+        ("set", [a]) => MSet(Arc::new(a.clone())),
+        ("ticket", [a]) => MTicket(Arc::new(a.clone())),
+        ("option", [a]) => MOption(Arc::new(a.clone())),
+        ("contract", [a]) => MContract(Arc::new(a.clone())),
+        ("pair", [a, b]) => MPair(pair(a, b)),
+        ("or", [a, b]) => MOr(pair(a, b)),
+        ("map", [a, b]) => MMap(pair(a, b)),
+        ("big_map", [a, b]) => MBigMap(pair(a, b)),
+        ("lambda", [a, b]) => MLambda(pair(a, b)),
+        _ => return Result::Err(format!("Unknown type constructor {}", c)),
+    })
+}
+
+/// Builds the `ArgConstraint`s of the arguments or input stack of a
+/// signature.
+pub fn constraints_from_signature(ts: &[SigType]) -> Result<Vec<Constraint>, String> {
+    let leaf = |t: &SigType| match t {
+        SigType::Var(Var::Atomic(a)) => Result::Ok(MWrapped(CAtomic(atomic_type(a)?))),
+        SigType::Var(Var::Wildcard(c, a)) => Result::Ok(MWrapped(CWarg(*c, attributes(a)?))),
+        SigType::Var(Var::TypeArg(c, a)) => Result::Ok(MWrapped(CTypeArg(*c, attributes(a)?))),
+        SigType::Var(Var::Ref(c)) => Result::Ok(MWrapped(CTypeArgRef(*c))),
+        SigType::Atomic(_) | SigType::App(..) => {
+            Result::Err(String::from("Malformed type variable"))
+        }
+    };
+    ts.iter().map(|t| from_signature(t, &leaf)).collect()
+}
+
+/// Builds the `StackResultElem`s of the output stack of a signature.
+pub fn stack_results_from_signature(ts: &[SigType]) -> Result<Vec<StackResult>, String> {
+    let leaf = |t: &SigType| match t {
+        SigType::Var(Var::Atomic(a)) => Result::Ok(MWrapped(ElemType(atomic_type(a)?))),
+        SigType::Var(Var::Ref(c)) => Result::Ok(MWrapped(TRef(*c))),
+        _ => Result::Err(String::from("Malformed type variable")),
+    };
+    ts.iter().map(|t| from_signature(t, &leaf)).collect()
+}
+
+/// Like `parse_constraints`, but reports errors instead of panicking.
+pub fn try_parse_constraints(cs: &str) -> Result<Vec<Constraint>, String> {
+    constraints_from_signature(&parse_types(cs, MAX_NESTING_DEPTH)?)
+        .map_err(|e| format!("{} in {}", e, cs))
+}
+
+/// Like `parse_stack_results`, but reports errors instead of panicking.
+pub fn try_parse_stack_results(cs: &str) -> Result<Vec<StackResult>, String> {
+    stack_results_from_signature(&parse_types(cs, MAX_NESTING_DEPTH)?)
+        .map_err(|e| format!("{} in {}", e, cs))
 }
 
 pub fn parse_stack(cs: &str) -> StackState<MAtomic> {
-    LiveStack(VecDeque::from(parse_mdyn_to(cs, mdyn_to_concrete)))
+    try_parse_stack(cs).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Like `parse_stack`, but reports malformed stacks and type variables
+/// instead of panicking.
+pub fn try_parse_stack(cs: &str) -> Result<ConcreteStack, String> {
+    let leaf = |t: &SigType| match t {
+        SigType::Atomic(a) => Result::Ok(MWrapped(atomic_type(a)?)),
+        _ => Result::Err(format!("Stack {} should not contain type variables", cs)),
+    };
+    let types = parse_types(cs, MAX_NESTING_DEPTH)?
+        .iter()
+        .map(|t| from_signature(t, &leaf))
+        .collect::<Result<SharedStack<_>, _>>()?;
+    Result::Ok(LiveStack(types))
 }
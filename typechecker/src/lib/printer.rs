@@ -0,0 +1,466 @@
+use serde_json::json;
+use serde_json::Value;
+
+use crate::encoding::*;
+use crate::micheline::Node;
+use crate::types::ArgValue;
+use crate::types::AtomicValue::*;
+use crate::types::CompositeValue::*;
+use crate::types::CompoundInstruction;
+use crate::types::CompoundInstruction::*;
+use crate::types::ConcreteType;
+use crate::types::Contract;
+use crate::types::LocatedInstruction;
+use crate::types::MType::*;
+use crate::types::MValue;
+use crate::types::MValue::*;
+use crate::types::SomeValue;
+use crate::types::SomeValue::*;
+
+// Printing of contracts, instructions and values back to Michelson, in the
+// layout used by the reference client, and to Micheline JSON. Annotations are
+// not kept by the parser, so they are not printed either.
+
+/// Values that can be printed as instruction arguments.
+pub trait PrintValue {
+    fn print_value(&self, out: &mut String);
+    // Whether the value needs parentheses when it is an argument.
+    fn is_application(&self) -> bool;
+    fn to_json(&self) -> Value;
+}
+
+fn column(out: &str) -> usize {
+    out.len() - out.rfind('\n').map(|i| i + 1).unwrap_or(0)
+}
+
+fn print_type_arg(t: &ConcreteType, out: &mut String) {
+    match t {
+        MWrapped(a) => out.push_str(&a.to_string()),
+        t => out.push_str(&format!("({})", t)),
+    }
+}
+
+fn print_value_arg<T: PrintValue>(v: &T, out: &mut String) {
+    if v.is_application() {
+        out.push('(');
+        v.print_value(out);
+        out.push(')');
+    } else {
+        v.print_value(out);
+    }
+}
+
+fn print_count(name: &str, n: usize, default: usize, out: &mut String) {
+    if n == default {
+        out.push_str(name);
+    } else {
+        out.push_str(&format!("{} {}", name, n));
+    }
+}
+
+pub fn print_block<T: PrintValue>(instructions: &[LocatedInstruction<T>], out: &mut String) {
+    if instructions.is_empty() {
+        out.push_str("{}");
+        return;
+    }
+    let c = column(out);
+    out.push_str("{ ");
+    for (k, instruction) in instructions.iter().enumerate() {
+        if k > 0 {
+            out.push_str(" ;\n");
+            out.push_str(&" ".repeat(c + 2));
+        }
+        print_instruction(&instruction.instruction, out);
+    }
+    out.push_str(" }");
+}
+
+fn print_branches<T: PrintValue>(
+    name: &str,
+    a: &[LocatedInstruction<T>],
+    b: &[LocatedInstruction<T>],
+    out: &mut String,
+) {
+    out.push_str(name);
+    out.push(' ');
+    let c = column(out);
+    print_block(a, out);
+    out.push('\n');
+    out.push_str(&" ".repeat(c));
+    print_block(b, out);
+}
+
+pub fn print_instruction<T: PrintValue>(instruction: &CompoundInstruction<T>, out: &mut String) {
+    match instruction {
+        Other(i) => {
+            out.push_str(&i.name);
+            for arg in &i.args {
+                out.push(' ');
+                match arg {
+                    ArgValue::TypeArg(t) => print_type_arg(t, out),
+                    ArgValue::ValueArg(v) => print_value_arg(v, out),
+                }
+            }
+        }
+        IF(a, b) | IF_CONS(a, b) | IF_SOME(a, b) | IF_NONE(a, b) | IF_LEFT(a, b) => {
+            print_branches(instruction.name(), a, b, out)
+        }
+        DIP(n, b) => {
+            print_count("DIP", *n, 1, out);
+            out.push(' ');
+            print_block(b, out);
+        }
+        SEQ(b) => print_block(b, out),
+        MAP(b) | ITER(b) | LOOP(b) | LOOP_LEFT(b) => {
+            out.push_str(instruction.name());
+            out.push(' ');
+            print_block(b, out);
+        }
+        LAMBDA_REC(t1, t2, b) => {
+            out.push_str("LAMBDA_REC ");
+            print_type_arg(t1, out);
+            out.push(' ');
+            print_type_arg(t2, out);
+            out.push(' ');
+            print_block(b, out);
+        }
+        PAIR(n) | UNPAIR(n) => print_count(instruction.name(), *n, 2, out),
+        DUP(n) | DROP(n) => print_count(instruction.name(), *n, 1, out),
+        DIG(n) | DUG(n) | GET(n) | UPDATE(n) => {
+            out.push_str(&format!("{} {}", instruction.name(), n))
+        }
+        SELF | FAIL | FAILWITH => out.push_str(instruction.name()),
+    }
+}
+
+pub fn print_contract<T: PrintValue>(contract: &Contract<T>) -> String {
+    let mut out = String::from("parameter ");
+    print_type_arg(&contract.parameter, &mut out);
+    out.push_str(" ;\nstorage ");
+    print_type_arg(&contract.storage, &mut out);
+    out.push_str(" ;\ncode ");
+    print_block(&contract.code, &mut out);
+    out.push('\n');
+    out
+}
+
+impl PrintValue for SomeValue {
+    fn print_value(&self, out: &mut String) {
+        match self {
+            Atomic(AVNumber(n)) => out.push_str(&n.to_string()),
+            Atomic(AVString(s)) => out.push_str(&format!("\"{}\"", s)),
+            Atomic(AVBool(b)) => out.push_str(if *b { "True" } else { "False" }),
+            Atomic(AVUnit) => out.push_str("Unit"),
+            Atomic(AVBytes(b)) => out.push_str(&format!("0x{}", bytes_to_hex(b))),
+            Composite(cv) => match cv.as_ref() {
+                CVPair(a, b) => {
+                    out.push_str("Pair ");
+                    print_value_arg(a, out);
+                    out.push(' ');
+                    print_value_arg(b, out);
+                }
+                CVLeft(v) => {
+                    out.push_str("Left ");
+                    print_value_arg(v, out);
+                }
+                CVRight(v) => {
+                    out.push_str("Right ");
+                    print_value_arg(v, out);
+                }
+                CVSome(v) => {
+                    out.push_str("Some ");
+                    print_value_arg(v, out);
+                }
+                CVNone => out.push_str("None"),
+                CVLambda(is) => print_block(is, out),
+                CVRaw(node) => out.push_str(&node.to_string()),
+                CVSeq(vs) => {
+                    if vs.is_empty() {
+                        out.push_str("{}");
+                    } else {
+                        out.push_str("{ ");
+                        for (k, v) in vs.iter().enumerate() {
+                            if k > 0 {
+                                out.push_str(" ; ");
+                            }
+                            v.print_value(out);
+                        }
+                        out.push_str(" }");
+                    }
+                }
+                CKVList(kvs) => {
+                    out.push_str("{ ");
+                    for (k, (key, v)) in kvs.iter().enumerate() {
+                        if k > 0 {
+                            out.push_str(" ; ");
+                        }
+                        out.push_str("Elt ");
+                        print_value_arg(key, out);
+                        out.push(' ');
+                        print_value_arg(v, out);
+                    }
+                    out.push_str(" }");
+                }
+            },
+        }
+    }
+
+    fn is_application(&self) -> bool {
+        match self {
+            Composite(cv) => matches!(
+                cv.as_ref(),
+                CVPair(_, _) | CVLeft(_) | CVRight(_) | CVSome(_)
+            ),
+            _ => false,
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        match self {
+            Atomic(AVNumber(n)) => json!({ "int": n.to_string() }),
+            Atomic(AVString(s)) => json!({ "string": s }),
+            Atomic(AVBool(b)) => prim(if *b { "True" } else { "False" }, vec![]),
+            Atomic(AVUnit) => prim("Unit", vec![]),
+            Atomic(AVBytes(b)) => json!({ "bytes": bytes_to_hex(b) }),
+            Composite(cv) => match cv.as_ref() {
+                CVPair(a, b) => prim("Pair", vec![a.to_json(), b.to_json()]),
+                CVLeft(v) => prim("Left", vec![v.to_json()]),
+                CVRight(v) => prim("Right", vec![v.to_json()]),
+                CVSome(v) => prim("Some", vec![v.to_json()]),
+                CVNone => prim("None", vec![]),
+                CVLambda(is) => block_to_json(is),
+                CVRaw(node) => node.to_json(),
+                CVSeq(vs) => Value::Array(vs.iter().map(|v| v.to_json()).collect()),
+                CKVList(kvs) => Value::Array(
+                    kvs.iter()
+                        .map(|(k, v)| prim("Elt", vec![k.to_json(), v.to_json()]))
+                        .collect(),
+                ),
+            },
+        }
+    }
+}
+
+fn prim(name: &str, args: Vec<Value>) -> Value {
+    if args.is_empty() {
+        json!({ "prim": name })
+    } else {
+        json!({ "prim": name, "args": args })
+    }
+}
+
+fn int(n: usize) -> Value {
+    json!({ "int": n.to_string() })
+}
+
+pub fn type_to_json(t: &ConcreteType) -> Value {
+    match t {
+        MWrapped(a) => prim(&a.to_string(), vec![]),
+        MTicket(t) => prim("ticket", vec![type_to_json(t)]),
+        MOption(t) => prim("option", vec![type_to_json(t)]),
+        MContract(t) => prim("contract", vec![type_to_json(t)]),
+        MList(t) => prim("list", vec![type_to_json(t)]),
+        MSet(t) => prim("set", vec![type_to_json(t)]),
+        MPair(b) => prim("pair", vec![type_to_json(&b.0), type_to_json(&b.1)]),
+        MOr(b) => prim("or", vec![type_to_json(&b.0), type_to_json(&b.1)]),
+        MLambda(b) => prim("lambda", vec![type_to_json(&b.0), type_to_json(&b.1)]),
+        MMap(b) => prim("map", vec![type_to_json(&b.0), type_to_json(&b.1)]),
+        MBigMap(b) => prim("big_map", vec![type_to_json(&b.0), type_to_json(&b.1)]),
+    }
+}
+
+pub fn block_to_json<T: PrintValue>(instructions: &[LocatedInstruction<T>]) -> Value {
+    Value::Array(
+        instructions
+            .iter()
+            .map(|i| instruction_to_json(&i.instruction))
+            .collect(),
+    )
+}
+
+fn count_to_json(name: &str, n: usize, default: usize) -> Value {
+    if n == default {
+        prim(name, vec![])
+    } else {
+        prim(name, vec![int(n)])
+    }
+}
+
+pub fn instruction_to_json<T: PrintValue>(instruction: &CompoundInstruction<T>) -> Value {
+    let name = instruction.name();
+    match instruction {
+        Other(i) => prim(
+            name,
+            i.args
+                .iter()
+                .map(|a| match a {
+                    ArgValue::TypeArg(t) => type_to_json(t),
+                    ArgValue::ValueArg(v) => v.to_json(),
+                })
+                .collect(),
+        ),
+        IF(a, b) | IF_CONS(a, b) | IF_SOME(a, b) | IF_NONE(a, b) | IF_LEFT(a, b) => {
+            prim(name, vec![block_to_json(a), block_to_json(b)])
+        }
+        DIP(n, b) => {
+            if *n == 1 {
+                prim(name, vec![block_to_json(b)])
+            } else {
+                prim(name, vec![int(*n), block_to_json(b)])
+            }
+        }
+        MAP(b) | ITER(b) | LOOP(b) | LOOP_LEFT(b) => prim(name, vec![block_to_json(b)]),
+        SEQ(b) => block_to_json(b),
+        LAMBDA_REC(t1, t2, b) => prim(
+            name,
+            vec![type_to_json(t1), type_to_json(t2), block_to_json(b)],
+        ),
+        PAIR(n) | UNPAIR(n) => count_to_json(name, *n, 2),
+        DUP(n) | DROP(n) => count_to_json(name, *n, 1),
+        DIG(n) | DUG(n) | GET(n) | UPDATE(n) => prim(name, vec![int(*n)]),
+        SELF | FAIL | FAILWITH => prim(name, vec![]),
+    }
+}
+
+pub fn contract_to_json<T: PrintValue>(contract: &Contract<T>) -> Value {
+    json!([
+        prim("parameter", vec![type_to_json(&contract.parameter)]),
+        prim("storage", vec![type_to_json(&contract.storage)]),
+        prim("code", vec![block_to_json(&contract.code)]),
+    ])
+}
+
+fn print_sequence<T>(items: &[T], out: &mut String, print: impl Fn(&T, &mut String)) {
+    if items.is_empty() {
+        out.push_str("{}");
+        return;
+    }
+    out.push_str("{ ");
+    for (k, item) in items.iter().enumerate() {
+        if k > 0 {
+            out.push_str(" ; ");
+        }
+        print(item, out);
+    }
+    out.push_str(" }");
+}
+
+fn print_elt(k: &MValue, v: &MValue, out: &mut String) {
+    out.push_str("Elt ");
+    print_value_arg(k, out);
+    out.push(' ');
+    print_value_arg(v, out);
+}
+
+impl PrintValue for MValue {
+    fn print_value(&self, out: &mut String) {
+        match self {
+            VUnit => out.push_str("Unit"),
+            VNat(n) => out.push_str(&n.to_string()),
+            VInt(i) => out.push_str(&i.to_string()),
+            VMutez(m) => out.push_str(&m.to_string()),
+            VBool(b) => out.push_str(if *b { "True" } else { "False" }),
+            VString(s) | VAddress(s) | VKeyHash(s) | VKey(s) | VSignature(s) | VChainId(s) => {
+                out.push_str(&format!("\"{}\"", s))
+            }
+            VBytes(b) => out.push_str(&format!("0x{}", bytes_to_hex(b))),
+            VTimestamp(t) => match timestamp_to_string(*t) {
+                Some(s) => out.push_str(&format!("\"{}\"", s)),
+                None => out.push_str(&t.to_string()),
+            },
+            VPair(b) => {
+                out.push_str("Pair ");
+                print_value_arg(&b.0, out);
+                out.push(' ');
+                print_value_arg(&b.1, out);
+            }
+            VLeft(v) | VRight(v) | VSome(v) => {
+                out.push_str(match self {
+                    VLeft(_) => "Left ",
+                    VRight(_) => "Right ",
+                    _ => "Some ",
+                });
+                print_value_arg(v.as_ref(), out);
+            }
+            VNone => out.push_str("None"),
+            VList(l) => print_sequence(l, out, |v, out| v.print_value(out)),
+            VSet(s) => print_sequence(&s.iter().collect::<Vec<_>>(), out, |v, out| {
+                v.print_value(out)
+            }),
+            VMap(m) | VBigMap(m) => {
+                print_sequence(&m.iter().collect::<Vec<_>>(), out, |(k, v), out| {
+                    print_elt(k, v, out)
+                })
+            }
+            VLambda(code) => print_block(code, out),
+        }
+    }
+
+    fn is_application(&self) -> bool {
+        matches!(self, VPair(_) | VLeft(_) | VRight(_) | VSome(_))
+    }
+
+    fn to_json(&self) -> Value {
+        match self {
+            VUnit => prim("Unit", vec![]),
+            VNat(n) => json!({ "int": n.to_string() }),
+            VInt(i) => json!({ "int": i.to_string() }),
+            VMutez(m) => json!({ "int": m.to_string() }),
+            VBool(b) => prim(if *b { "True" } else { "False" }, vec![]),
+            VString(s) | VAddress(s) | VKeyHash(s) | VKey(s) | VSignature(s) | VChainId(s) => {
+                json!({ "string": s })
+            }
+            VBytes(b) => json!({ "bytes": bytes_to_hex(b) }),
+            VTimestamp(t) => match timestamp_to_string(*t) {
+                Some(s) => json!({ "string": s }),
+                None => json!({ "int": t.to_string() }),
+            },
+            VPair(b) => prim("Pair", vec![b.0.to_json(), b.1.to_json()]),
+            VLeft(v) => prim("Left", vec![v.to_json()]),
+            VRight(v) => prim("Right", vec![v.to_json()]),
+            VSome(v) => prim("Some", vec![v.to_json()]),
+            VNone => prim("None", vec![]),
+            VList(l) => Value::Array(l.iter().map(|v| v.to_json()).collect()),
+            VSet(s) => Value::Array(s.iter().map(|v| v.to_json()).collect()),
+            VMap(m) | VBigMap(m) => Value::Array(
+                m.iter()
+                    .map(|(k, v)| prim("Elt", vec![k.to_json(), v.to_json()]))
+                    .collect(),
+            ),
+            VLambda(code) => block_to_json(code),
+        }
+    }
+}
+
+// Values display as the literals `parse_value` reads back.
+impl std::fmt::Display for MValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", print_value(self))
+    }
+}
+
+/// Prints a value as a Michelson literal.
+pub fn print_value<T: PrintValue>(value: &T) -> String {
+    let mut out = String::new();
+    value.print_value(&mut out);
+    out
+}
+
+/// Converts Micheline JSON back to Michelson source. A top level sequence is
+/// taken to be a contract, so its elements are separated with `;` instead of
+/// being wrapped in braces.
+pub fn micheline_to_source(v: &Value) -> Result<String, String> {
+    let mut out = String::new();
+    match v {
+        Value::Array(items) => {
+            for (k, item) in items.iter().enumerate() {
+                if k > 0 {
+                    out.push_str(" ;\n");
+                }
+                out.push_str(&Node::from_json(item)?.to_string());
+            }
+        }
+        v => out.push_str(&Node::from_json(v)?.to_string()),
+    }
+    Result::Ok(out)
+}
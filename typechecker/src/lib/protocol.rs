@@ -0,0 +1,325 @@
+use crate::instructions::parse_signatures;
+use crate::instructions::InstructionRegistry;
+use crate::instructions::MICHELSON_INSTRUCTIONS;
+use crate::typechecker::typecheck_contract_with;
+use crate::types::CompoundInstruction::*;
+use crate::types::MAtomic::*;
+use crate::types::MType::*;
+use crate::types::*;
+
+// The instruction set of a protocol is derived from the table of the latest
+// one, `MICHELSON_INSTRUCTIONS`, by undoing the changes made since.
+
+/// Protocols of Tezos, in order of activation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Protocol {
+    Athens,
+    Babylon,
+    Carthage,
+    Delphi,
+    Edo,
+    Florence,
+    Granada,
+    Hangzhou,
+    Ithaca,
+    Jakarta,
+    Kathmandu,
+    Lima,
+    Mumbai,
+    Nairobi,
+    Oxford,
+}
+
+use Protocol::*;
+
+impl Protocol {
+    pub const LATEST: Protocol = Oxford;
+
+    pub const ALL: [Protocol; 15] = [
+        Athens, Babylon, Carthage, Delphi, Edo, Florence, Granada, Hangzhou, Ithaca, Jakarta,
+        Kathmandu, Lima, Mumbai, Nairobi, Oxford,
+    ];
+
+    /// The largest type the protocol accepts, in type constructors, which is
+    /// its `michelson_maximum_type_size` constant.
+    pub fn max_type_size(self) -> usize {
+        if self < Babylon {
+            usize::MAX
+        } else {
+            2001
+        }
+    }
+
+    /// The deepest stack the protocol typechecks.
+    pub fn max_stack_depth(self) -> usize {
+        10_000
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Athens => "athens",
+            Babylon => "babylon",
+            Carthage => "carthage",
+            Delphi => "delphi",
+            Edo => "edo",
+            Florence => "florence",
+            Granada => "granada",
+            Hangzhou => "hangzhou",
+            Ithaca => "ithaca",
+            Jakarta => "jakarta",
+            Kathmandu => "kathmandu",
+            Lima => "lima",
+            Mumbai => "mumbai",
+            Nairobi => "nairobi",
+            Oxford => "oxford",
+        }
+    }
+}
+
+impl std::fmt::Display for Protocol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl std::str::FromStr for Protocol {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Protocol, String> {
+        Protocol::ALL
+            .into_iter()
+            .find(|p| p.name() == s.to_lowercase())
+            .ok_or_else(|| format!("Unknown protocol {}", s))
+    }
+}
+
+// A change to an instruction of the table made by `protocol`, with the
+// signatures the instruction had before, in the notation of
+// `parse_signatures`. An empty `before` means the instruction was added.
+struct Change {
+    protocol: Protocol,
+    name: &'static str,
+    before: &'static str,
+    note: &'static str,
+}
+
+const CHANGES: [Change; 17] = [
+    Change {
+        protocol: Babylon,
+        name: "CREATE_ACCOUNT",
+        before: "CREATE_ACCOUNT :: <a|key_hash>;option <a|key_hash>;<a|bool>;<a|mutez> \
+                 -> <a|operation>;<a|address>",
+        note: "is removed",
+    },
+    Change {
+        protocol: Babylon,
+        name: "STEPS_TO_QUOTA",
+        before: "STEPS_TO_QUOTA :: -> <a|nat>",
+        note: "is removed",
+    },
+    Change {
+        protocol: Babylon,
+        name: "APPLY",
+        before: "",
+        note: "is added",
+    },
+    Change {
+        protocol: Babylon,
+        name: "CHAIN_ID",
+        before: "",
+        note: "is added",
+    },
+    Change {
+        protocol: Babylon,
+        name: "EMPTY_BIG_MAP",
+        before: "",
+        note: "is added",
+    },
+    Change {
+        protocol: Edo,
+        name: "SELF_ADDRESS",
+        before: "",
+        note: "is added",
+    },
+    Change {
+        protocol: Edo,
+        name: "LEVEL",
+        before: "",
+        note: "is added",
+    },
+    Change {
+        protocol: Edo,
+        name: "TICKET",
+        before: "",
+        note: "is added",
+    },
+    Change {
+        protocol: Hangzhou,
+        name: "VIEW",
+        before: "",
+        note: "is added",
+    },
+    Change {
+        protocol: Ithaca,
+        name: "SUB",
+        before: "SUB :: <a|nat>;<a|nat> -> <a|int>
+                 SUB :: <a|int>;<a|int> -> <a|int>
+                 SUB :: <a|int>;<a|nat> -> <a|int>
+                 SUB :: <a|nat>;<a|int> -> <a|int>
+                 SUB :: <a|timestamp>;<a|int> -> <a|timestamp>
+                 SUB :: <a|timestamp>;<a|timestamp> -> <a|int>
+                 SUB :: <a|mutez>;<a|mutez> -> <a|mutez>",
+        note: "no longer applies to mutez, which SUB_MUTEZ subtracts",
+    },
+    Change {
+        protocol: Ithaca,
+        name: "SUB_MUTEZ",
+        before: "",
+        note: "is added",
+    },
+    Change {
+        protocol: Kathmandu,
+        name: "EMIT",
+        before: "",
+        note: "is added",
+    },
+    Change {
+        protocol: Lima,
+        name: "TICKET",
+        before: "TICKET :: <w|a=>comparable>;<a|nat> -> ticket <r|a>",
+        note: "returns an option",
+    },
+    Change {
+        protocol: Mumbai,
+        name: "NOT",
+        before: "NOT :: <a|bool> -> <a|bool>
+                 NOT :: <a|nat> -> <a|int>
+                 NOT :: <a|int> -> <a|int>",
+        note: "applies to bytes",
+    },
+    Change {
+        protocol: Mumbai,
+        name: "AND",
+        before: "AND :: <a|bool>;<a|bool> -> <a|bool>
+                 AND :: <a|nat>;<a|nat> -> <a|nat>
+                 AND :: <a|int>;<a|nat> -> <a|nat>",
+        note: "applies to bytes",
+    },
+    Change {
+        protocol: Mumbai,
+        name: "OR",
+        before: "OR :: <a|bool>;<a|bool> -> <a|bool>
+                 OR :: <a|nat>;<a|nat> -> <a|nat>",
+        note: "applies to bytes",
+    },
+    Change {
+        protocol: Mumbai,
+        name: "XOR",
+        before: "XOR :: <a|bool>;<a|bool> -> <a|bool>
+                 XOR :: <a|nat>;<a|nat> -> <a|nat>",
+        note: "applies to bytes",
+    },
+];
+
+impl InstructionRegistry {
+    /// The signatures of the instructions as of `protocol`.
+    pub fn for_protocol(protocol: Protocol) -> InstructionRegistry {
+        let mut registry = MICHELSON_INSTRUCTIONS.clone();
+        for change in CHANGES.iter().rev().filter(|c| c.protocol > protocol) {
+            let defs = parse_signatures(change.before)
+                .unwrap_or_else(|d| panic!("Malformed signature of {}: {}", change.name, d));
+            if defs.is_empty() {
+                registry.remove(change.name);
+            } else {
+                registry.set(change.name, defs.into_iter().map(|(_, d)| d).collect());
+            }
+        }
+        registry
+    }
+}
+
+/// Checks that the instructions typechecked on their own, rather than from
+/// the table, are available in `protocol`.
+pub fn check_instruction_available<T>(
+    instruction: &CompoundInstruction<T>,
+    protocol: Protocol,
+) -> Result<(), String> {
+    let since = match instruction {
+        LAMBDA_REC(_, _, _) => Lima,
+        DIG(_) | DUG(_) => Babylon,
+        DUP(n) if *n > 1 => Edo,
+        PAIR(n) if *n > 2 => Edo,
+        // Until then UNPAIR was a macro, which contracts are stored expanded.
+        UNPAIR(_) => Edo,
+        GET(_) | UPDATE(_) => Edo,
+        _ => return Result::Ok(()),
+    };
+    if protocol < since {
+        Result::Err(format!(
+            "{} is not available before {}",
+            instruction.name(),
+            since
+        ))
+    } else {
+        Result::Ok(())
+    }
+}
+
+/// Checks that every type constructor of `t` exists in `protocol`.
+pub fn check_type_available(t: &ConcreteType, protocol: Protocol) -> Result<(), String> {
+    match t {
+        MTicket(_) if protocol < Edo => {
+            Result::Err(format!("Type ticket is not available before {}", Edo))
+        }
+        MWrapped(MChainId) if protocol < Babylon => {
+            Result::Err(format!("Type chain_id is not available before {}", Babylon))
+        }
+        MWrapped(_) => Result::Ok(()),
+        MTicket(t) | MOption(t) | MContract(t) | MList(t) | MSet(t) => {
+            check_type_available(t, protocol)
+        }
+        MPair(b) | MOr(b) | MLambda(b) | MBigMap(b) | MMap(b) => {
+            check_type_available(&b.0, protocol)?;
+            check_type_available(&b.1, protocol)
+        }
+    }
+}
+
+fn typecheck_under(contract: &Contract<SomeValue>, protocol: Protocol) -> Vec<Diagnostic> {
+    let mut session = TcSession::default();
+    let instructions = InstructionRegistry::for_protocol(protocol);
+    typecheck_contract_with(protocol, &instructions, contract, &mut session);
+    session.diagnostics
+}
+
+/// What changes for a contract when moving from protocol `from` to `to`: the
+/// instructions it uses whose definition changes in between, and the errors
+/// it has under `to` but not under `from`.
+pub fn upgrade_report(
+    contract: &Contract<SomeValue>,
+    from: Protocol,
+    to: Protocol,
+) -> Vec<Diagnostic> {
+    let (low, high) = if from <= to { (from, to) } else { (to, from) };
+    let mut report = vec![];
+    for (_, instruction) in instructions_in_source_order(&contract.code) {
+        let name = instruction.instruction.name();
+        for change in CHANGES
+            .iter()
+            .filter(|c| c.name == name && low < c.protocol && c.protocol <= high)
+        {
+            report.push(Diagnostic {
+                location: Some(instruction.location),
+                kind: ErrorKind::Type,
+                message: format!("{} {} in {}", change.name, change.note, change.protocol),
+            });
+        }
+    }
+    let before = typecheck_under(contract, from);
+    report.extend(
+        typecheck_under(contract, to)
+            .into_iter()
+            .filter(|d| !before.contains(d)),
+    );
+    report
+}
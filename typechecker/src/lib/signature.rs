@@ -0,0 +1,371 @@
+// The notation of src/lib/instructions.sig, where a signature reads
+//
+//     NAME <t|a> :: <w|b=>comparable>;list <r|b> -> option <r|a>
+//
+// and of the stacks given on the command line, like `nat; pair int bool`.
+// The build script includes this file to generate `MICHELSON_INSTRUCTIONS`,
+// so it only depends on std, and each of them builds types from the syntax
+// read here.
+
+/// Atomic types, with the variant of `MAtomic` they are.
+pub const ATOMIC_TYPES: [(&str, &str); 14] = [
+    ("int", "MInt"),
+    ("nat", "MNat"),
+    ("key", "MKey"),
+    ("key_hash", "MKeyHash"),
+    ("chain_id", "MChainId"),
+    ("bytes", "MBytes"),
+    ("string", "MString"),
+    ("bool", "MBool"),
+    ("mutez", "MMutez"),
+    ("timestamp", "MTimestamp"),
+    ("address", "MAddress"),
+    ("operation", "MOperation"),
+    ("signature", "MSignature"),
+    ("unit", "MUnit"),
+];
+
+/// Attributes that type variables can require, with the variant of
+/// `Attribute` they are.
+pub const ATTRIBUTES: [(&str, &str); 7] = [
+    ("comparable", "Comparable"),
+    ("passable", "Passable"),
+    ("pushable", "Pushable"),
+    ("storable", "Storable"),
+    ("packable", "Packable"),
+    ("bigmapvalue", "BigmapValue"),
+    ("duplicable", "Duplicable"),
+];
+
+/// Type constructors of one argument, with the variant of `MType` they are.
+pub const UNARY: [(&str, &str); 5] = [
+    ("list", "MList"),
+    ("set", "MSet"),
+    ("ticket", "MTicket"),
+    ("option", "MOption"),
+    ("contract", "MContract"),
+];
+
+/// Type constructors of two arguments, with the variant of `MType` they are.
+pub const BINARY: [(&str, &str); 5] = [
+    ("pair", "MPair"),
+    ("or", "MOr"),
+    ("map", "MMap"),
+    ("big_map", "MBigMap"),
+    ("lambda", "MLambda"),
+];
+
+/// A type variable, between angle brackets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Var {
+    // `<a|nat>`: an atomic type.
+    Atomic(&'static str),
+    // `<w|a=>comparable>`: bound to the type found on the stack.
+    Wildcard(char, Vec<&'static str>),
+    // `<t|a>`: bound to a type argument of the instruction.
+    TypeArg(char, Vec<&'static str>),
+    // `<r|a>`: the type bound to `a`.
+    Ref(char),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SigType {
+    Atomic(&'static str),
+    Var(Var),
+    // A type constructor and its arguments. Combs of pairs are read as
+    // nested pairs.
+    App(&'static str, Vec<SigType>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature {
+    pub name: String,
+    pub args: Vec<SigType>,
+    pub input: Vec<SigType>,
+    pub output: Vec<SigType>,
+}
+
+// The name in `table` that is `name`, borrowed for as long as the table.
+fn lookup(table: &'static [(&'static str, &'static str)], name: &str) -> Option<&'static str> {
+    table.iter().find(|(n, _)| *n == name).map(|(n, _)| *n)
+}
+
+/// The Rust variant that `name` is in `table`.
+pub fn variant(table: &[(&str, &'static str)], name: &str) -> Option<&'static str> {
+    table.iter().find(|(n, _)| *n == name).map(|(_, v)| *v)
+}
+
+fn tokenize(src: &str) -> Result<Vec<String>, String> {
+    let mut tokens = vec![];
+    let mut chars = src.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            ' ' | '\t' | '\n' | '\r' => {
+                chars.next();
+            }
+            '(' | ')' | ';' => {
+                tokens.push(c.to_string());
+                chars.next();
+            }
+            '<' => {
+                let mut token = String::new();
+                // `=>` does not close a type variable.
+                for c in chars.by_ref() {
+                    token.push(c);
+                    if c == '>' && !token.ends_with("=>") {
+                        break;
+                    }
+                }
+                if !token.ends_with('>') || token.ends_with("=>") {
+                    return Err(format!("Unterminated type variable {}", token));
+                }
+                tokens.push(token);
+            }
+            // Annotations are left out.
+            '%' | '@' | ':' => {
+                chars.next();
+                while chars
+                    .peek()
+                    .is_some_and(|c| c.is_ascii_alphanumeric() || "_.%@".contains(*c))
+                {
+                    chars.next();
+                }
+            }
+            _ => {
+                let mut token = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_ascii_alphanumeric() || c == '_' {
+                        token.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                if token.is_empty() {
+                    return Err(format!("Unexpected character {}", c));
+                }
+                tokens.push(token);
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+fn variable(token: &str) -> Result<Var, String> {
+    let inner = &token[1..token.len() - 1];
+    let malformed = || format!("Malformed type variable {}", token);
+    let (tag, rest) = inner.split_once('|').ok_or_else(malformed)?;
+    let var = |s: &str| match s.as_bytes() {
+        [c] if c.is_ascii_lowercase() => Ok(*c as char),
+        _ => Err(malformed()),
+    };
+    match tag {
+        "a" => lookup(&ATOMIC_TYPES, rest)
+            .map(Var::Atomic)
+            .ok_or_else(|| format!("Unknown type {} in {}", rest, token)),
+        "r" => Ok(Var::Ref(var(rest)?)),
+        "w" | "t" => {
+            let (v, attributes) = match rest.split_once("=>") {
+                Some((v, attributes)) => (v, attributes.split('+').collect()),
+                None => (rest, vec![]),
+            };
+            let attributes = attributes
+                .into_iter()
+                .map(|a| {
+                    lookup(&ATTRIBUTES, a)
+                        .ok_or_else(|| format!("Unknown attribute {} in {}", a, token))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            if tag == "w" {
+                Ok(Var::Wildcard(var(v)?, attributes))
+            } else {
+                Ok(Var::TypeArg(var(v)?, attributes))
+            }
+        }
+        _ => Err(malformed()),
+    }
+}
+
+struct Parser {
+    tokens: Vec<String>,
+    position: usize,
+    // How deep the type being read nests, and how deep it may.
+    depth: usize,
+    max_depth: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Option<String> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.position).map(|t| t.as_str())
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<(), String> {
+        match self.next() {
+            Some(t) if t == expected => Ok(()),
+            Some(t) => Err(format!("Expecting {} but found {}", expected, t)),
+            None => Err(format!("Expecting {} at the end", expected)),
+        }
+    }
+
+    fn ty(&mut self) -> Result<SigType, String> {
+        self.depth += 1;
+        if self.depth > self.max_depth {
+            return Err(format!(
+                "Nesting exceeds the limit of {} levels",
+                self.max_depth
+            ));
+        }
+        let t = self.term();
+        self.depth -= 1;
+        t
+    }
+
+    fn term(&mut self) -> Result<SigType, String> {
+        let token = self.next().ok_or("Unexpected end of type")?;
+        if token.starts_with('<') {
+            return Ok(SigType::Var(variable(&token)?));
+        }
+        if token == "(" {
+            if self.peek() == Some("pair") {
+                self.next();
+                // The elements of a comb are as deep as the pairs they end
+                // up in.
+                let depth = self.depth;
+                let mut elems = vec![self.ty()?];
+                self.depth += 1;
+                elems.push(self.ty()?);
+                while self.peek().is_some_and(|t| t != ")") {
+                    self.depth += 1;
+                    elems.push(self.ty()?);
+                }
+                self.depth = depth;
+                self.expect(")")?;
+                let last = elems.pop().ok_or("Empty pair")?;
+                return Ok(elems
+                    .into_iter()
+                    .rev()
+                    .fold(last, |acc, t| SigType::App("pair", vec![t, acc])));
+            }
+            // Brackets do not nest the type they hold.
+            let t = self.term()?;
+            self.expect(")")?;
+            return Ok(t);
+        }
+        if let Some(c) = lookup(&UNARY, &token) {
+            return Ok(SigType::App(c, vec![self.ty()?]));
+        }
+        if let Some(c) = lookup(&BINARY, &token) {
+            let (t1, t2) = (self.ty()?, self.ty()?);
+            return Ok(SigType::App(c, vec![t1, t2]));
+        }
+        match lookup(&ATOMIC_TYPES, &token) {
+            Some(a) => Ok(SigType::Atomic(a)),
+            None => Err(format!("Unexpected {}", token)),
+        }
+    }
+
+    fn list(mut self) -> Result<Vec<SigType>, String> {
+        let mut types = vec![];
+        if self.peek().is_some() {
+            types.push(self.ty()?);
+            while self.peek() == Some(";") {
+                self.next();
+                types.push(self.ty()?);
+            }
+        }
+        match self.peek() {
+            None => Ok(types),
+            Some(t) => Err(format!("Unexpected {}", t)),
+        }
+    }
+}
+
+/// Reads types separated by `;`, as in a stack, that nest at most
+/// `max_depth` levels deep.
+pub fn parse_types(src: &str, max_depth: usize) -> Result<Vec<SigType>, String> {
+    Parser {
+        tokens: tokenize(src)?,
+        position: 0,
+        depth: 0,
+        max_depth,
+    }
+    .list()
+}
+
+impl SigType {
+    /// Whether every atomic type and type variable of the type satisfies
+    /// `leaf`.
+    pub fn all_leaves(&self, leaf: &impl Fn(&SigType) -> bool) -> bool {
+        match self {
+            SigType::App(_, args) => args.iter().all(|a| a.all_leaves(leaf)),
+            t => leaf(t),
+        }
+    }
+}
+
+/// Reads the signature of one variant of an instruction, in which the type
+/// arguments before `::` can be left out and either stack can be empty.
+pub fn parse_signature(line: &str, max_depth: usize) -> Result<Signature, String> {
+    let (head, stacks) = line
+        .split_once("::")
+        .ok_or_else(|| format!("Expecting `::` in signature {}", line))?;
+    let (input, output) = stacks
+        .split_once("->")
+        .ok_or_else(|| format!("Expecting `->` in signature {}", line))?;
+    let head = head.trim();
+    let (name, args) = head.split_once(' ').unwrap_or((head, ""));
+    let mut chars = name.chars();
+    if !chars.next().is_some_and(|c| c.is_ascii_uppercase())
+        || !chars.all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+    {
+        return Err(format!("Invalid instruction name {}", name));
+    }
+    let signature = Signature {
+        name: name.to_string(),
+        args: parse_types(args, max_depth)?,
+        input: parse_types(input, max_depth)?,
+        output: parse_types(output, max_depth)?,
+    };
+    // Atomic types are written as type variables, and the output only refers
+    // to what the arguments and input bound.
+    let constraint = |t: &SigType| matches!(t, SigType::Var(_));
+    let result = |t: &SigType| matches!(t, SigType::Var(Var::Atomic(_) | Var::Ref(_)));
+    if !signature
+        .args
+        .iter()
+        .chain(&signature.input)
+        .all(|t| t.all_leaves(&constraint))
+        || !signature.output.iter().all(|t| t.all_leaves(&result))
+    {
+        return Err(format!("Malformed type variable in {}", line));
+    }
+    Ok(signature)
+}
+
+/// Reads a file of signatures, one per line, where comments start with a
+/// hash. Signatures and errors come with the offset of their line.
+pub fn parse_signature_file(
+    src: &str,
+    max_depth: usize,
+) -> Result<Vec<(usize, Signature)>, (usize, String)> {
+    let mut signatures = vec![];
+    let mut offset = 0;
+    for line in src.split_inclusive('\n') {
+        let location = offset;
+        offset += line.len();
+        let line = line.split('#').next().unwrap_or("").trim();
+        if !line.is_empty() {
+            signatures.push((
+                location,
+                parse_signature(line, max_depth).map_err(|e| (location, e))?,
+            ));
+        }
+    }
+    Ok(signatures)
+}
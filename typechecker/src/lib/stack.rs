@@ -0,0 +1,207 @@
+use std::sync::Arc;
+
+// A persistent stack: a list of shared nodes from the top down. Clones share
+// every node, and pushing or popping only touches the top, so the branches of
+// a conditional and the body of a DIP share the part of the stack they leave
+// alone instead of each copying it.
+pub struct SharedStack<T> {
+    head: Option<Arc<Node<T>>>,
+    len: usize,
+}
+
+struct Node<T> {
+    elem: T,
+    next: Option<Arc<Node<T>>>,
+}
+
+impl<T> SharedStack<T> {
+    pub fn new() -> Self {
+        SharedStack { head: None, len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, i: usize) -> Option<&T> {
+        self.iter().nth(i)
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.head.as_deref(),
+        }
+    }
+
+    pub fn push_front(&mut self, elem: T) {
+        let next = self.head.take();
+        self.head = Some(Arc::new(Node { elem, next }));
+        self.len += 1;
+    }
+
+    /// The stack without its `n` topmost elements, sharing all its nodes.
+    pub fn skip(&self, n: usize) -> Self {
+        let mut head = self.head.as_ref();
+        for _ in 0..n.min(self.len) {
+            head = head.and_then(|node| node.next.as_ref());
+        }
+        SharedStack {
+            head: head.cloned(),
+            len: self.len.saturating_sub(n),
+        }
+    }
+}
+
+impl<T: Clone> SharedStack<T> {
+    // The top node is moved out when no other stack shares it, and copied
+    // otherwise.
+    pub fn pop_front(&mut self) -> Option<T> {
+        let node = self.head.take()?;
+        self.len -= 1;
+        match Arc::try_unwrap(node) {
+            Result::Ok(node) => {
+                self.head = node.next;
+                Some(node.elem)
+            }
+            Result::Err(node) => {
+                self.head = node.next.clone();
+                Some(node.elem.clone())
+            }
+        }
+    }
+
+    // Elements below the `i` topmost ones are left shared.
+    fn split_off_front(&mut self, i: usize) -> Vec<T> {
+        (0..i).filter_map(|_| self.pop_front()).collect()
+    }
+
+    fn extend_front(&mut self, elems: Vec<T>) {
+        for elem in elems.into_iter().rev() {
+            self.push_front(elem);
+        }
+    }
+
+    pub fn remove(&mut self, i: usize) -> Option<T> {
+        let top = self.split_off_front(i);
+        let elem = self.pop_front();
+        self.extend_front(top);
+        elem
+    }
+
+    pub fn insert(&mut self, i: usize, elem: T) {
+        let top = self.split_off_front(i);
+        self.push_front(elem);
+        self.extend_front(top);
+    }
+
+    pub fn set(&mut self, i: usize, elem: T) {
+        let top = self.split_off_front(i);
+        self.pop_front();
+        self.push_front(elem);
+        self.extend_front(top);
+    }
+
+    /// Puts the elements of `self` on top of `bottom`, which is left empty.
+    pub fn append(&mut self, bottom: &mut Self) {
+        let top: Vec<T> = self.iter().cloned().collect();
+        *self = std::mem::take(bottom);
+        self.extend_front(top);
+    }
+
+    pub fn take(&self, n: usize) -> Self {
+        self.iter().take(n).cloned().collect()
+    }
+}
+
+pub struct Iter<'a, T> {
+    next: Option<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let node = self.next?;
+        self.next = node.next.as_deref();
+        Some(&node.elem)
+    }
+}
+
+impl<T> Clone for SharedStack<T> {
+    fn clone(&self) -> Self {
+        SharedStack {
+            head: self.head.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T> Default for SharedStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Dropping the nodes one by one rather than recursively, so that a deep stack
+// cannot overflow the call stack.
+impl<T> Drop for SharedStack<T> {
+    fn drop(&mut self) {
+        let mut head = self.head.take();
+        while let Some(node) = head {
+            match Arc::try_unwrap(node) {
+                Result::Ok(mut node) => head = node.next.take(),
+                Result::Err(_) => break,
+            }
+        }
+    }
+}
+
+impl<T> FromIterator<T> for SharedStack<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let elems: Vec<T> = iter.into_iter().collect();
+        let mut stack = SharedStack::new();
+        for elem in elems.into_iter().rev() {
+            stack.push_front(elem);
+        }
+        stack
+    }
+}
+
+impl<T> From<Vec<T>> for SharedStack<T> {
+    fn from(v: Vec<T>) -> Self {
+        v.into_iter().collect()
+    }
+}
+
+// Stacks that share their bottom are compared down to the first shared node.
+impl<T: PartialEq> PartialEq for SharedStack<T> {
+    fn eq(&self, other: &Self) -> bool {
+        if self.len != other.len {
+            return false;
+        }
+        let (mut a, mut b) = (self.head.as_ref(), other.head.as_ref());
+        while let (Some(x), Some(y)) = (a, b) {
+            if Arc::ptr_eq(x, y) {
+                return true;
+            }
+            if x.elem != y.elem {
+                return false;
+            }
+            a = x.next.as_ref();
+            b = y.next.as_ref();
+        }
+        true
+    }
+}
+
+impl<T: Eq> Eq for SharedStack<T> {}
+
+impl<T: std::fmt::Debug> std::fmt::Debug for SharedStack<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
@@ -2,130 +2,200 @@
 use std::collections::BTreeMap;
 use std::collections::BTreeSet;
 use std::convert::TryFrom;
+use std::sync::Arc;
 
+use crate::encoding::*;
 use crate::types::ArgValue as AV;
 use crate::*;
 
 type ResolveCache = BTreeMap<char, ConcreteType>;
 
-fn add_symbol(resolved: &mut ResolveCache, arg_con: char, type_: &ConcreteType) {
-    resolved.insert(arg_con, type_.clone());
+// Why a type does not unify with a signature. Most mismatches are of overload
+// variants that are tried and dropped, so they are only formatted when they
+// are reported.
+#[derive(Debug)]
+enum Mismatch {
+    // The constructor the signature expects, and the type found instead.
+    Shape(&'static str, ConcreteType),
+    Atomic(MAtomic, ConcreteType),
+    Attribute(ConcreteType, Attribute),
+    Bound(char, ConcreteType, ConcreteType),
+    UnknownRef,
+    StackTooSmall(usize, usize),
+    TooFewValues,
+    Element(usize, Box<Mismatch>),
+    Other(String),
+}
+
+impl std::fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Mismatch::Shape(expected, found) => {
+                let article = if expected.starts_with('o') { "an" } else { "a" };
+                write!(f, "Expecting {} {} but found {}", article, expected, found)
+            }
+            Mismatch::Atomic(expected, found) => {
+                write!(f, "Expecting type {} but found {}", expected, found)
+            }
+            Mismatch::Attribute(t, a) => {
+                write!(f, "Type {} is not {}", t, format!("{:?}", a).to_lowercase())
+            }
+            Mismatch::Bound(c, bound, found) => write!(
+                f,
+                "Type variable '{} is bound to {} but {} was found",
+                c, bound, found
+            ),
+            Mismatch::UnknownRef => write!(f, "Unknown type ref"),
+            Mismatch::StackTooSmall(needed, found) => write!(
+                f,
+                "Stack was found too small for the operation: needed {}, found {}",
+                needed, found
+            ),
+            Mismatch::TooFewValues => write!(f, "Too few values in stack"),
+            Mismatch::Element(i, m) => write!(f, "At stack element {}: {}", i, m),
+            Mismatch::Other(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl From<String> for Mismatch {
+    fn from(message: String) -> Mismatch {
+        Mismatch::Other(message)
+    }
+}
+
+impl From<Mismatch> for String {
+    fn from(m: Mismatch) -> String {
+        m.to_string()
+    }
+}
+
+// Type variables of a signature are bound by their first occurrence, and every
+// later occurrence has to agree with that binding.
+fn add_symbol(
+    resolved: &mut ResolveCache,
+    arg_con: char,
+    type_: &ConcreteType,
+) -> Result<(), Mismatch> {
+    match resolved.get(&arg_con) {
+        Some(bound) if bound != type_ => {
+            Result::Err(Mismatch::Bound(arg_con, bound.clone(), type_.clone()))
+        }
+        Some(_) => Result::Ok(()),
+        None => {
+            resolved.insert(arg_con, type_.clone());
+            Result::Ok(())
+        }
+    }
 }
 
 fn unify_args(
     tcenv: &TcEnv,
     args: &[ArgValue<SomeValue>],
     arg_cons: &[Constraint],
+    session: &mut TcSession,
 ) -> Result<(ResolveCache, Vec<ArgValue<MValue>>), String> {
+    if args.len() != arg_cons.len() {
+        return Result::Err(format!(
+            "Expecting {} arguments but found {}",
+            arg_cons.len(),
+            args.len()
+        ));
+    }
     let mut resolved = BTreeMap::new();
     let mut args_ = Vec::new();
     for (arg, con) in args.iter().zip(arg_cons.iter()) {
-        args_.push(unify_arg(tcenv, &mut resolved, arg, con)?);
+        args_.push(unify_arg(tcenv, &mut resolved, arg, con, session)?);
     }
     Result::Ok((resolved, args_))
 }
 
+// A type left unknown by an error unifies with any constraint, and leaves the
+// type variables it meets unknown too, unless they are bound already.
+fn unify_unknown(resolved: &mut ResolveCache, arg_con: &Constraint) {
+    match arg_con {
+        MWrapped(CWarg(c, _)) | MWrapped(CTypeArg(c, _)) => {
+            resolved.entry(*c).or_insert(MWrapped(MUnknown));
+        }
+        MWrapped(_) => {}
+        MList(c) | MTicket(c) | MContract(c) | MOption(c) | MSet(c) => unify_unknown(resolved, c),
+        MPair(b) | MOr(b) | MLambda(b) | MBigMap(b) | MMap(b) => {
+            unify_unknown(resolved, &b.0);
+            unify_unknown(resolved, &b.1);
+        }
+    }
+}
+
+// Unifies depth first and left to right, as the order in which type variables
+// are bound matters, from an explicit work stack so that deeply nested types
+// cannot overflow the call stack. Constraints are cheap to clone as their
+// arguments are shared.
 fn unify_concrete_arg(
     resolved: &mut ResolveCache,
     arg: &ConcreteType,
     arg_con: &Constraint,
-) -> Result<(), String> {
-    match arg_con {
-        MWrapped(CWarg(c, _)) => {
-            add_symbol(resolved, *c, arg);
-            Result::Ok(())
-        }
-        MWrapped(CTypeArg(c, _)) => {
-            add_symbol(resolved, *c, arg);
-            Result::Ok(())
+) -> Result<(), Mismatch> {
+    let mut todo = vec![(arg, arg_con.clone())];
+    while let Some((arg, arg_con)) = todo.pop() {
+        if arg.is_unknown() {
+            unify_unknown(resolved, &arg_con);
+            continue;
         }
-        MWrapped(CTypeArgRef(c)) => match resolved.get(c) {
-            Some(tt) => unify_concrete_arg(resolved, arg, &map_mtype(tt, &|x| CAtomic(x.clone()))),
-            _ => Result::Err(String::from("Unknown type ref")),
-        },
-        MList(ic) => match arg {
-            MList(iv) => unify_concrete_arg(resolved, iv, ic),
-
-            _ => Result::Err(String::from("Expecting a list but got something else...")),
-        },
-        MTicket(ic) => match arg {
-            MTicket(iv) => unify_concrete_arg(resolved, iv, ic),
-
-            x => Result::Err(format!("Expecting a Ticket but got something else.{:?}", x)),
-        },
-        MContract(ic) => match arg {
-            MContract(iv) => unify_concrete_arg(resolved, iv, ic),
-
-            c => Result::Err(format!(
-                "Expecting a Contract but got something else {:?}",
-                c
-            )),
-        },
-        MOption(ic) => match arg {
-            MOption(iv) => unify_concrete_arg(resolved, iv, ic),
-
-            x => Result::Err(format!(
-                "Expecting a Option but got something else: {:?}",
-                x
-            )),
-        },
-        MSet(ic) => match arg {
-            MSet(iv) => unify_concrete_arg(resolved, iv, ic),
-
-            _ => Result::Err(String::from("Expecting a Option but got something else...")),
-        },
-        MLambda(b) => match arg {
-            MLambda(b1) => {
-                unify_concrete_arg(resolved, &b1.0, &b.0)?;
-                unify_concrete_arg(resolved, &b1.1, &b.1)
-            }
-            _ => Result::Err(String::from("Expecting a lambda but got something else...")),
-        },
-        MOr(b) => match arg {
-            MOr(b1) => {
-                unify_concrete_arg(resolved, &b1.0, &b.0)?;
-                unify_concrete_arg(resolved, &b1.1, &b.1)
-            }
-            _ => Result::Err(String::from("Expecting a pair but got something else...")),
-        },
-        MPair(b) => match arg {
-            MPair(b1) => {
-                unify_concrete_arg(resolved, &b1.0, &b.0)?;
-                unify_concrete_arg(resolved, &b1.1, &b.1)
-            }
-            _ => Result::Err(String::from("Expecting a pair but got something else...")),
-        },
-        MBigMap(b) => match arg {
-            MBigMap(b1) => {
-                unify_concrete_arg(resolved, &b1.0, &b.0)?;
-                unify_concrete_arg(resolved, &b1.1, &b.1)
-            }
-            _ => Result::Err(String::from(
-                "Expecting a big_map but got something else...",
-            )),
-        },
-        MMap(b) => match arg {
-            MMap(b1) => {
-                unify_concrete_arg(resolved, &b1.0, &b.0)?;
-                unify_concrete_arg(resolved, &b1.1, &b.1)
-            }
-            _ => Result::Err(String::from("Expecting a map but got something else...")),
-        },
-        MWrapped(CAtomic(at)) => match arg {
-            MWrapped(cn) => {
-                if at == cn {
-                    Result::Ok(())
-                } else {
-                    Result::Err(format!(
-                        "Expecting type {:?}, but found something else: {:?}",
-                        at, cn
-                    ))
+        match &arg_con {
+            MWrapped(CWarg(c, atrs)) => match atrs.iter().find(|a| !check_attribute(a, arg)) {
+                Some(a) => return Result::Err(Mismatch::Attribute(arg.clone(), a.clone())),
+                None => add_symbol(resolved, *c, arg)?,
+            },
+            MWrapped(CTypeArg(c, _)) => add_symbol(resolved, *c, arg)?,
+            MWrapped(CTypeArgRef(c)) => match resolved.get(c) {
+                Some(tt) => todo.push((arg, map_mtype(tt, &|x| CAtomic(x.clone())))),
+                _ => return Result::Err(Mismatch::UnknownRef),
+            },
+            MList(ic) | MTicket(ic) | MContract(ic) | MOption(ic) | MSet(ic) => {
+                match (&arg_con, arg) {
+                    (MList(_), MList(iv))
+                    | (MTicket(_), MTicket(iv))
+                    | (MContract(_), MContract(iv))
+                    | (MOption(_), MOption(iv))
+                    | (MSet(_), MSet(iv)) => todo.push((iv, ic.as_ref().clone())),
+                    _ => return Result::Err(Mismatch::Shape(constructor(&arg_con), arg.clone())),
                 }
             }
-            _ => Result::Err(String::from(
-                "Expecting an atomic type, but found something else",
-            )),
-        },
+            MLambda(b) | MOr(b) | MPair(b) | MBigMap(b) | MMap(b) => {
+                let b1 = match (&arg_con, arg) {
+                    (MLambda(_), MLambda(b1))
+                    | (MOr(_), MOr(b1))
+                    | (MPair(_), MPair(b1))
+                    | (MBigMap(_), MBigMap(b1))
+                    | (MMap(_), MMap(b1)) => b1,
+                    _ => return Result::Err(Mismatch::Shape(constructor(&arg_con), arg.clone())),
+                };
+                todo.push((&b1.1, b.1.clone()));
+                todo.push((&b1.0, b.0.clone()));
+            }
+            MWrapped(CAtomic(at)) => match arg {
+                MWrapped(cn) if at == cn || *at == MUnknown => {}
+                _ => return Result::Err(Mismatch::Atomic(at.clone(), arg.clone())),
+            },
+        }
+    }
+    Result::Ok(())
+}
+
+// The name of the outermost constructor of a signature type.
+fn constructor(c: &Constraint) -> &'static str {
+    match c {
+        MTicket(_) => "ticket",
+        MOption(_) => "option",
+        MContract(_) => "contract",
+        MPair(_) => "pair",
+        MOr(_) => "or",
+        MList(_) => "list",
+        MLambda(_) => "lambda",
+        MBigMap(_) => "big_map",
+        MMap(_) => "map",
+        MSet(_) => "set",
+        MWrapped(_) => "type",
     }
 }
 
@@ -134,12 +204,14 @@ fn unify_arg(
     resolved: &mut ResolveCache,
     arg: &ArgValue<SomeValue>,
     arg_con: &Constraint,
+    session: &mut TcSession,
 ) -> Result<ArgValue<MValue>, String> {
     match arg {
         AV::TypeArg(ct) => match arg_con {
             MWrapped(CTypeArg(c, rattr)) => {
+                check_type_available(ct, tcenv.protocol)?;
                 if check_attributes(rattr, ct) {
-                    add_symbol(resolved, *c, ct);
+                    add_symbol(resolved, *c, ct)?;
                     Result::Ok(AV::TypeArg((*ct).clone()))
                 } else {
                     Result::Err(String::from("Type does not meet the required constraints"))
@@ -150,20 +222,20 @@ fn unify_arg(
         AV::ValueArg(some_val) => {
             let (m, ct): (MValue, ConcreteType) = match arg_con {
                 MWrapped(CTypeArg(_, _)) => {
-                    panic!("Unexpected value argument");
+                    return Result::Err(String::from("Expecting a type but found a value"));
                 }
                 MWrapped(CWarg(_, _)) => {
-                    panic!("Unexpected wildcard type encountered");
+                    return Result::Err(String::from("Unexpected wildcard type encountered"));
                 }
                 MWrapped(CTypeArgRef(ref c)) => match resolved.get(c) {
-                    Some(ct) => typecheck_value(tcenv, resolved, some_val, ct)?,
-                    None => panic!("Symbol resolution failed! {:?}", c),
+                    Some(ct) => typecheck_value(tcenv, resolved, some_val, ct, session)?,
+                    None => return Result::Err(format!("Symbol resolution failed! {:?}", c)),
                 },
                 _ => match constraint_to_concrete(resolved, arg_con) {
                     Some(concrete_type) => {
-                        typecheck_value(tcenv, resolved, some_val, &concrete_type)?
+                        typecheck_value(tcenv, resolved, some_val, &concrete_type, session)?
                     }
-                    None => panic!("Couldnt resolve type"),
+                    None => return Result::Err(String::from("Couldnt resolve type")),
                 },
             };
             unify_concrete_arg(resolved, &ct, arg_con)?;
@@ -175,148 +247,202 @@ fn unify_arg(
 fn constraint_to_concrete(resolved: &ResolveCache, c: &Constraint) -> Option<ConcreteType> {
     match c {
         MWrapped(CTypeArgRef(c)) => resolved.get(c).cloned(),
-        MPair(b) => Some(MPair(Box::new((
+        MPair(b) => Some(MPair(Arc::new((
             constraint_to_concrete(resolved, &b.0)?,
             constraint_to_concrete(resolved, &b.1)?,
         )))),
-        MOr(b) => Some(MOr(Box::new((
+        MOr(b) => Some(MOr(Arc::new((
             constraint_to_concrete(resolved, &b.0)?,
             constraint_to_concrete(resolved, &b.1)?,
         )))),
-        MMap(b) => Some(MMap(Box::new((
+        MMap(b) => Some(MMap(Arc::new((
             constraint_to_concrete(resolved, &b.0)?,
             constraint_to_concrete(resolved, &b.1)?,
         )))),
-        MBigMap(b) => Some(MBigMap(Box::new((
+        MBigMap(b) => Some(MBigMap(Arc::new((
             constraint_to_concrete(resolved, &b.0)?,
             constraint_to_concrete(resolved, &b.1)?,
         )))),
-        MSet(l) => Some(MSet(Box::new(constraint_to_concrete(resolved, l)?))),
-        MList(l) => Some(MList(Box::new(constraint_to_concrete(resolved, l)?))),
-        MTicket(l) => Some(MTicket(Box::new(constraint_to_concrete(resolved, l)?))),
-        MOption(l) => Some(MOption(Box::new(constraint_to_concrete(resolved, l)?))),
-        MContract(l) => Some(MContract(Box::new(constraint_to_concrete(resolved, l)?))),
-        MLambda(b) => Some(MLambda(Box::new((
+        MSet(l) => Some(MSet(Arc::new(constraint_to_concrete(resolved, l)?))),
+        MList(l) => Some(MList(Arc::new(constraint_to_concrete(resolved, l)?))),
+        MTicket(l) => Some(MTicket(Arc::new(constraint_to_concrete(resolved, l)?))),
+        MOption(l) => Some(MOption(Arc::new(constraint_to_concrete(resolved, l)?))),
+        MContract(l) => Some(MContract(Arc::new(constraint_to_concrete(resolved, l)?))),
+        MLambda(b) => Some(MLambda(Arc::new((
             constraint_to_concrete(resolved, &b.0)?,
             constraint_to_concrete(resolved, &b.1)?,
         )))),
         MWrapped(CWarg(_, _)) => None,
         MWrapped(CTypeArg(_, _)) => None,
-        MWrapped(CAtomic(_)) => None
+        MWrapped(CAtomic(a)) => Some(MWrapped(a.clone())),
     }
 }
 
+// Elements of set literals and keys of map literals must be given in strictly
+// increasing order, which also rules out duplicates.
+fn ensure_increasing(previous: Option<&MValue>, next: &MValue, what: &str) -> Result<(), String> {
+    match previous {
+        Some(p) if p >= next => Err(format!(
+            "{} should be in strictly increasing order, but {} follows {}",
+            what,
+            print_value(next),
+            print_value(p)
+        )),
+        _ => Ok(()),
+    }
+}
+
+// A sequence read before its type was known is elaborated against the type
+// it is expected to have. The code of lambdas elaborated here is dropped
+// afterwards, so the stack trace, which points into the code it was given,
+// leaves it out.
+fn typecheck_raw(
+    tcenv: &TcEnv,
+    resolved: &ResolveCache,
+    node: &Node,
+    target: &ConcreteType,
+    session: &mut TcSession,
+) -> Result<(MValue, ConcreteType), String> {
+    let value = elaborate_typed_value(node, target).map_err(|d| d.message)?;
+    if matches!(&value, Composite(cv) if matches!(cv.as_ref(), CVRaw(_))) {
+        return Err(format!(
+            "Expecting a value of type {} but found {}",
+            target, node
+        ));
+    }
+    let trace = session.stack_trace.take();
+    let result = typecheck_value(tcenv, resolved, &value, target, session);
+    session.stack_trace = trace;
+    result
+}
+
 fn typecheck_value(
     tcenv: &TcEnv,
     _resolved: &ResolveCache,
     some_val: &SomeValue,
     target: &ConcreteType,
+    session: &mut TcSession,
 ) -> Result<(MValue, ConcreteType), String> {
+    session.burn(1, None)?;
+    if let Composite(cv) = some_val {
+        if let CVRaw(node) = cv.as_ref() {
+            return typecheck_raw(tcenv, _resolved, node, target, session);
+        }
+    }
     match (target, some_val) {
         (MWrapped(MUnit), Atomic(AVUnit)) => Ok((VUnit, MWrapped(MUnit))),
         (MWrapped(MBool), Atomic(AVBool(n))) => Ok((VBool(*n), MWrapped(MBool))),
-        (MWrapped(MMutez), Atomic(AVNumber(n))) => match u32::try_from(*n) {
-            Ok(n1) => Ok((VMutez(n1), MWrapped(MMutez))),
-            Err(_) => Err(String::from("Expecting a Nat but found an Int")),
+        (MWrapped(MMutez), Atomic(AVNumber(n))) => match i64::try_from(n) {
+            Ok(n1 @ 0..=MAX_MUTEZ) => Ok((VMutez(n1), MWrapped(MMutez))),
+            _ => Err(format!("Mutez amount {} is out of range", n)),
         },
-        (MWrapped(MNat), Atomic(AVNumber(n))) => match u32::try_from(*n) {
-            Ok(n1) => Ok((VNat(n1), MWrapped(MNat))),
-            Err(_) => Err(String::from("Expecting a Nat but found an Int")),
+        (MWrapped(MNat), Atomic(AVNumber(n))) => match n.to_biguint() {
+            Some(n1) => Ok((VNat(n1), MWrapped(MNat))),
+            None => Err(String::from("Expecting a Nat but found an Int")),
         },
-        (MWrapped(MInt), Atomic(AVNumber(n))) => Ok((VInt(*n), MWrapped(MInt))),
+        (MWrapped(MInt), Atomic(AVNumber(n))) => Ok((VInt(n.clone()), MWrapped(MInt))),
         (MWrapped(MString), Atomic(AVString(s))) => Ok((VString(s.clone()), MWrapped(MString))),
-        (MSet(c), Composite(cv)) => match cv.as_ref() {
-            CVSeq(SqValue(items)) => {
-                let mut il: BTreeSet<MValue> = BTreeSet::new();
-                for i in items {
-                    let (mv, _) = typecheck_value(tcenv, _resolved, i, c.as_ref())?;
-                    il.insert(mv);
+        (MWrapped(MBytes), Atomic(AVBytes(b))) => Ok((VBytes(b.clone()), MWrapped(MBytes))),
+        (MWrapped(MTimestamp), Atomic(AVNumber(n))) => match i64::try_from(n) {
+            Ok(t) => Ok((VTimestamp(t), MWrapped(MTimestamp))),
+            Err(_) => Err(format!("Timestamp {} is out of range", n)),
+        },
+        (MWrapped(MTimestamp), Atomic(AVString(s))) => match timestamp_from_string(s) {
+            Some(t) => Ok((VTimestamp(t), MWrapped(MTimestamp))),
+            None => Err(format!("Invalid timestamp {}", s)),
+        },
+        (
+            MWrapped(a @ (MAddress | MKeyHash | MKey | MSignature | MChainId)),
+            Atomic(AVString(s)),
+        ) => {
+            let (valid, value) = match a {
+                MAddress => (address_to_bytes(s).is_some(), VAddress(s.clone())),
+                MKeyHash => (key_hash_to_bytes(s).is_some(), VKeyHash(s.clone())),
+                MKey => (key_to_bytes(s).is_some(), VKey(s.clone())),
+                MSignature => (signature_to_bytes(s).is_some(), VSignature(s.clone())),
+                _ => (chain_id_to_bytes(s).is_some(), VChainId(s.clone())),
+            };
+            if valid {
+                Ok((value, MWrapped(a.clone())))
+            } else {
+                Err(format!("Invalid {} {}", a, s))
+            }
+        }
+        (MSet(c), Composite(cv)) => {
+            if !check_attribute(&Comparable, c) {
+                return Err(String::from("Set elements should be comparable"));
+            }
+            match cv.as_ref() {
+                CVSeq(items) => {
+                    let mut il: BTreeSet<MValue> = BTreeSet::new();
+                    for i in items {
+                        let (mv, _) = typecheck_value(tcenv, _resolved, i, c.as_ref(), session)?;
+                        ensure_increasing(il.last(), &mv, "Set elements")?;
+                        il.insert(mv);
+                    }
+                    Ok((VSet(il), MSet(c.clone())))
                 }
-                Ok((VSet(il), MSet(c.clone())))
+                _ => Err(String::from(
+                    "Expecting a Sequence but found something else...",
+                )),
             }
-            _ => Err(String::from(
-                "Expecting a Sequence but found something else...",
-            )),
-        },
+        }
         (MList(c), Composite(cv)) => match cv.as_ref() {
             CVSeq(items) => {
-                if items.len() == 0 {
-                    Ok((VList(vec![]), MList(c.clone())))
-                } else {
-                    match items {
-                        SqValue(items) => {
-                            let mut il: Vec<MValue> = vec![];
-                            for i in items {
-                                let (mv, _) = typecheck_value(tcenv, _resolved, i, c.as_ref())?;
-                                il.push(mv);
-                            }
-                            Ok((VList(il), MList(c.clone())))
-                        }
-                        SqInstr(_) => Err(String::from(
-                            "Expecting a list of values but found instructions..",
-                        )),
-                    }
+                let mut il: Vec<MValue> = vec![];
+                for i in items {
+                    let (mv, _) = typecheck_value(tcenv, _resolved, i, c.as_ref(), session)?;
+                    il.push(mv);
                 }
+                Ok((VList(il), MList(c.clone())))
             }
             _ => Err(String::from("Expecting a List but found something else...")),
         },
-        (MMap(b), Composite(cv)) => match cv.as_ref() {
-            CVSeq(x) => {
-                if x.len() == 0 {
-                    let (kt, vt) = b.as_ref();
-                    Ok((
-                        VMap(BTreeMap::default()),
-                        MMap(Box::new((kt.clone(), vt.clone()))),
-                    ))
-                } else {
-                    Err(String::from(
-                        "Expecting a key/value list but found value list...",
-                    ))
-                }
+        (MMap(b) | MBigMap(b), Composite(cv)) => {
+            let (kt, vt) = b.as_ref();
+            if !check_attribute(&Comparable, kt) {
+                return Err(String::from("Map keys should be comparable"));
+            }
+            if matches!(target, MBigMap(_)) && !check_attribute(&BigmapValue, vt) {
+                return Err(String::from("Type not allowed to be a big_map value"));
             }
-            CKVList(items) => {
-                let mut hm: BTreeMap<MValue, MValue> = BTreeMap::new();
-                let (kt, vt) = b.as_ref();
-                if check_attribute(&Comparable, kt) {
+            let hm = match cv.as_ref() {
+                CKVList(items) => {
+                    let mut hm: BTreeMap<MValue, MValue> = BTreeMap::new();
                     for (k, v) in items {
-                        let (mkv, _) = typecheck_value(tcenv, _resolved, k, kt)?;
-                        let (mvv, _) = typecheck_value(tcenv, _resolved, v, vt)?;
+                        let (mkv, _) = typecheck_value(tcenv, _resolved, k, kt, session)?;
+                        let (mvv, _) = typecheck_value(tcenv, _resolved, v, vt, session)?;
+                        ensure_increasing(hm.keys().last(), &mkv, "Map keys")?;
                         hm.insert(mkv, mvv);
                     }
-                    Ok((VMap(hm), MMap(Box::new((kt.clone(), vt.clone())))))
-                } else {
-                    Err(String::from("Big map keys should be comparable"))
-                }
-            }
-            _ => Err(String::from("Expecting a map but found something else...")),
-        },
-        (MBigMap(b), Composite(cv)) => match cv.as_ref() {
-            CKVList(items) => {
-                let mut hm: BTreeMap<MValue, MValue> = BTreeMap::new();
-                let (kt, vt) = b.as_ref();
-                if check_attribute(&Comparable, kt) {
-                    if check_attribute(&BigmapValue, vt) {
-                        for (k, v) in items {
-                            let (mkv, _) = typecheck_value(tcenv, _resolved, k, kt)?;
-                            let (mvv, _) = typecheck_value(tcenv, _resolved, v, vt)?;
-                            hm.insert(mkv, mvv);
-                        }
-                        Ok((VBigMap(hm), MBigMap(Box::new((kt.clone(), vt.clone())))))
-                    } else {
-                        Err(String::from("Type not allowed to be a big_map value"))
-                    }
-                } else {
-                    Err(String::from("Big map keys should be comparable"))
+                    hm
                 }
+                _ => return Err(String::from("Expecting a map but found something else...")),
+            };
+            if matches!(target, MBigMap(_)) {
+                Ok((VBigMap(hm), target.clone()))
+            } else {
+                Ok((VMap(hm), target.clone()))
             }
-            _ => Err(String::from("Expecting a map but found something else...")),
-        },
+        }
         (MPair(b), Composite(cv)) => match cv.as_ref() {
             CVPair(sv1, sv2) => {
                 let (c1, c2) = b.as_ref();
-                let (mv1, ct1) = typecheck_value(tcenv, _resolved, sv1, c1)?;
-                let (mv2, ct2) = typecheck_value(tcenv, _resolved, sv2, c2)?;
-                Result::Ok((VPair(Box::new((mv1, mv2))), MPair(Box::new((ct1, ct2)))))
+                let (mv1, ct1) = typecheck_value(tcenv, _resolved, sv1, c1, session)?;
+                let (mv2, ct2) = typecheck_value(tcenv, _resolved, sv2, c2, session)?;
+                Result::Ok((VPair(Box::new((mv1, mv2))), MPair(Arc::new((ct1, ct2)))))
+            }
+            // `{ a ; b ; c }` is `Pair a b c`.
+            CVSeq(items) if items.len() >= 2 => {
+                let (c1, c2) = b.as_ref();
+                let rest = if items.len() == 2 {
+                    items[1].clone()
+                } else {
+                    Composite(Box::new(CVSeq(items[1..].to_vec())))
+                };
+                let (mv1, ct1) = typecheck_value(tcenv, _resolved, &items[0], c1, session)?;
+                let (mv2, ct2) = typecheck_value(tcenv, _resolved, &rest, c2, session)?;
+                Result::Ok((VPair(Box::new((mv1, mv2))), MPair(Arc::new((ct1, ct2)))))
             }
             _ => Err(String::from("Expecting a Pair but found something else...")),
         },
@@ -324,11 +450,11 @@ fn typecheck_value(
             let (c1, c2) = b.as_ref();
             match cv.as_ref() {
                 CVLeft(sv1) => {
-                    let (mv1, _) = typecheck_value(tcenv, _resolved, sv1, c1)?;
+                    let (mv1, _) = typecheck_value(tcenv, _resolved, sv1, c1, session)?;
                     Result::Ok((VLeft(Box::new(mv1)), MOr(b.clone())))
                 }
                 CVRight(sv1) => {
-                    let (mv1, _) = typecheck_value(tcenv, _resolved, sv1, c2)?;
+                    let (mv1, _) = typecheck_value(tcenv, _resolved, sv1, c2, session)?;
                     Result::Ok((VRight(Box::new(mv1)), MOr(b.clone())))
                 }
                 _ => Err(String::from(
@@ -337,23 +463,19 @@ fn typecheck_value(
             }
         }
         (MLambda(b), Composite(cv)) => match cv.as_ref() {
-            CVSeq(SqInstr(instructions)) => {
+            CVLambda(instructions) => {
                 let (c1, c2) = b.as_ref();
                 let lambda_input = c1.clone();
                 let lambda_output = c2.clone();
                 let mut stack: ConcreteStack = StackState::from(vec![lambda_input.clone()]);
-                match typecheck(tcenv, instructions, &mut stack) {
-                    Ok(tins) => {
-                        if stack.compare_singleton(&lambda_output) {
-                            Result::Ok((
-                                VLambda(tins),
-                                MLambda(Box::new((lambda_input, lambda_output))),
-                            ))
-                        } else {
-                            Err(String::from("Lambda does not match the expected type"))
-                        }
-                    }
-                    Err(s) => Err(s),
+                let tins = typecheck_memoized(tcenv, instructions, &mut stack, session);
+                if stack.compare_singleton(&lambda_output) {
+                    Result::Ok((
+                        VLambda(tins),
+                        MLambda(Arc::new((lambda_input, lambda_output))),
+                    ))
+                } else {
+                    Err(String::from("Lambda does not match the expected type"))
                 }
             }
             _ => Err(String::from(
@@ -363,652 +485,1388 @@ fn typecheck_value(
 
         (MOption(b), Composite(cv)) => match cv.as_ref() {
             CVSome(v) => {
-                let (tv, vt) = typecheck_value(tcenv, _resolved, v, b.as_ref())?;
-                Result::Ok((VSome(Box::new(tv)), MOption(Box::new(vt))))
+                let (tv, vt) = typecheck_value(tcenv, _resolved, v, b.as_ref(), session)?;
+                Result::Ok((VSome(Box::new(tv)), MOption(Arc::new(vt))))
             }
-            x => Err(format!(
-                "Expecting an Option value but found something else: {:?}",
-                x
+            CVNone => Result::Ok((VNone, target.clone())),
+            _ => Err(String::from(
+                "Expecting an Option value but found something else...",
             )),
         },
-        (x, y) => Err(format!("Error type mismatch {:?} {:?}", x, y)),
+        (t, _) => Err(format!("Expecting a value of type {}", t)),
     }
 }
 
-fn stack_result_to_concrete_type(resolved: &mut ResolveCache, sr: &StackResult) -> ConcreteType {
-    match sr {
-        MWrapped(wrp) => match wrp {
-            ElemType(et) => MWrapped(et.clone()),
-            TRef(c) => match resolved.get(c) {
-                Some(ct) => (*ct).clone(),
-                None => {
-                    panic!("Symbol resolution failed! {:?}", c)
-                }
-            },
+/// Typechecks a value against a type outside of any instruction.
+pub fn typecheck_data(
+    tcenv: &TcEnv,
+    some_val: &SomeValue,
+    target: &ConcreteType,
+) -> Result<MValue, String> {
+    let mut session = TcSession::default();
+    let (value, _) = typecheck_value(tcenv, &ResolveCache::new(), some_val, target, &mut session)?;
+    match session.diagnostics.first() {
+        Some(d) => Result::Err(d.to_string()),
+        None => Result::Ok(value),
+    }
+}
+
+// Signatures loaded at run time can refer in their output to type variables
+// that their input does not bind.
+fn stack_result_to_concrete_type(
+    resolved: &mut ResolveCache,
+    sr: &StackResult,
+) -> Result<ConcreteType, String> {
+    let mut pair = |b: &(StackResult, StackResult)| -> Result<_, String> {
+        Result::Ok(Arc::new((
+            stack_result_to_concrete_type(resolved, &b.0)?,
+            stack_result_to_concrete_type(resolved, &b.1)?,
+        )))
+    };
+    Result::Ok(match sr {
+        MWrapped(ElemType(et)) => MWrapped(et.clone()),
+        MWrapped(TRef(c)) => match resolved.get(c) {
+            Some(ct) => (*ct).clone(),
+            None => return Result::Err(format!("Type variable {} of the output is not bound", c)),
         },
-        MList(l) => MList(Box::new(stack_result_to_concrete_type(
-            resolved,
-            l.as_ref(),
-        ))),
-        MContract(l) => MContract(Box::new(stack_result_to_concrete_type(
-            resolved,
-            l.as_ref(),
-        ))),
-        MTicket(l) => MTicket(Box::new(stack_result_to_concrete_type(
-            resolved,
-            l.as_ref(),
-        ))),
-        MOption(l) => MOption(Box::new(stack_result_to_concrete_type(
-            resolved,
-            l.as_ref(),
-        ))),
-        MSet(l) => MSet(Box::new(stack_result_to_concrete_type(
-            resolved,
-            l.as_ref(),
-        ))),
-        MMap(b) => MMap(Box::new((
-            stack_result_to_concrete_type(resolved, &b.0),
-            stack_result_to_concrete_type(resolved, &b.1),
-        ))),
-        MBigMap(b) => MBigMap(Box::new((
-            stack_result_to_concrete_type(resolved, &b.0),
-            stack_result_to_concrete_type(resolved, &b.1),
-        ))),
-        MOr(b) => MOr(Box::new((
-            stack_result_to_concrete_type(resolved, &b.0),
-            stack_result_to_concrete_type(resolved, &b.1),
-        ))),
-        MPair(b) => MPair(Box::new((
-            stack_result_to_concrete_type(resolved, &b.0),
-            stack_result_to_concrete_type(resolved, &b.1),
-        ))),
-        MLambda(b) => MLambda(Box::new((
-            stack_result_to_concrete_type(resolved, &b.0),
-            stack_result_to_concrete_type(resolved, &b.1),
-        ))),
+        MList(l) => MList(Arc::new(stack_result_to_concrete_type(resolved, l)?)),
+        MContract(l) => MContract(Arc::new(stack_result_to_concrete_type(resolved, l)?)),
+        MTicket(l) => MTicket(Arc::new(stack_result_to_concrete_type(resolved, l)?)),
+        MOption(l) => MOption(Arc::new(stack_result_to_concrete_type(resolved, l)?)),
+        MSet(l) => MSet(Arc::new(stack_result_to_concrete_type(resolved, l)?)),
+        MMap(b) => MMap(pair(b)?),
+        MBigMap(b) => MBigMap(pair(b)?),
+        MOr(b) => MOr(pair(b)?),
+        MPair(b) => MPair(pair(b)?),
+        MLambda(b) => MLambda(pair(b)?),
+    })
+}
+
+// Whether the outermost constructor of a stack element agrees with a
+// constraint. This never allocates, and rules out most variants of an
+// overloaded instruction before they are unified.
+fn head_matches(constraint: &Constraint, t: &ConcreteType) -> bool {
+    match (constraint, t) {
+        (MWrapped(CAtomic(a)), MWrapped(b)) => a == b,
+        (MWrapped(CAtomic(_)), _) => false,
+        (MWrapped(_), _) => true,
+        (MList(_), MList(_))
+        | (MTicket(_), MTicket(_))
+        | (MContract(_), MContract(_))
+        | (MOption(_), MOption(_))
+        | (MSet(_), MSet(_))
+        | (MLambda(_), MLambda(_))
+        | (MOr(_), MOr(_))
+        | (MPair(_), MPair(_))
+        | (MBigMap(_), MBigMap(_))
+        | (MMap(_), MMap(_)) => true,
+        _ => false,
     }
 }
 
-fn unify_stack(
+fn heads_match(sem_stack_in: &[StackArg], stack_state: &StackState<MAtomic>) -> bool {
+    sem_stack_in
+        .iter()
+        .enumerate()
+        .all(|(i, constraint)| match stack_state.get_index(i) {
+            SdOk(Result::Ok(stack_elem)) => {
+                stack_elem.is_unknown() || head_matches(constraint, stack_elem)
+            }
+            _ => true,
+        })
+}
+
+// Unifies the top of the stack with the input of a signature, without
+// touching the stack, so that a failed variant leaves nothing to undo.
+fn unify_stack_input(
     resolved: &mut ResolveCache,
-    sem_stack_in: &Vec<StackArg>,
-    sem_stack_out: &[StackResult],
-    stack_state: &mut StackState<MAtomic>,
-) -> Result<(), String> {
+    sem_stack_in: &[StackArg],
+    stack_state: &StackState<MAtomic>,
+) -> Result<(), Mismatch> {
     match stack_state.len() {
         SdFailed => Result::Ok(()),
         SdOk(sslen) => {
             if sslen < sem_stack_in.len() {
-                return Result::Err(format!(
-                    "Stack was found too small for the operation: needed {}, found {}",
-                    sem_stack_in.len(),
-                    sslen
-                ));
+                return Result::Err(Mismatch::StackTooSmall(sem_stack_in.len(), sslen));
             }
-            for constraint in sem_stack_in {
-                match stack_state.pop_front() {
+            for (i, constraint) in sem_stack_in.iter().enumerate() {
+                match stack_state.get_index(i) {
                     SdOk(Result::Ok(stack_elem)) => {
-                        unify_concrete_arg(resolved, &stack_elem, constraint)?;
-                    }
-                    SdOk(Result::Err(_)) => {
-                        return Result::Err("Too few values in stack".to_string());
+                        unify_concrete_arg(resolved, stack_elem, constraint)
+                            .map_err(|e| Mismatch::Element(i + 1, Box::new(e)))?;
                     }
+                    SdOk(Result::Err(_)) => return Result::Err(Mismatch::TooFewValues),
                     SdFailed => {
                         return Result::Ok(());
                     }
                 }
             }
+            Result::Ok(())
+        }
+    }
+}
 
-            for i in sem_stack_out.iter().rev() {
-                stack_state.push_front(stack_result_to_concrete_type(resolved, i));
-            }
+// Replaces the input of a signature, already unified by `unify_stack_input`,
+// with its output.
+fn replace_stack_input(
+    resolved: &mut ResolveCache,
+    sem_stack_in: &[StackArg],
+    sem_stack_out: &[StackResult],
+    stack_state: &mut StackState<MAtomic>,
+) -> Result<(), String> {
+    if let SdFailed = stack_state.len() {
+        return Result::Ok(());
+    }
+    let outputs = sem_stack_out
+        .iter()
+        .map(|t| stack_result_to_concrete_type(resolved, t))
+        .collect::<Result<Vec<_>, _>>()?;
+    for _ in sem_stack_in {
+        stack_state.pop_front();
+    }
+    for t in outputs.into_iter().rev() {
+        stack_state.push_front(t);
+    }
+    Result::Ok(())
+}
 
-            Result::Ok(())
+fn unify_variant(
+    tcenv: &TcEnv,
+    instruction: &Instruction<SomeValue>,
+    variant: &InstructionDef,
+    stack: &ConcreteStack,
+    session: &mut TcSession,
+) -> Result<(ResolveCache, Vec<ArgValue<MValue>>), Mismatch> {
+    let (mut resolved, args_) = unify_args(tcenv, &instruction.args, &variant.args, session)?;
+    unify_stack_input(&mut resolved, &variant.input_stack, stack)?;
+    Result::Ok((resolved, args_))
+}
+
+// Variants are unified in a session of their own, whose diagnostics, fuel and
+// stack trace only count for the variant that is selected, or once a limit is
+// hit. The memo is handed over, as what it holds does not depend on the
+// variant.
+fn scratch_session(session: &mut TcSession) -> TcSession {
+    TcSession {
+        diagnostics: vec![],
+        stack_trace: session.stack_trace.as_ref().map(|_| vec![]),
+        limits: session.limits,
+        usage: session.usage.clone(),
+        memo: std::mem::take(&mut session.memo),
+    }
+}
+
+fn end_scratch(session: &mut TcSession, scratch: TcSession, selected: bool) {
+    session.memo = scratch.memo;
+    if selected || scratch.usage.exceeded.is_some() {
+        session.diagnostics.extend(scratch.diagnostics);
+        session.usage = scratch.usage;
+        if let (Some(trace), Some(more)) = (&mut session.stack_trace, scratch.stack_trace) {
+            trace.extend(more);
         }
     }
 }
 
 pub fn typecheck_contract(contract: Contract<SomeValue>) -> Result<Contract<MValue>, String> {
-    let mut stack = StackState::from(vec![MPair(Box::new((
+    typecheck_contract_all(contract).map_err(|diagnostics| join_diagnostics(&diagnostics))
+}
+
+/// Typechecks a contract, recovering from errors so that every diagnostic
+/// found in the code is returned instead of only the first one.
+pub fn typecheck_contract_all(
+    contract: Contract<SomeValue>,
+) -> Result<Contract<MValue>, Vec<Diagnostic>> {
+    let mut session = TcSession::default();
+    let tcontract = typecheck_contract_in(&contract, &mut session);
+    if session.diagnostics.is_empty() {
+        Result::Ok(tcontract)
+    } else {
+        Result::Err(session.diagnostics)
+    }
+}
+
+/// Typechecks a contract, recording diagnostics and, if enabled, the stack
+/// trace in `session`.
+pub fn typecheck_contract_in(
+    contract: &Contract<SomeValue>,
+    session: &mut TcSession,
+) -> Contract<MValue> {
+    typecheck_contract_with(Protocol::LATEST, &MICHELSON_INSTRUCTIONS, contract, session)
+}
+
+/// Like `typecheck_contract_in`, as of `protocol` and with the instruction
+/// signatures of `instructions`, usually `InstructionRegistry::for_protocol`.
+pub fn typecheck_contract_with(
+    protocol: Protocol,
+    instructions: &InstructionRegistry,
+    contract: &Contract<SomeValue>,
+    session: &mut TcSession,
+) -> Contract<MValue> {
+    let mut stack = StackState::from(vec![MPair(Arc::new((
         contract.parameter.clone(),
         contract.storage.clone(),
     )))]);
+    check_toplevel_types(contract, protocol, session);
     let tcenv = TcEnv {
-        self_type: contract.parameter.clone(),
+        self_type: Some(contract.parameter.clone()),
+        instructions,
+        protocol,
     };
-    let tins = typecheck(&tcenv, &contract.code, &mut stack)?;
-    let expected_stack_elem = MPair(Box::new((
-        MList(Box::new(MWrapped(MOperation))),
+    let tins = typecheck_block(&tcenv, &contract.code, &mut stack, session);
+    let expected_stack_elem = MPair(Arc::new((
+        MList(Arc::new(MWrapped(MOperation))),
         contract.storage.clone(),
     )));
-    if stack.compare_singleton(&expected_stack_elem) {
-        Result::Ok(Contract {
-            parameter: contract.parameter.clone(),
-            storage: contract.storage.clone(),
-            code: tins,
-        })
-    } else {
-        panic!(
-            "Unexpected stack result {:?} while expecting {:>?}",
-            stack, expected_stack_elem
-        );
+    if !session.aborted() && !stack.compare_singleton(&expected_stack_elem) {
+        session.diagnostics.push(Diagnostic {
+            location: None,
+            kind: ErrorKind::Type,
+            message: format!(
+                "Unexpected stack result {} while expecting {}",
+                stack, expected_stack_elem
+            ),
+        });
+    }
+    Contract {
+        parameter: contract.parameter.clone(),
+        storage: contract.storage.clone(),
+        code: tins,
+        parameter_annotations: contract.parameter_annotations.clone(),
+        storage_annotations: contract.storage_annotations.clone(),
+    }
+}
+
+const MAX_ANNOTATION_LENGTH: usize = 255;
+const MAX_ENTRYPOINT_LENGTH: usize = 31;
+
+fn annotation_error(location: usize, message: String) -> Diagnostic {
+    Diagnostic {
+        location: Some(location),
+        kind: ErrorKind::Type,
+        message,
+    }
+}
+
+fn field_annotation(annots: &TypeAnnotations) -> Option<&str> {
+    annots
+        .annotations
+        .iter()
+        .find(|a| a.starts_with('%') && a.as_str() != "%@")
+        .map(|a| &a[1..])
+}
+
+// Checks that a type has at most one field and one type annotation, and no
+// variable annotations, which are only meaningful on instructions.
+fn check_type_annotations(annots: &TypeAnnotations, diagnostics: &mut Vec<Diagnostic>) {
+    let mut seen = (false, false);
+    for a in &annots.annotations {
+        let kind = if a.starts_with('%') && a.as_str() != "%@" {
+            &mut seen.0
+        } else if a.starts_with(':') {
+            &mut seen.1
+        } else {
+            diagnostics.push(annotation_error(
+                annots.location,
+                format!("Unexpected annotation {} on a type", a),
+            ));
+            continue;
+        };
+        if *kind {
+            diagnostics.push(annotation_error(
+                annots.location,
+                format!("Unexpected annotation {}, the type is already annotated", a),
+            ));
+        }
+        *kind = true;
+        if a.len() > MAX_ANNOTATION_LENGTH {
+            diagnostics.push(annotation_error(
+                annots.location,
+                format!(
+                    "Annotation {} is longer than {} characters",
+                    a, MAX_ANNOTATION_LENGTH
+                ),
+            ));
+        }
+    }
+    for arg in &annots.args {
+        check_type_annotations(arg, diagnostics);
+    }
+}
+
+// Collects the entrypoints declared in the `or` tree of a parameter type,
+// along with the branches that are not below any entrypoint.
+fn collect_entrypoints<'a>(
+    t: &ConcreteType,
+    annots: &'a TypeAnnotations,
+    entrypoints: &mut Vec<(&'a str, usize)>,
+    unnamed: &mut Vec<usize>,
+) {
+    if let MOr(b) = t {
+        for (t, a) in [&b.0, &b.1].into_iter().zip(&annots.args) {
+            match field_annotation(a) {
+                Some(name) => {
+                    entrypoints.push((name, a.location));
+                    let mut below = vec![];
+                    collect_entrypoints(t, a, entrypoints, &mut below);
+                }
+                None => {
+                    if !matches!(t, MOr(_)) {
+                        unnamed.push(a.location);
+                    }
+                    collect_entrypoints(t, a, entrypoints, unnamed);
+                }
+            }
+        }
+    }
+}
+
+/// Checks the parameter and storage types of a contract, and the annotations
+/// declaring its entrypoints.
+fn check_toplevel_types(
+    contract: &Contract<SomeValue>,
+    protocol: Protocol,
+    session: &mut TcSession,
+) {
+    let (pannots, sannots) = (
+        &contract.parameter_annotations,
+        &contract.storage_annotations,
+    );
+    for (t, annots) in [(&contract.parameter, pannots), (&contract.storage, sannots)] {
+        check_type_size(protocol, t, Some(annots.location), session);
+    }
+    let diagnostics = &mut session.diagnostics;
+    for (t, annots) in [(&contract.parameter, pannots), (&contract.storage, sannots)] {
+        if let Result::Err(e) = check_type_available(t, protocol) {
+            diagnostics.push(annotation_error(annots.location, e));
+        }
+    }
+    if !check_attribute(&Passable, &contract.parameter) {
+        diagnostics.push(annotation_error(
+            pannots.location,
+            format!("Parameter type {} is not passable", contract.parameter),
+        ));
+    }
+    if !check_attribute(&Storable, &contract.storage) {
+        diagnostics.push(annotation_error(
+            sannots.location,
+            format!("Storage type {} is not storable", contract.storage),
+        ));
+    }
+    check_type_annotations(pannots, diagnostics);
+    check_type_annotations(sannots, diagnostics);
+
+    let root = field_annotation(pannots);
+    let mut entrypoints: Vec<(&str, usize)> =
+        root.map(|r| (r, pannots.location)).into_iter().collect();
+    let mut unnamed = vec![];
+    collect_entrypoints(&contract.parameter, pannots, &mut entrypoints, &mut unnamed);
+    for (i, (name, location)) in entrypoints.iter().enumerate() {
+        if name.len() > MAX_ENTRYPOINT_LENGTH {
+            diagnostics.push(annotation_error(
+                *location,
+                format!(
+                    "Entrypoint name {} is longer than {} characters",
+                    name, MAX_ENTRYPOINT_LENGTH
+                ),
+            ));
+        }
+        if entrypoints[..i].iter().any(|(n, _)| n == name) {
+            diagnostics.push(annotation_error(
+                *location,
+                format!("Duplicate entrypoint %{}", name),
+            ));
+        }
+    }
+    // An explicit %default below the root replaces the root as the default
+    // entrypoint, so branches that have no entrypoint of their own can no
+    // longer be called.
+    if root != Some("default") && entrypoints.iter().any(|(n, _)| *n == "default") {
+        for location in unnamed {
+            diagnostics.push(annotation_error(
+                location,
+                String::from("Branch is unreachable since %default is declared elsewhere"),
+            ));
+        }
     }
 }
 
 pub fn typecheck(
     tcenv: &TcEnv,
-    instructions: &Vec<CompoundInstruction<SomeValue>>,
+    instructions: &Vec<LocatedInstruction<SomeValue>>,
     stack: &mut StackState<MAtomic>,
-) -> Result<Vec<CompoundInstruction<MValue>>, String> {
-    let mut resolved: Vec<CompoundInstruction<MValue>> = Vec::with_capacity(instructions.len());
-    for instruction in instructions {
-        //println!("Instruction: {:?}", instruction);
-        //println!("Stack in: {:?}", stack);
-        resolved.push(typecheck_one(tcenv, instruction, stack)?);
-        //println!("Stack out: {:?}", stack);
+) -> Result<Vec<LocatedInstruction<MValue>>, String> {
+    typecheck_all(tcenv, instructions, stack).map_err(|diagnostics| join_diagnostics(&diagnostics))
+}
+
+/// Like `typecheck`, but returns every diagnostic found instead of only the
+/// first one.
+pub fn typecheck_all(
+    tcenv: &TcEnv,
+    instructions: &Vec<LocatedInstruction<SomeValue>>,
+    stack: &mut StackState<MAtomic>,
+) -> Result<Vec<LocatedInstruction<MValue>>, Vec<Diagnostic>> {
+    let mut session = TcSession::default();
+    let tins = typecheck_block(tcenv, instructions, stack, &mut session);
+    if session.diagnostics.is_empty() {
+        Result::Ok(tins)
+    } else {
+        Result::Err(session.diagnostics)
     }
-    Result::Ok(resolved)
 }
 
-fn ensure_iter_body(
+/// Typechecks code, recording diagnostics and, if enabled, the stack trace in
+/// `session`, whose limits apply.
+pub fn typecheck_in(
     tcenv: &TcEnv,
+    instructions: &Vec<LocatedInstruction<SomeValue>>,
     stack: &mut StackState<MAtomic>,
-    m_iter_item: Option<&ConcreteType>,
-    instr: &Vec<CompoundInstruction<SomeValue>>,
-) -> Result<Vec<CompoundInstruction<MValue>>, String> {
-    match m_iter_item {
+    session: &mut TcSession,
+) -> Vec<LocatedInstruction<MValue>> {
+    typecheck_block(tcenv, instructions, stack, session)
+}
+
+/// Typechecks code on its own, starting from the declared `input` stack, and
+/// checks that it leaves the declared `output` stack. Code that fails on every
+/// path conforms to any output stack, which is reported as `AlwaysFails`.
+pub fn typecheck_against(
+    tcenv: &TcEnv,
+    instructions: &Vec<LocatedInstruction<SomeValue>>,
+    input: &ConcreteStack,
+    output: &ConcreteStack,
+) -> Result<(Vec<LocatedInstruction<MValue>>, Conformance), Vec<Diagnostic>> {
+    let mut session = TcSession::default();
+    let mut stack = input.clone();
+    let tins = typecheck_block(tcenv, instructions, &mut stack, &mut session);
+    if !session.diagnostics.is_empty() {
+        return Result::Err(session.diagnostics);
+    }
+    match stack.compare(output) {
+        Match => Result::Ok((tins, Conformance::Conforms)),
+        LeftFailed | BothFailed => Result::Ok((tins, Conformance::AlwaysFails)),
+        RightFailed | NoMatch => Result::Err(vec![Diagnostic {
+            location: None,
+            kind: ErrorKind::Type,
+            message: format!(
+                "Code leaves the stack {} while {} was declared",
+                stack, output
+            ),
+        }]),
+    }
+}
+
+fn join_diagnostics(diagnostics: &[Diagnostic]) -> String {
+    diagnostics
+        .iter()
+        .map(|d| d.to_string())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+type Block = Vec<LocatedInstruction<SomeValue>>;
+type TypedBlock = Vec<LocatedInstruction<MValue>>;
+
+// What remains to do for a compound instruction once its blocks are
+// typechecked: given the stack before the instruction and, for each block,
+// the typed block and the stack it ended with, it updates the stack and
+// builds the typed instruction.
+type Finish<'c> = Box<
+    dyn FnOnce(
+            &mut ConcreteStack,
+            Vec<(TypedBlock, ConcreteStack)>,
+        ) -> Result<CompoundInstruction<MValue>, String>
+        + 'c,
+>;
+
+// The outcome of typechecking one instruction. The blocks of a compound
+// instruction are not checked by `typecheck_one` itself but handed back to
+// the loop of `typecheck_block`, with the stacks they start from, so that
+// nesting costs heap rather than native stack.
+enum Step<'c> {
+    Done(CompoundInstruction<MValue>),
+    Blocks(Vec<(&'c Block, ConcreteStack)>, Finish<'c>),
+}
+
+use Step::*;
+
+// A block on the work stack of `typecheck_block`, and the compound
+// instruction of the enclosing block that waits for it, if any.
+struct Frame<'c> {
+    instructions: &'c Block,
+    next: usize,
+    stack: ConcreteStack,
+    resolved: TypedBlock,
+    waiting: Option<Waiting<'c>>,
+    // Where the block is in the digests of the memo, if it is there.
+    digest: Option<usize>,
+    // For a block whose result goes to the memo: the stack it started from
+    // and how many diagnostics there were before it.
+    memo: Option<(ConcreteStack, usize)>,
+}
+
+struct Waiting<'c> {
+    instruction: &'c LocatedInstruction<SomeValue>,
+    // The index of the instruction in its block.
+    index: usize,
+    blocks: std::vec::IntoIter<(&'c Block, ConcreteStack)>,
+    done: Vec<(TypedBlock, ConcreteStack)>,
+    finish: Finish<'c>,
+}
+
+impl<'c> Frame<'c> {
+    fn new(
+        instructions: &'c Block,
+        stack: ConcreteStack,
+        waiting: Option<Waiting<'c>>,
+        digest: Option<usize>,
+        memo: Option<(ConcreteStack, usize)>,
+    ) -> Self {
+        Frame {
+            instructions,
+            next: 0,
+            stack,
+            resolved: Vec::with_capacity(instructions.len()),
+            waiting,
+            digest,
+            memo,
+        }
+    }
+
+    // Errors are recorded in the session instead of being returned, and the
+    // stack is recovered from them so that the rest of the block is still
+    // checked.
+    fn record(
+        &mut self,
+        tcenv: &TcEnv,
+        LocatedInstruction {
+            location,
+            instruction,
+        }: &'c LocatedInstruction<SomeValue>,
+        result: Result<CompoundInstruction<MValue>, String>,
+        session: &mut TcSession,
+    ) {
+        let tins = match result {
+            Result::Ok(tins) => {
+                check_stack_limits(tcenv, &self.stack, *location, session);
+                tins
+            }
+            // The error that stopped the session is already reported.
+            Result::Err(_) if session.aborted() => return,
+            Result::Err(message) => {
+                session.diagnostics.push(Diagnostic {
+                    location: Some(*location),
+                    kind: ErrorKind::Type,
+                    message,
+                });
+                recover(tcenv, instruction, &mut self.stack);
+                FAIL
+            }
+        };
+        self.resolved.push(LocatedInstruction {
+            location: *location,
+            instruction: tins,
+        });
+        if let Some(trace) = &mut session.stack_trace {
+            trace.push((*location, self.stack.clone()));
+        }
+    }
+}
+
+// Typechecks a block whose own result is not memoized, such as the code of a
+// contract, while the blocks nested in it are.
+fn typecheck_block(
+    tcenv: &TcEnv,
+    instructions: &Block,
+    stack: &mut StackState<MAtomic>,
+    session: &mut TcSession,
+) -> TypedBlock {
+    let (digest, hashed) = session.memo.enter(instructions);
+    let tins = typecheck_frames(tcenv, instructions, digest, stack, session);
+    session.memo.leave(hashed);
+    tins
+}
+
+// Nested blocks are kept on an explicit work stack: the innermost block is
+// checked first, and when it ends its result goes to the instruction that
+// waits for it, which either starts its next block or is finished.
+fn typecheck_frames(
+    tcenv: &TcEnv,
+    instructions: &Block,
+    digest: Option<usize>,
+    stack: &mut StackState<MAtomic>,
+    session: &mut TcSession,
+) -> TypedBlock {
+    let mut frames = vec![Frame::new(
+        instructions,
+        std::mem::take(stack),
+        None,
+        digest,
+        None,
+    )];
+    while let Some(frame) = frames.last_mut() {
+        if session.aborted() {
+            break;
+        }
+        if let Some(instruction) = frame.instructions.get(frame.next) {
+            let index = frame.next;
+            frame.next += 1;
+            if !spend_instruction(instruction.location, session) {
+                break;
+            }
+            session.memo.set_at(frame.digest.map(|d| (d, index)));
+            match typecheck_one(tcenv, instruction, &mut frame.stack, session) {
+                Result::Ok(Done(tins)) => {
+                    frame.record(tcenv, instruction, Result::Ok(tins), session)
+                }
+                Result::Ok(Blocks(blocks, finish)) => {
+                    let waiting = Waiting {
+                        instruction,
+                        index,
+                        blocks: blocks.into_iter(),
+                        done: vec![],
+                        finish,
+                    };
+                    next_block(tcenv, &mut frames, waiting, session);
+                }
+                Result::Err(message) => {
+                    frame.record(tcenv, instruction, Result::Err(message), session)
+                }
+            }
+            continue;
+        }
+        let Some(finished) = frames.pop() else { break };
+        let Some(mut waiting) = finished.waiting else {
+            *stack = finished.stack;
+            return finished.resolved;
+        };
+        // What a session found after a limit was hit is incomplete.
+        if let (Some(digest), Some((input, before))) = (
+            finished.digest,
+            finished.memo.filter(|_| !session.aborted()),
+        ) {
+            let diagnostics = session.diagnostics[before..].to_vec();
+            let memoized = (
+                finished.stack.clone(),
+                finished.resolved.clone(),
+                diagnostics,
+            );
+            session.memo.insert(digest, tcenv, input, memoized);
+        }
+        waiting.done.push((finished.resolved, finished.stack));
+        next_block(tcenv, &mut frames, waiting, session);
+    }
+    // Only a limit stops the loop early, and then nothing that follows is
+    // checked.
+    stack.fail();
+    vec![]
+}
+
+// After an error the inputs of the instruction are dropped and its outputs
+// are left unknown, so that the rest of the block is still checked but cannot
+// report errors that are only a consequence of the first one. When the
+// instruction has no fixed number of inputs and outputs the whole stack is
+// failed instead, which unifies with anything.
+fn recover(tcenv: &TcEnv, instruction: &CompoundInstruction<SomeValue>, stack: &mut ConcreteStack) {
+    let arity = |s: &InstructionDef| (s.input_stack.len(), s.output_stack.len());
+    let arities = match instruction {
+        Other(i) => match tcenv.instructions.get(&i.name) {
+            Some(variants) => variants.iter().map(arity).collect(),
+            None => vec![],
+        },
+        GET(_) => vec![(1, 1)],
+        UPDATE(_) => vec![(2, 1)],
+        _ => vec![],
+    };
+    match (arities.split_first(), stack.len()) {
+        (Some((&(inputs, outputs), others)), SdOk(len))
+            if len >= inputs && others.iter().all(|a| *a == (inputs, outputs)) =>
+        {
+            for _ in 0..inputs {
+                stack.pop();
+            }
+            for _ in 0..outputs {
+                stack.push(MWrapped(MUnknown));
+            }
+        }
+        _ => stack.fail(),
+    }
+}
+
+// Compound instructions cannot tell what to do with an element left unknown
+// by an error, so they see the stack as failed when they look into one.
+fn looks_into_unknown(instruction: &CompoundInstruction<SomeValue>, stack: &ConcreteStack) -> bool {
+    let element = |i: usize| match stack.get_index(i) {
+        SdOk(Result::Ok(t)) => Some(t),
+        _ => None,
+    };
+    match instruction {
+        IF(..) | IF_CONS(..) | IF_SOME(..) | IF_NONE(..) | IF_LEFT(..) | LOOP(_) | LOOP_LEFT(_)
+        | MAP(_) | ITER(_) => element(0).is_some_and(|t| t.is_unknown()),
+        GET(_) | UNPAIR(_) => element(0).is_some_and(|t| t.has_unknown()),
+        UPDATE(_) => element(1).is_some_and(|t| t.has_unknown()),
+        _ => false,
+    }
+}
+
+// Starts the next block that `waiting` waits for, taking the results of the
+// blocks the memo has, or finishes its instruction once it has them all.
+fn next_block<'c>(
+    tcenv: &TcEnv,
+    frames: &mut Vec<Frame<'c>>,
+    mut waiting: Waiting<'c>,
+    session: &mut TcSession,
+) {
+    let parent = frames.last().and_then(|f| f.digest);
+    for (block, start) in waiting.blocks.by_ref() {
+        let digest = parent.and_then(|p| session.memo.nested(p, waiting.index, block));
+        match digest.and_then(|d| find_memoized(tcenv, d, &start, session)) {
+            Some((output, tins)) => waiting.done.push((tins, output)),
+            None => {
+                let memo = digest.map(|_| (start.clone(), session.diagnostics.len()));
+                frames.push(Frame::new(block, start, Some(waiting), digest, memo));
+                return;
+            }
+        }
+    }
+    if let Some(frame) = frames.last_mut() {
+        let result = (waiting.finish)(&mut frame.stack, waiting.done);
+        frame.record(tcenv, waiting.instruction, result, session);
+    }
+}
+
+// The output stack and typed code the memo of the session has for a block,
+// whose diagnostics are reported again. The memo is left alone while the
+// stack trace is recorded, as the trace would miss the instructions of the
+// blocks found there.
+fn find_memoized(
+    tcenv: &TcEnv,
+    digest: usize,
+    input: &ConcreteStack,
+    session: &mut TcSession,
+) -> Option<(ConcreteStack, TypedBlock)> {
+    if session.stack_trace.is_some() {
+        return None;
+    }
+    let (output, tins, diagnostics) = session.memo.find(digest, tcenv, input)?;
+    session.diagnostics.extend(diagnostics);
+    Some((output, tins))
+}
+
+// Like `typecheck_block`, reusing the result of the same block from the memo
+// of the session. The instruction being typechecked is restored afterwards,
+// as the block can be one of several in the values of that instruction.
+fn typecheck_memoized(
+    tcenv: &TcEnv,
+    instructions: &Block,
+    stack: &mut ConcreteStack,
+    session: &mut TcSession,
+) -> TypedBlock {
+    let at = session.memo.at();
+    let (digest, hashed) = session.memo.enter(instructions);
+    let tins = match digest.and_then(|d| find_memoized(tcenv, d, stack, session)) {
+        Some((output, tins)) => {
+            *stack = output;
+            tins
+        }
         None => {
-            let tinst = typecheck(tcenv, instr, &mut FailedStack)?;
-            stack.fail();
-            Result::Ok(tinst)
+            let input = stack.clone();
+            let before = session.diagnostics.len();
+            let tins = typecheck_frames(tcenv, instructions, digest, stack, session);
+            // What a session found after a limit was hit is incomplete.
+            if let Some(digest) = digest.filter(|_| !session.aborted()) {
+                let diagnostics = session.diagnostics[before..].to_vec();
+                let memoized = (stack.clone(), tins.clone(), diagnostics);
+                session.memo.insert(digest, tcenv, input, memoized);
+            }
+            tins
         }
+    };
+    session.memo.leave(hashed);
+    session.memo.set_at(at);
+    tins
+}
+
+// Counts an instruction against the limits of the session, and tells whether
+// checking can go on.
+fn spend_instruction(location: usize, session: &mut TcSession) -> bool {
+    let location = Some(location);
+    session.usage.instructions += 1;
+    if session.usage.instructions > session.limits.max_instructions {
+        let message = format!(
+            "Code has more than {} instructions",
+            session.limits.max_instructions
+        );
+        session.exceed(Limit::Instructions, location, message);
+    } else {
+        let _ = session.burn(1, location);
+    }
+    !session.aborted()
+}
+
+// The limits of the session and those of the protocol apply to the stack
+// after each instruction. Only the top of the stack is measured, as that is
+// where instructions leave the types they make.
+fn check_stack_limits(
+    tcenv: &TcEnv,
+    stack: &ConcreteStack,
+    location: usize,
+    session: &mut TcSession,
+) {
+    let location = Some(location);
+    let max_depth = session
+        .limits
+        .max_stack_depth
+        .min(tcenv.protocol.max_stack_depth());
+    if let SdOk(depth) = stack.len() {
+        if depth > max_depth {
+            let message = format!(
+                "Stack of {} elements exceeds the limit of {}",
+                depth, max_depth
+            );
+            session.exceed(Limit::StackDepth, location, message);
+            return;
+        }
+    }
+    if let SdOk(Result::Ok(top)) = stack.get_index(0) {
+        check_type_size(tcenv.protocol, top, location, session);
+    }
+}
+
+fn check_type_size(
+    protocol: Protocol,
+    t: &ConcreteType,
+    location: Option<usize>,
+    session: &mut TcSession,
+) {
+    let max_size = session.limits.max_type_size.min(protocol.max_type_size());
+    if type_size_exceeds(t, max_size) {
+        let message = format!("Type exceeds the limit of {} type constructors", max_size);
+        session.exceed(Limit::TypeSize, location, message);
+    }
+}
+
+/// Names of the instructions in `MICHELSON_INSTRUCTIONS` that have at least
+/// one variant accepting the given stack.
+pub fn applicable_instructions(stack: &ConcreteStack) -> Vec<&'static str> {
+    MICHELSON_INSTRUCTIONS
+        .iter()
+        .filter(|(_, variants)| {
+            variants
+                .iter()
+                .any(|v| accepts_stack(&v.input_stack, stack))
+        })
+        .map(|(name, _)| name.as_str())
+        .collect()
+}
+
+fn accepts_stack(sem_stack_in: &[StackArg], stack: &ConcreteStack) -> bool {
+    heads_match(sem_stack_in, stack)
+        && unify_stack_input(&mut ResolveCache::new(), sem_stack_in, stack).is_ok()
+}
+
+fn one_block(results: Vec<(TypedBlock, ConcreteStack)>) -> (TypedBlock, ConcreteStack) {
+    results.into_iter().next().unwrap_or((vec![], FailedStack))
+}
+
+// Once the stack has failed, the types its elements had are unknown, but the
+// nested blocks are still checked from a failed stack for errors of their own.
+fn failed_block<'c>(
+    instr: &'c Block,
+    wrap: impl FnOnce(TypedBlock) -> CompoundInstruction<MValue> + 'c,
+) -> Step<'c> {
+    Blocks(
+        vec![(instr, FailedStack)],
+        Box::new(move |stack, results| {
+            stack.fail();
+            Result::Ok(wrap(one_block(results).0))
+        }),
+    )
+}
+
+fn failed_branches<'c>(
+    (first, second): (&'c Block, &'c Block),
+    wrap: impl FnOnce(TypedBlock, TypedBlock) -> CompoundInstruction<MValue> + 'c,
+) -> Step<'c> {
+    Blocks(
+        vec![(first, FailedStack), (second, FailedStack)],
+        Box::new(move |stack, results| {
+            let mut results = results.into_iter().map(|(typed, _)| typed);
+            let first = results.next().unwrap_or_default();
+            let second = results.next().unwrap_or_default();
+            stack.fail();
+            Result::Ok(wrap(first, second))
+        }),
+    )
+}
+
+fn ensure_iter_body<'c>(
+    stack: &ConcreteStack,
+    m_iter_item: Option<&ConcreteType>,
+    instr: &'c Block,
+) -> Step<'c> {
+    match m_iter_item {
+        None => Blocks(
+            vec![(instr, FailedStack)],
+            Box::new(|stack, results| {
+                let (tinst, _) = one_block(results);
+                stack.fail();
+                Result::Ok(ITER(tinst))
+            }),
+        ),
         Some(iter_item) => {
             let expected_stack = stack.clone_tail();
             let mut start_stack: ConcreteStack = expected_stack.clone();
             start_stack.push(iter_item.clone());
-            let tinst = typecheck(tcenv, instr, &mut start_stack)?;
-            match start_stack.compare(&expected_stack) {
-                NoMatch => Result::Err(String::from("ITER body has unexpected type")),
-                _ => {
-                    *stack = start_stack;
-                    Result::Ok(tinst)
-                }
-            }
+            Blocks(
+                vec![(instr, start_stack)],
+                Box::new(move |stack, results| {
+                    let (tinst, end_stack) = one_block(results);
+                    match end_stack.compare(&expected_stack) {
+                        NoMatch => Result::Err(String::from("ITER body has unexpected type")),
+                        _ => {
+                            *stack = end_stack;
+                            Result::Ok(ITER(tinst))
+                        }
+                    }
+                }),
+            )
         }
     }
 }
-fn ensure_map_body<F: (Fn(ConcreteType) -> ConcreteType)>(
-    tcenv: &TcEnv,
-    stack: &mut StackState<MAtomic>,
+
+fn ensure_map_body<'c, F: (Fn(ConcreteType) -> ConcreteType) + 'c>(
+    stack: &ConcreteStack,
     iter_item_info: StackDerived<(&ConcreteType, F)>,
-    instr: &Vec<CompoundInstruction<SomeValue>>,
-) -> Result<Vec<CompoundInstruction<MValue>>, String> {
+    instr: &'c Block,
+) -> Step<'c> {
     match iter_item_info {
-        SdFailed => {
-            let tinst = typecheck(tcenv, instr, &mut FailedStack)?;
-            Result::Ok(tinst)
-        }
+        SdFailed => Blocks(
+            vec![(instr, FailedStack)],
+            Box::new(|_, results| Result::Ok(MAP(one_block(results).0))),
+        ),
         SdOk((iter_item, to_result)) => {
             let expected_stack = stack.clone_tail();
             let mut start_stack: ConcreteStack = expected_stack.clone();
             start_stack.push(iter_item.clone());
-            let tinst = typecheck(tcenv, instr, &mut start_stack)?;
-            match start_stack.pop_front() {
-                SdFailed => {
-                    *stack = start_stack;
-                    Result::Ok(tinst)
-                }
-                SdOk(Result::Err(_)) => {
-                    Result::Err("Map body returned too few values on stack.".to_string())
-                }
-                SdOk(Result::Ok(start_stack_head)) => match start_stack.compare(&expected_stack) {
-                    NoMatch => Result::Err(String::from(
-                        "MAP body cannot mutated the tail of the stack.",
-                    )),
-                    _ => {
-                        start_stack.push(to_result(start_stack_head));
-                        *stack = start_stack;
-                        Result::Ok(tinst)
+            Blocks(
+                vec![(instr, start_stack)],
+                Box::new(move |stack, results| {
+                    let (tinst, mut end_stack) = one_block(results);
+                    match end_stack.pop_front() {
+                        SdFailed => {
+                            *stack = end_stack;
+                            Result::Ok(MAP(tinst))
+                        }
+                        SdOk(Result::Err(_)) => {
+                            Result::Err("Map body returned too few values on stack.".to_string())
+                        }
+                        SdOk(Result::Ok(end_stack_head)) => {
+                            match end_stack.compare(&expected_stack) {
+                                NoMatch => Result::Err(String::from(
+                                    "MAP body cannot mutated the tail of the stack.",
+                                )),
+                                _ => {
+                                    end_stack.push(to_result(end_stack_head));
+                                    *stack = end_stack;
+                                    Result::Ok(MAP(tinst))
+                                }
+                            }
+                        }
                     }
-                },
-            }
+                }),
+            )
         }
     }
 }
-fn ensure_loop_body(
-    tcenv: &TcEnv,
-    stack: &mut StackState<MAtomic>,
-    instr: &Vec<CompoundInstruction<SomeValue>>,
-) -> Result<Vec<CompoundInstruction<MValue>>, String> {
-    let expected_stack = stack.clone_tail();
-    let mut start_stack: ConcreteStack = expected_stack.clone();
-    start_stack.push(MWrapped(MBool));
-    let tinst = typecheck(tcenv, instr, &mut start_stack)?;
-    match start_stack.compare(&expected_stack) {
-        NoMatch => Result::Err(String::from("LOOP body has unexpected type")),
-        _ => {
-            *stack = expected_stack;
-            Result::Ok(tinst)
-        }
-    }
+
+fn ensure_loop_body<'c>(stack: &ConcreteStack, instr: &'c Block) -> Step<'c> {
+    // The body starts from the tail and must leave a new condition on top
+    // of it.
+    let tail = stack.clone_tail();
+    let mut expected_stack: ConcreteStack = tail.clone();
+    expected_stack.push(MWrapped(MBool));
+    Blocks(
+        vec![(instr, tail.clone())],
+        Box::new(move |stack, results| {
+            let (tinst, end_stack) = one_block(results);
+            match end_stack.compare(&expected_stack) {
+                NoMatch => Result::Err(String::from("LOOP body has unexpected type")),
+                _ => {
+                    *stack = tail;
+                    Result::Ok(LOOP(tinst))
+                }
+            }
+        }),
+    )
 }
 
-fn ensure_loop_left_body(
-    tcenv: &TcEnv,
-    stack: &mut StackState<MAtomic>,
+fn ensure_loop_left_body<'c>(
+    stack: &ConcreteStack,
     mleft: StackDerived<MType<MAtomic>>,
     mright: StackDerived<MType<MAtomic>>,
-    instr: &Vec<CompoundInstruction<SomeValue>>,
-) -> Result<Vec<CompoundInstruction<MValue>>, String> {
+    instr: &'c Block,
+) -> Step<'c> {
     match (mleft, mright) {
         (SdOk(left), SdOk(right)) => {
             let mut expected_stack = stack.clone_tail();
             let mut start_stack: ConcreteStack = expected_stack.clone();
             start_stack.push(left.clone());
-            expected_stack.push(MOr(Box::new((left, right.clone()))));
-            let tinst = typecheck(tcenv, instr, &mut start_stack)?;
-            match start_stack.compare(&expected_stack) {
-                NoMatch => Result::Err(String::from("LOOP_LEFT body has unexpected type")),
-                _ => {
-                    expected_stack.pop();
-                    expected_stack.push(right);
-                    *stack = expected_stack;
-                    Result::Ok(tinst)
-                }
-            }
-        }
-        _ => {
-            let tinst = typecheck(tcenv, instr, stack)?;
-            Result::Ok(tinst)
+            expected_stack.push(MOr(Arc::new((left, right.clone()))));
+            Blocks(
+                vec![(instr, start_stack)],
+                Box::new(move |stack, results| {
+                    let (tinst, end_stack) = one_block(results);
+                    match end_stack.compare(&expected_stack) {
+                        NoMatch => Result::Err(String::from("LOOP_LEFT body has unexpected type")),
+                        _ => {
+                            expected_stack.pop();
+                            expected_stack.push(right);
+                            *stack = expected_stack;
+                            Result::Ok(LOOP_LEFT(tinst))
+                        }
+                    }
+                }),
+            )
         }
+        _ => Blocks(
+            vec![(instr, stack.clone())],
+            Box::new(|stack, results| {
+                let (tinst, end_stack) = one_block(results);
+                *stack = end_stack;
+                Result::Ok(LOOP_LEFT(tinst))
+            }),
+        ),
     }
 }
 
-fn ensure_if_cons_body(
-    tcenv: &TcEnv,
-    stack_: &mut StackState<MAtomic>,
-    (cs, ns): (
-        &Vec<CompoundInstruction<SomeValue>>,
-        &Vec<CompoundInstruction<SomeValue>>,
-    ),
-) -> Result<
-    (
-        Vec<CompoundInstruction<MValue>>,
-        Vec<CompoundInstruction<MValue>>,
-    ),
-    String,
-> {
-    let stack_head =
-        get_stack_derived_result_handle_failed!(stack_.get_index(0), (vec![FAIL], vec![FAIL]));
-    match stack_head.clone() {
-        MList(x) => {
-            let mut temp_stack_nil: ConcreteStack = stack_.clone_tail();
-            let mut temp_stack_cons: ConcreteStack = stack_.clone_tail();
-            temp_stack_cons.push(MList(x.clone()));
-            temp_stack_cons.push(x.as_ref().clone());
-            let cbtc = typecheck(tcenv, cs, &mut temp_stack_cons)?;
-            let nbtc = typecheck(tcenv, ns, &mut temp_stack_nil)?;
-            match temp_stack_cons.compare(&temp_stack_nil) {
-                NoMatch => Result::Err(format!(
-                    "Type of IF_CONS branches differ {:?} {:?}",
-                    temp_stack_cons, temp_stack_nil
-                )),
-                RightFailed => {
-                    *stack_ = temp_stack_cons;
-                    Result::Ok((cbtc, nbtc))
+// The two branches of a conditional, checked in order from the given stacks.
+// Their end stacks have to agree unless one of them fails; `mismatch`
+// explains a disagreement and `wrap` builds the instruction from the typed
+// branches, in the same order.
+fn branches<'c>(
+    (first, first_start): (&'c Block, ConcreteStack),
+    (second, second_start): (&'c Block, ConcreteStack),
+    mismatch: impl FnOnce(&ConcreteStack, &ConcreteStack) -> String + 'c,
+    wrap: impl FnOnce(TypedBlock, TypedBlock) -> CompoundInstruction<MValue> + 'c,
+) -> Step<'c> {
+    Blocks(
+        vec![(first, first_start), (second, second_start)],
+        Box::new(move |stack, results| {
+            let mut results = results.into_iter();
+            let (first, first_stack) = results.next().unwrap_or((vec![], FailedStack));
+            let (second, second_stack) = results.next().unwrap_or((vec![], FailedStack));
+            match first_stack.compare(&second_stack) {
+                Match | RightFailed => {
+                    *stack = first_stack;
+                    Result::Ok(wrap(first, second))
                 }
                 LeftFailed => {
-                    *stack_ = temp_stack_nil;
-                    Result::Ok((cbtc, nbtc))
-                }
-                Match => {
-                    *stack_ = temp_stack_cons;
-                    Result::Ok((cbtc, nbtc))
+                    *stack = second_stack;
+                    Result::Ok(wrap(first, second))
                 }
+                NoMatch => Result::Err(mismatch(&first_stack, &second_stack)),
                 BothFailed => {
-                    stack_.fail();
-                    Result::Ok((vec![FAIL], vec![FAIL]))
+                    stack.fail();
+                    Result::Ok(wrap(first, second))
                 }
             }
+        }),
+    )
+}
+
+fn ensure_if_cons_body<'c>(
+    stack_: &ConcreteStack,
+    (cs, ns): (&'c Block, &'c Block),
+) -> Result<Step<'c>, String> {
+    let stack_head = get_stack_derived_result_handle_failed!(
+        stack_.get_index(0),
+        failed_branches((cs, ns), IF_CONS)
+    );
+    match stack_head {
+        MList(x) => {
+            let temp_stack_nil: ConcreteStack = stack_.clone_tail();
+            let mut temp_stack_cons: ConcreteStack = stack_.clone_tail();
+            temp_stack_cons.push(MList(x.clone()));
+            temp_stack_cons.push(x.as_ref().clone());
+            Result::Ok(branches(
+                (cs, temp_stack_cons),
+                (ns, temp_stack_nil),
+                |cons, nil| format!("Type of IF_CONS branches differ: {} and {}", cons, nil),
+                IF_CONS,
+            ))
         }
-        m => Result::Err(format!("IF_CONS requires a list, but found {:?}", m)),
+        m => Result::Err(format!("IF_CONS requires a list, but found {}", m)),
     }
 }
 
-fn ensure_if_left_body(
-    tcenv: &TcEnv,
-    stack_: &mut ConcreteStack,
-    (lb, rb): (
-        &Vec<CompoundInstruction<SomeValue>>,
-        &Vec<CompoundInstruction<SomeValue>>,
-    ),
-) -> Result<
-    (
-        Vec<CompoundInstruction<MValue>>,
-        Vec<CompoundInstruction<MValue>>,
-    ),
-    String,
-> {
-    let stack_head =
-        get_stack_derived_result_handle_failed!(stack_.get_index(0), (vec![FAIL], vec![FAIL]));
-    match stack_head.clone() {
+fn ensure_if_left_body<'c>(
+    stack_: &ConcreteStack,
+    (lb, rb): (&'c Block, &'c Block),
+) -> Result<Step<'c>, String> {
+    let stack_head = get_stack_derived_result_handle_failed!(
+        stack_.get_index(0),
+        failed_branches((lb, rb), IF_LEFT)
+    );
+    match stack_head {
         MOr(b) => {
             let mut temp_stack_left: ConcreteStack = stack_.clone_tail();
             let mut temp_stack_right: ConcreteStack = stack_.clone_tail();
             temp_stack_left.push(b.0.clone());
             temp_stack_right.push(b.1.clone());
-            let lbtc = typecheck(tcenv, lb, &mut temp_stack_left)?;
-            let rbtc = typecheck(tcenv, rb, &mut temp_stack_right)?;
-
-            match temp_stack_left.compare(&temp_stack_right) {
-                RightFailed => {
-                    *stack_ = temp_stack_left;
-                    Result::Ok((lbtc, rbtc))
-                }
-                LeftFailed => {
-                    *stack_ = temp_stack_right;
-                    Result::Ok((lbtc, rbtc))
-                }
-                Match => {
-                    *stack_ = temp_stack_right;
-                    Result::Ok((lbtc, rbtc))
-                }
-                NoMatch => Result::Err(format!(
-                    "Type of IF_LEFT branches differ {:?} {:?}",
-                    temp_stack_left, temp_stack_right
-                )),
-                BothFailed => {
-                    stack_.fail();
-                    Result::Ok((vec![FAIL], vec![FAIL]))
-                }
-            }
+            Result::Ok(branches(
+                (lb, temp_stack_left),
+                (rb, temp_stack_right),
+                |left, right| format!("Type of IF_LEFT branches differ: {} and {}", left, right),
+                IF_LEFT,
+            ))
         }
-        m => Result::Err(format!(
-            "IF_LEFT requires an or, but found {:?}, {:?} {:?}",
-            m, lb, rb
-        )),
+        m => Result::Err(format!("IF_LEFT requires an or, but found {}", m)),
     }
 }
 
-fn ensure_if_none_body(
-    tcenv: &TcEnv,
-    stack_: &mut ConcreteStack,
-    (nb, sb): (
-        &Vec<CompoundInstruction<SomeValue>>,
-        &Vec<CompoundInstruction<SomeValue>>,
-    ),
-) -> Result<
-    (
-        Vec<CompoundInstruction<MValue>>,
-        Vec<CompoundInstruction<MValue>>,
-    ),
-    String,
-> {
-    let stack_head =
-        get_stack_derived_result_handle_failed!(stack_.get_index(0), (vec![FAIL], vec![FAIL]));
-    match stack_head.clone() {
+// Shared by IF_NONE and IF_SOME, whose `wrap` receives the typed branches
+// for none and some, in that order. The some branch is checked first.
+fn ensure_if_none_body<'c>(
+    stack_: &ConcreteStack,
+    (nb, sb): (&'c Block, &'c Block),
+    wrap: impl FnOnce(TypedBlock, TypedBlock) -> CompoundInstruction<MValue> + 'c,
+) -> Result<Step<'c>, String> {
+    let stack_head = get_stack_derived_result_handle_failed!(
+        stack_.get_index(0),
+        failed_branches((sb, nb), move |sbtc, nbtc| wrap(nbtc, sbtc))
+    );
+    match stack_head {
         MOption(x) => {
-            let mut temp_stack_none: ConcreteStack = stack_.clone_tail();
+            let temp_stack_none: ConcreteStack = stack_.clone_tail();
             let mut temp_stack_some: ConcreteStack = stack_.clone_tail();
-            temp_stack_some.push(*x);
-            let sbtc = typecheck(tcenv, sb, &mut temp_stack_some)?;
-            let nbtc = typecheck(tcenv, nb, &mut temp_stack_none)?;
-            match temp_stack_some.compare(&temp_stack_none) {
-                LeftFailed => {
-                    *stack_ = temp_stack_none;
-                    Result::Ok((nbtc, sbtc))
-                }
-                RightFailed => {
-                    *stack_ = temp_stack_some;
-                    Result::Ok((nbtc, sbtc))
-                }
-                Match => {
-                    *stack_ = temp_stack_some;
-                    Result::Ok((nbtc, sbtc))
-                }
-                NoMatch => Result::Err(String::from("Type of IF_NONE branches differ")),
-                BothFailed => {
-                    stack_.fail();
-                    Result::Ok((vec![FAIL], vec![FAIL]))
-                }
-            }
+            temp_stack_some.push(x.as_ref().clone());
+            Result::Ok(branches(
+                (sb, temp_stack_some),
+                (nb, temp_stack_none),
+                |_, _| String::from("Type of IF_NONE branches differ"),
+                move |sbtc, nbtc| wrap(nbtc, sbtc),
+            ))
         }
-        m => Result::Err(format!(
-            "IF_NONE requires an option, but found {:?}, {:?} {:?}",
-            m, sb, nb
-        )),
+        m => Result::Err(format!("IF_NONE requires an option, but found {}", m)),
     }
 }
 
-fn ensure_same_lambda_type(
-    tcenv: &TcEnv,
-    stack_: &mut ConcreteStack,
-    (tb, fb): (
-        &Vec<CompoundInstruction<SomeValue>>,
-        &Vec<CompoundInstruction<SomeValue>>,
-    ),
-) -> Result<
-    (
-        Vec<CompoundInstruction<MValue>>,
-        Vec<CompoundInstruction<MValue>>,
-    ),
-    String,
-> {
-    let mut temp_stack_t: ConcreteStack = stack_.clone_tail();
-    let mut temp_stack_f: ConcreteStack = stack_.clone_tail();
-    let tbtc = typecheck(tcenv, tb, &mut temp_stack_t)?;
-    let fbtc = typecheck(tcenv, fb, &mut temp_stack_f)?;
-
-    match temp_stack_t.compare(&temp_stack_f) {
-        RightFailed => {
-            *stack_ = temp_stack_t;
-            Result::Ok((tbtc, fbtc))
-        }
-        LeftFailed => {
-            *stack_ = temp_stack_f;
-            Result::Ok((tbtc, fbtc))
-        }
-        Match => {
-            *stack_ = temp_stack_f;
-            Result::Ok((tbtc, fbtc))
-        }
-        NoMatch => Result::Err(String::from("Type of branches differ")),
-        BothFailed => {
-            stack_.fail();
-            Result::Ok((vec![FAIL], vec![FAIL]))
-        }
-    }
+fn ensure_same_lambda_type<'c>(
+    stack_: &ConcreteStack,
+    (tb, fb): (&'c Block, &'c Block),
+) -> Step<'c> {
+    branches(
+        (tb, stack_.clone_tail()),
+        (fb, stack_.clone_tail()),
+        |_, _| String::from("Type of branches differ"),
+        IF,
+    )
 }
 
-fn typecheck_one(
+fn typecheck_one<'c>(
     tcenv: &TcEnv,
-    cinstruction: &CompoundInstruction<SomeValue>,
+    LocatedInstruction {
+        location,
+        instruction: cinstruction,
+    }: &'c LocatedInstruction<SomeValue>,
     stack: &mut ConcreteStack,
-) -> Result<CompoundInstruction<MValue>, String> {
+    session: &mut TcSession,
+) -> Result<Step<'c>, String> {
+    check_instruction_available(cinstruction, tcenv.protocol)?;
+    if looks_into_unknown(cinstruction, stack) {
+        stack.fail();
+    }
     match cinstruction {
-        Other(instruction) => match MICHELSON_INSTRUCTIONS.get(&instruction.name) {
+        Other(instruction) => match tcenv.instructions.get(&instruction.name) {
             Some(variants) => {
-                let mut errors: String = String::new();
+                // Variants whose heads do not match are only unified again,
+                // to explain why, when no variant matches.
+                let mut failures: Vec<Option<Mismatch>> = Vec::with_capacity(variants.len());
                 for s in variants {
-                    match unify_args(tcenv, &instruction.args, &s.args) {
+                    if !heads_match(&s.input_stack, stack) {
+                        failures.push(None);
+                        continue;
+                    }
+                    session.burn(1, Some(*location))?;
+                    let mut scratch = scratch_session(session);
+                    let unified = unify_variant(tcenv, instruction, s, stack, &mut scratch);
+                    end_scratch(session, scratch, unified.is_ok());
+                    match unified {
                         Result::Ok((mut resolved, args_)) => {
-                            let r = if variants.len() > 1 {
-                                let mut temp_stack = stack.clone();
-                                match unify_stack(
-                                    &mut resolved,
-                                    &s.input_stack,
-                                    &s.output_stack,
-                                    &mut temp_stack,
-                                ) {
-                                    Result::Ok(_) => {
-                                        *stack = temp_stack;
-                                        Result::Ok(())
-                                    }
-                                    e => e,
-                                }
-                            } else {
-                                unify_stack(&mut resolved, &s.input_stack, &s.output_stack, stack)
-                            };
-                            match r {
-                                Result::Ok(_) => {
-                                    return Result::Ok(Other(Instruction {
-                                        location: instruction.location,
-                                        args: args_,
-                                        name: instruction.name.clone(),
-                                    }));
-                                }
-                                Result::Err(s) => {
-                                    errors = format!("{};\n {}", errors, s);
-                                    continue;
+                            // Inputs left unknown by an error could as well
+                            // have matched another variant, so its outputs
+                            // are unknown.
+                            let ambiguous = variants.len() > 1
+                                && (0..s.input_stack.len()).any(|i| {
+                                    matches!(stack.get_index(i), SdOk(Result::Ok(t)) if t.has_unknown())
+                                });
+                            replace_stack_input(
+                                &mut resolved,
+                                &s.input_stack,
+                                &s.output_stack,
+                                stack,
+                            )?;
+                            if ambiguous {
+                                for i in 0..s.output_stack.len() {
+                                    stack.replace_index(i, MWrapped(MUnknown));
                                 }
                             }
+                            return Result::Ok(Done(Other(Instruction {
+                                args: args_,
+                                name: instruction.name.clone(),
+                            })));
                         }
-                        Result::Err(s) => {
-                            errors = format!("{};\n {}", errors, s);
-                            continue;
-                        }
+                        Result::Err(e) => failures.push(Some(e)),
                     }
                 }
+                let mut errors: Vec<String> = vec![];
+                for (s, failure) in variants.iter().zip(failures) {
+                    let e = match failure {
+                        Some(e) => Some(e),
+                        None => {
+                            let mut scratch = scratch_session(session);
+                            let e = unify_variant(tcenv, instruction, s, stack, &mut scratch).err();
+                            end_scratch(session, scratch, false);
+                            e
+                        }
+                    };
+                    errors.push(e.map(|e| e.to_string()).unwrap_or_default());
+                }
                 Result::Err(format!(
-                    "{}; None of the instruction variants matched here for {:?} with stack {:?}",
-                    errors, &instruction, &stack
+                    "None of the instruction variants of {} matched the stack {}: {}",
+                    instruction.name,
+                    stack,
+                    errors.join("; ")
                 ))
             }
             None => Result::Err(format!("Instruction {} not found", &instruction.name)),
         },
-        SELF => {
-            stack.push(MContract(Box::new(tcenv.self_type.clone())));
-            Result::Ok(SELF)
-        }
+        SELF => match &tcenv.self_type {
+            Some(t) => {
+                stack.push(MContract(Arc::new(t.clone())));
+                Result::Ok(Done(SELF))
+            }
+            None => Result::Err(String::from(
+                "SELF can only be used in the code of a contract",
+            )),
+        },
         FAIL => {
             stack.fail();
-            Result::Ok(FAIL)
+            Result::Ok(Done(FAIL))
         }
         FAILWITH => {
+            ensure_stack_derived!(
+                stack.ensure_non_empty(),
+                "FAILWITH requires a value on the stack".to_string(),
+                Done(FAILWITH)
+            );
+            let target =
+                get_stack_derived_result_handle_failed!(stack.get_index(0), Done(FAILWITH));
+            if !check_attribute(&Packable, target) {
+                return Result::Err(format!("Type {} is not packable", target));
+            }
             stack.fail();
-            Result::Ok(FAIL)
+            Result::Ok(Done(FAILWITH))
         }
         LOOP(ins) => match stack.get_index(0) {
-            SdOk(Result::Ok(MWrapped(MBool))) => {
-                let tinst = ensure_loop_body(tcenv, stack, ins)?;
-                Result::Ok(LOOP(tinst))
+            SdOk(Result::Ok(MWrapped(MBool))) | SdFailed => {
+                Result::Ok(ensure_loop_body(stack, ins))
             }
-            SdOk(Result::Ok(m)) => Result::Err(format!("LOOP requires a bool, but found {:?}", m)),
+            SdOk(Result::Ok(m)) => Result::Err(format!("LOOP requires a bool, but found {}", m)),
             SdOk(Result::Err(_)) => Result::Err("Stack can't be empty for LOOP".to_string()),
-            SdFailed => {
-                let tinst = ensure_loop_body(tcenv, stack, ins)?;
-                Result::Ok(LOOP(tinst))
-            }
         },
         LOOP_LEFT(ins) => match stack.get_index(0) {
             SdOk(Result::Ok(stack_head)) => match stack_head {
-                MOr(b) => {
-                    let tinst = ensure_loop_left_body(
-                        tcenv,
-                        stack,
-                        SdOk(b.0.clone()),
-                        SdOk(b.1.clone()),
-                        ins,
-                    )?;
-                    Result::Ok(LOOP_LEFT(tinst))
-                }
-                m => Result::Err(format!("LOOP_LEFT requires an or, but found {:?}", m)),
+                MOr(b) => Result::Ok(ensure_loop_left_body(
+                    stack,
+                    SdOk(b.0.clone()),
+                    SdOk(b.1.clone()),
+                    ins,
+                )),
+                m => Result::Err(format!("LOOP_LEFT requires an or, but found {}", m)),
             },
             SdOk(Result::Err(_)) => Result::Err("LOOP_LEFT stack cannot be empty!".to_string()),
 
-            SdFailed => {
-                let tinst = ensure_loop_left_body(tcenv, stack, SdFailed, SdFailed, ins)?;
-                Result::Ok(LOOP_LEFT(tinst))
-            }
+            SdFailed => Result::Ok(ensure_loop_left_body(stack, SdFailed, SdFailed, ins)),
         },
         MAP(ins) => match stack.get_index(0) {
             SdOk(Result::Ok(stack_head)) => match stack_head.clone() {
-                MList(t) => {
-                    let tinst =
-                        ensure_map_body(tcenv, stack, SdOk((&t, |x| MList(Box::new(x)))), ins)?;
-                    Result::Ok(MAP(tinst))
-                }
-                MOption(t) => {
-                    let tinst =
-                        ensure_map_body(tcenv, stack, SdOk((&t, |x| MOption(Box::new(x)))), ins)?;
-                    Result::Ok(MAP(tinst))
-                }
+                MList(t) => Result::Ok(ensure_map_body(
+                    stack,
+                    SdOk((&t, |x| MList(Arc::new(x)))),
+                    ins,
+                )),
+                MOption(t) => Result::Ok(ensure_map_body(
+                    stack,
+                    SdOk((&t, |x| MOption(Arc::new(x)))),
+                    ins,
+                )),
                 MMap(t) => {
-                    let tinst = ensure_map_body(
-                        tcenv,
+                    let item = MPair(t.clone());
+                    Result::Ok(ensure_map_body(
                         stack,
-                        SdOk((&MPair(t.clone()), |x| MMap(Box::new((t.0.clone(), x))))),
+                        SdOk((&item, move |x| MMap(Arc::new((t.0.clone(), x))))),
                         ins,
-                    )?;
-                    Result::Ok(MAP(tinst))
+                    ))
                 }
                 m => Result::Err(format!(
-                    "Map requires a list, option or map, but found {:?}",
+                    "Map requires a list, option or map, but found {}",
                     m
                 )),
             },
             SdOk(Result::Err(_)) => Result::Err("Map cannot work with Empty list".to_string()),
-            SdFailed => Result::Ok(FAIL),
+            SdFailed => Result::Ok(failed_block(ins, MAP)),
         },
         ITER(ins) => {
-            match get_stack_derived_result_handle_failed!(stack.get_index(0), FAIL).clone() {
-                MList(t) => {
-                    let tinst = ensure_iter_body(tcenv, stack, Some(&t), ins)?;
-                    Result::Ok(ITER(tinst))
-                }
-                MSet(t) => {
-                    let tinst = ensure_iter_body(tcenv, stack, Some(&t), ins)?;
-                    Result::Ok(ITER(tinst))
-                }
-                MMap(t) => {
-                    let tinst = ensure_iter_body(tcenv, stack, Some(&MPair(t)), ins)?;
-                    Result::Ok(ITER(tinst))
-                }
-                m => Result::Err(format!(
-                    "ITER requires a list, set or map, but found {:?}",
-                    m
-                )),
+            match get_stack_derived_result_handle_failed!(
+                stack.get_index(0),
+                failed_block(ins, ITER)
+            )
+            .clone()
+            {
+                MList(t) => Result::Ok(ensure_iter_body(stack, Some(&t), ins)),
+                MSet(t) => Result::Ok(ensure_iter_body(stack, Some(&t), ins)),
+                MMap(t) => Result::Ok(ensure_iter_body(stack, Some(&MPair(t)), ins)),
+                m => Result::Err(format!("ITER requires a list, set or map, but found {}", m)),
             }
         }
         IF_CONS(tb, fb) => {
             ensure_stack_derived!(
                 stack.ensure_non_empty(),
                 "stack too short!".to_string(),
-                FAIL
+                failed_branches((tb, fb), IF_CONS)
             );
-            let (cbtc, nbtc) = ensure_if_cons_body(tcenv, stack, (tb, fb))?;
-            Result::Ok(IF_CONS(cbtc, nbtc))
+            ensure_if_cons_body(stack, (tb, fb))
         }
         IF_NONE(nb, sb) => {
             ensure_stack_derived!(
                 stack.ensure_non_empty(),
                 "stack too short!".to_string(),
-                FAIL
+                failed_branches((sb, nb), |sbtc, nbtc| IF_NONE(nbtc, sbtc))
             );
-            let (nbtc, sbtc) = ensure_if_none_body(tcenv, stack, (nb, sb))?;
-            Result::Ok(IF_NONE(nbtc, sbtc))
+            ensure_if_none_body(stack, (nb, sb), IF_NONE)
         }
         IF_SOME(sb, nb) => {
             ensure_stack_derived!(
                 stack.ensure_non_empty(),
                 "stack too short!".to_string(),
-                FAIL
+                failed_branches((sb, nb), IF_SOME)
             );
-            let (nbtc, sbtc) = ensure_if_none_body(tcenv, stack, (nb, sb))?;
-            Result::Ok(IF_SOME(sbtc, nbtc))
+            ensure_if_none_body(stack, (nb, sb), |nbtc, sbtc| IF_SOME(sbtc, nbtc))
         }
 
         IF_LEFT(lb, rb) => {
             ensure_stack_derived!(
                 stack.ensure_non_empty(),
                 "stack too short!".to_string(),
-                FAIL
+                failed_branches((lb, rb), IF_LEFT)
             );
-            let (lbtc, rbtc) = ensure_if_left_body(tcenv, stack, (lb, rb))?;
-            Result::Ok(IF_LEFT(lbtc, rbtc))
+            ensure_if_left_body(stack, (lb, rb))
         }
         IF(tb, fb) => {
-            match get_stack_derived_result_handle_failed!(stack.get_index(0), FAIL) {
+            match get_stack_derived_result_handle_failed!(
+                stack.get_index(0),
+                failed_branches((tb, fb), IF)
+            ) {
                 MWrapped(MBool) => {}
                 _ => {
                     return Result::Err(
@@ -1016,42 +1874,46 @@ fn typecheck_one(
                     );
                 }
             }
-            let (tbtc, fbtc) = ensure_same_lambda_type(tcenv, stack, (tb, fb))?;
-            Result::Ok(IF(tbtc, fbtc))
+            Result::Ok(ensure_same_lambda_type(stack, (tb, fb)))
         }
         GET(n) => {
-            let stack_head = get_stack_derived_result_handle_failed!(stack.get_index(0), FAIL);
+            let stack_head =
+                get_stack_derived_result_handle_failed!(stack.get_index(0), Done(FAIL));
             let r = get_n_pair(n, stack_head)?;
             stack.replace_index(0, r.clone());
-            Result::Ok(GET(*n))
+            Result::Ok(Done(GET(*n)))
         }
 
         UPDATE(n) => {
             ensure_stack_derived!(
                 stack.ensure_stack_atleast(2),
                 "Stack too small!".to_string(),
-                FAIL
+                Done(FAIL)
             );
 
             let stack_head =
-                get_stack_derived_result_handle_failed!(stack.get_index(0), FAIL).clone();
+                get_stack_derived_result_handle_failed!(stack.get_index(0), Done(FAIL)).clone();
             let mut update_target =
-                get_stack_derived_result_handle_failed!(stack.get_index(1), FAIL).clone();
+                get_stack_derived_result_handle_failed!(stack.get_index(1), Done(FAIL)).clone();
             update_n_pair(n, &stack_head, &mut update_target)?;
             stack.pop();
             stack.replace_index(0, update_target);
-            Result::Ok(UPDATE(*n))
+            Result::Ok(Done(UPDATE(*n)))
         }
         DUP(n) => {
             if *n > 0 {
                 ensure_stack_derived!(
                     stack.ensure_stack_atleast(*n),
                     "Stack too small!".to_string(),
-                    FAIL
+                    Done(FAIL)
                 );
-                let target = get_stack_derived_result_handle_failed!(stack.get_index(n - 1), FAIL);
+                let target =
+                    get_stack_derived_result_handle_failed!(stack.get_index(n - 1), Done(FAIL));
+                if !check_attribute(&Duplicable, target) {
+                    return Result::Err(format!("Type {} is not duplicable", target));
+                }
                 stack.push(target.clone());
-                Result::Ok(DUP(*n))
+                Result::Ok(Done(DUP(*n)))
             } else {
                 Result::Err("DUP(0) is forbidden".to_string())
             }
@@ -1060,22 +1922,22 @@ fn typecheck_one(
             ensure_stack_derived!(
                 stack.ensure_stack_atleast(*n),
                 "Stack too small!".to_string(),
-                FAIL
+                Done(FAIL)
             );
-            let pair = get_stack_derived_result_handle_failed!(mk_pair(stack, *n), FAIL);
+            let pair = get_stack_derived_result_handle_failed!(mk_pair(stack, *n), Done(FAIL));
             stack.push(pair);
-            Result::Ok(PAIR(*n))
+            Result::Ok(Done(PAIR(*n)))
         }
         UNPAIR(n) => {
             if *n >= 2 {
                 ensure_stack_derived!(
                     stack.ensure_stack_atleast(1),
                     "Stack too small!".to_string(),
-                    FAIL
+                    Done(FAIL)
                 );
-                let stack_head = get_stack_derived_result_handle_failed!(stack.pop(), FAIL);
+                let stack_head = get_stack_derived_result_handle_failed!(stack.pop(), Done(FAIL));
                 unmk_pair(&stack_head, *n, stack)?;
-                Result::Ok(UNPAIR(*n))
+                Result::Ok(Done(UNPAIR(*n)))
             } else {
                 Result::Err("PAIR(<2) is forbidden".to_string())
             }
@@ -1084,71 +1946,100 @@ fn typecheck_one(
             ensure_stack_derived!(
                 stack.ensure_stack_atleast(*n + 1),
                 "Stack too small!".to_string(),
-                FAIL
+                Done(FAIL)
             );
-            stack.move_element(*n, 0);
-            Result::Ok(DIG(*n))
+            stack.move_element(*n, 0)?;
+            Result::Ok(Done(DIG(*n)))
         }
 
         DUG(n) => {
             ensure_stack_derived!(
                 stack.ensure_stack_atleast(*n + 1),
                 "Stack too small!".to_string(),
-                FAIL
+                Done(FAIL)
             );
-            stack.move_element(0, *n);
-            Result::Ok(DUG(*n))
+            stack.move_element(0, *n)?;
+            Result::Ok(Done(DUG(*n)))
         }
 
         DROP(n) => {
             ensure_stack_derived!(
                 stack.ensure_stack_atleast(*n),
                 "Stack too small!".to_string(),
-                FAIL
+                Done(FAIL)
             );
             for _ in 1..=*n {
                 stack.pop();
             }
-            Result::Ok(DROP(*n))
+            Result::Ok(Done(DROP(*n)))
         }
         DIP(n, instr) => {
             if n > &0 {
                 ensure_stack_derived!(
                     stack.ensure_stack_atleast(*n),
                     "Stack too small!".to_string(),
-                    FAIL
+                    {
+                        let n = *n;
+                        failed_block(instr, move |tins| DIP(n, tins))
+                    }
                 );
-                let mut temp_stack = stack.clone_tail_at(*n);
-                let tins = typecheck(tcenv, instr, &mut temp_stack)?;
-                let mut result_stack = stack.clone_head_till(*n);
-                result_stack.append_stack(&mut temp_stack);
-                *stack = result_stack;
-                Result::Ok(DIP(*n, tins))
+                let n = *n;
+                Result::Ok(Blocks(
+                    vec![(instr, stack.clone_tail_at(n))],
+                    Box::new(move |stack, results| {
+                        let (tins, mut temp_stack) = one_block(results);
+                        let mut result_stack = stack.clone_head_till(n);
+                        result_stack.append_stack(&mut temp_stack);
+                        *stack = result_stack;
+                        Result::Ok(DIP(n, tins))
+                    }),
+                ))
             } else {
                 Result::Err("DIP instruction's argument cannot be zero".to_string())
             }
         }
+        SEQ(instr) => Result::Ok(Blocks(
+            vec![(instr, stack.clone())],
+            Box::new(|stack, results| {
+                let (tins, result_stack) = one_block(results);
+                *stack = result_stack;
+                Result::Ok(SEQ(tins))
+            }),
+        )),
         LAMBDA_REC(it, ot, instr) => {
-            let mut temp_stack = StackState::from(vec![
+            check_type_available(it, tcenv.protocol)?;
+            check_type_available(ot, tcenv.protocol)?;
+            let temp_stack = StackState::from(vec![
                 it.clone(),
-                MLambda(Box::new((it.clone(), ot.clone()))),
+                MLambda(Arc::new((it.clone(), ot.clone()))),
             ]);
-            let tins = typecheck(tcenv, instr, &mut temp_stack)?;
-            let temp_stack_len = temp_stack.len();
-            let temp_stack_head = temp_stack.get_index(0);
-            match (temp_stack_len, temp_stack_head) {
-                (SdOk(l), SdOk(Result::Ok(sh))) => {
-                    if sh == ot && l == 1 {
-                        stack.push(MLambda(Box::new((it.clone(), ot.clone()))));
-                        Result::Ok(LAMBDA_REC(it.clone(), ot.clone(), tins))
-                    } else {
-                        Result::Err(String::from(
+            Result::Ok(Blocks(
+                vec![(instr, temp_stack)],
+                Box::new(move |stack, results| {
+                    let (tins, temp_stack) = one_block(results);
+                    let temp_stack_len = temp_stack.len();
+                    let temp_stack_head = temp_stack.get_index(0);
+                    match (temp_stack_len, temp_stack_head) {
+                        (SdOk(l), SdOk(Result::Ok(sh))) => {
+                            if sh == ot && l == 1 {
+                                stack.push(MLambda(Arc::new((it.clone(), ot.clone()))));
+                                Result::Ok(LAMBDA_REC(it.clone(), ot.clone(), tins))
+                            } else {
+                                Result::Err(String::from(
+                                    "Unexpected output stack for lambda rec lambda",
+                                ))
+                            }
+                        }
+                        (SdOk(_), SdOk(Result::Err(_))) => Result::Err(String::from(
                             "Unexpected output stack for lambda rec lambda",
-                        ))
+                        )),
+                        _ => {
+                            stack.push(MLambda(Arc::new((it.clone(), ot.clone()))));
+                            Result::Ok(LAMBDA_REC(it.clone(), ot.clone(), tins))
+                        }
                     }
-                }
-                _ => Result::Ok(LAMBDA_REC(it.clone(), ot.clone(), tins)),
-            }
+                }),
+            ))
         }
     }
 }
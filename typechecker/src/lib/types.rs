@@ -1,13 +1,25 @@
 #![allow(non_camel_case_types)]
 #![allow(clippy::enum_variant_names)]
 #![allow(clippy::upper_case_acronyms)]
+use crate::encoding::*;
+use crate::instructions::InstructionRegistry;
+use crate::limits::Limit;
+use crate::limits::Limits;
+use crate::limits::Usage;
+use crate::memo::BlockMemo;
+use crate::micheline::Node;
+use crate::protocol::Protocol;
+use crate::stack::SharedStack;
+use crate::types::MAtomic::*;
 use crate::types::MType::*;
 use core::cmp::Eq;
 use core::cmp::Ordering;
 use core::fmt::Debug;
+use num_bigint::BigInt;
+use num_bigint::BigUint;
 use std::collections::BTreeMap;
 use std::collections::BTreeSet;
-use std::collections::VecDeque;
+use std::sync::Arc;
 
 pub type ConcreteType = MType<MAtomic>;
 
@@ -27,29 +39,133 @@ pub enum MAtomic {
     MUnit,
     MOperation,
     MSignature,
+    // The type of a stack element left unknown by an error, which stands for
+    // any type so that the code after the error is still checked.
+    MUnknown,
 }
 
-#[derive(Debug, Eq, PartialEq)]
+// Arguments are shared rather than owned, so that cloning a type, as DUP or
+// a branch does with types that can be hundreds of nodes deep, is O(1), and
+// comparing two clones of the same type stops at the shared pointer.
+#[derive(Debug, Clone)]
 pub enum MType<T> {
-    MTicket(Box<MType<T>>),
-    MOption(Box<MType<T>>),
-    MContract(Box<MType<T>>),
-    MPair(Box<(MType<T>, MType<T>)>),
-    MOr(Box<(MType<T>, MType<T>)>),
-    MList(Box<MType<T>>),
-    MLambda(Box<(MType<T>, MType<T>)>),
-    MBigMap(Box<(MType<T>, MType<T>)>),
-    MMap(Box<(MType<T>, MType<T>)>),
-    MSet(Box<MType<T>>),
+    MTicket(Arc<MType<T>>),
+    MOption(Arc<MType<T>>),
+    MContract(Arc<MType<T>>),
+    MPair(Arc<(MType<T>, MType<T>)>),
+    MOr(Arc<(MType<T>, MType<T>)>),
+    MList(Arc<MType<T>>),
+    MLambda(Arc<(MType<T>, MType<T>)>),
+    MBigMap(Arc<(MType<T>, MType<T>)>),
+    MMap(Arc<(MType<T>, MType<T>)>),
+    MSet(Arc<MType<T>>),
     MWrapped(T),
 }
 
-impl<T: Clone> Clone for MType<T> {
-    fn clone(&self) -> Self {
-        map_mtype(self, &|x| x.clone())
+impl std::fmt::Display for MAtomic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            MChainId => "chain_id",
+            MBytes => "bytes",
+            MAddress => "address",
+            MNat => "nat",
+            MInt => "int",
+            MString => "string",
+            MBool => "bool",
+            MKey => "key",
+            MKeyHash => "key_hash",
+            MMutez => "mutez",
+            MTimestamp => "timestamp",
+            MUnit => "unit",
+            MOperation => "operation",
+            MSignature => "signature",
+            MUnknown => "_",
+        };
+        write!(f, "{}", name)
     }
 }
 
+// Renders types in Michelson syntax, parenthesizing non-atomic arguments.
+impl<T: std::fmt::Display> std::fmt::Display for MType<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fn arg<T: std::fmt::Display>(t: &MType<T>) -> String {
+            match t {
+                MWrapped(w) => w.to_string(),
+                t => format!("({})", t),
+            }
+        }
+        match self {
+            MWrapped(w) => write!(f, "{}", w),
+            MTicket(t) => write!(f, "ticket {}", arg(t)),
+            MOption(t) => write!(f, "option {}", arg(t)),
+            MContract(t) => write!(f, "contract {}", arg(t)),
+            MList(t) => write!(f, "list {}", arg(t)),
+            MSet(t) => write!(f, "set {}", arg(t)),
+            MPair(b) => write!(f, "pair {} {}", arg(&b.0), arg(&b.1)),
+            MOr(b) => write!(f, "or {} {}", arg(&b.0), arg(&b.1)),
+            MLambda(b) => write!(f, "lambda {} {}", arg(&b.0), arg(&b.1)),
+            MMap(b) => write!(f, "map {} {}", arg(&b.0), arg(&b.1)),
+            MBigMap(b) => write!(f, "big_map {} {}", arg(&b.0), arg(&b.1)),
+        }
+    }
+}
+
+impl MType<MAtomic> {
+    pub fn is_unknown(&self) -> bool {
+        matches!(self, MWrapped(MUnknown))
+    }
+
+    pub fn has_unknown(&self) -> bool {
+        match self {
+            MWrapped(a) => *a == MUnknown,
+            MTicket(t) | MOption(t) | MContract(t) | MList(t) | MSet(t) => t.has_unknown(),
+            MPair(b) | MOr(b) | MLambda(b) | MBigMap(b) | MMap(b) => {
+                b.0.has_unknown() || b.1.has_unknown()
+            }
+        }
+    }
+
+    // Whether the two types are the same where both are known.
+    pub fn agrees_with(&self, other: &Self) -> bool {
+        match (self, other) {
+            (a, b) if a.is_unknown() || b.is_unknown() => true,
+            (MWrapped(a), MWrapped(b)) => a == b,
+            (MTicket(a), MTicket(b))
+            | (MOption(a), MOption(b))
+            | (MContract(a), MContract(b))
+            | (MList(a), MList(b))
+            | (MSet(a), MSet(b)) => a.agrees_with(b),
+            (MPair(a), MPair(b))
+            | (MOr(a), MOr(b))
+            | (MLambda(a), MLambda(b))
+            | (MBigMap(a), MBigMap(b))
+            | (MMap(a), MMap(b)) => a.0.agrees_with(&b.0) && a.1.agrees_with(&b.1),
+            _ => false,
+        }
+    }
+}
+
+impl<T: PartialEq> PartialEq for MType<T> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (MWrapped(a), MWrapped(b)) => a == b,
+            (MTicket(a), MTicket(b))
+            | (MOption(a), MOption(b))
+            | (MContract(a), MContract(b))
+            | (MList(a), MList(b))
+            | (MSet(a), MSet(b)) => Arc::ptr_eq(a, b) || a == b,
+            (MPair(a), MPair(b))
+            | (MOr(a), MOr(b))
+            | (MLambda(a), MLambda(b))
+            | (MBigMap(a), MBigMap(b))
+            | (MMap(a), MMap(b)) => Arc::ptr_eq(a, b) || a == b,
+            _ => false,
+        }
+    }
+}
+
+impl<T: Eq> Eq for MType<T> {}
+
 #[derive(Debug, Clone)]
 pub enum SomeValue {
     Atomic(AtomicValue),
@@ -58,29 +174,46 @@ pub enum SomeValue {
 
 #[derive(Debug, Clone)]
 pub enum AtomicValue {
-    AVNumber(i32),
+    // Numbers are unbounded in Michelson.
+    AVNumber(BigInt),
     AVString(String),
     AVBool(bool),
+    AVBytes(Vec<u8>),
     AVUnit,
 }
 
+/// The largest amount of mutez, 2^63 - 1, as the protocol keeps amounts in
+/// signed 64 bit integers.
+pub const MAX_MUTEZ: i64 = i64::MAX;
+
 #[derive(Debug, Clone)]
 pub enum MValue {
     VUnit,
-    VNat(u32),
-    VInt(i32),
+    VNat(BigUint),
+    VInt(BigInt),
     VBool(bool),
     VString(String),
     VPair(Box<(MValue, MValue)>),
     VRight(Box<MValue>),
     VLeft(Box<MValue>),
     VSome(Box<MValue>),
-    VMutez(u32),
+    VNone,
+    // At most MAX_MUTEZ.
+    VMutez(i64),
+    VBytes(Vec<u8>),
+    // Seconds since the epoch.
+    VTimestamp(i64),
+    // Values below are kept in their base58check form.
+    VAddress(String),
+    VKeyHash(String),
+    VKey(String),
+    VSignature(String),
+    VChainId(String),
     VMap(BTreeMap<MValue, MValue>),
     VBigMap(BTreeMap<MValue, MValue>),
     VList(Vec<MValue>),
     VSet(BTreeSet<MValue>),
-    VLambda(Vec<CompoundInstruction<MValue>>),
+    VLambda(Vec<LocatedInstruction<MValue>>),
 }
 
 use MValue::*;
@@ -95,25 +228,75 @@ impl PartialEq for MValue {
 
 impl PartialOrd for MValue {
     fn partial_cmp(&self, m2: &Self) -> Option<Ordering> {
-        match (self, m2) {
-            (VNat(u1), VNat(u2)) => u1.partial_cmp(u2),
-            (VInt(i1), VInt(i2)) => i1.partial_cmp(i2),
-            (VBool(b1), VBool(b2)) => b1.partial_cmp(b2),
-            (VString(s1), VString(s2)) => s1.partial_cmp(s2),
-            (VPair(s1), VPair(s2)) => {
-                s1.0.partial_cmp(&s2.0)
-                    .partial_cmp(&(s1.1).partial_cmp(&s2.1))
-            }
-            _ => panic!("Uncomparable types!"),
+        Some(self.cmp(m2))
+    }
+}
+
+impl MValue {
+    // Order of values of different variants. Values of a comparable type only
+    // meet values of another variant when they are both options or both
+    // `or`s, where `None` comes before `Some` and `Left` before `Right`.
+    fn rank(&self) -> u8 {
+        match self {
+            VNone | VLeft(_) => 0,
+            VSome(_) | VRight(_) => 1,
+            VUnit => 2,
+            VNat(_) => 3,
+            VInt(_) => 4,
+            VBool(_) => 5,
+            VString(_) => 6,
+            VPair(_) => 7,
+            VMutez(_) => 8,
+            VBytes(_) => 9,
+            VTimestamp(_) => 10,
+            VAddress(_) => 11,
+            VKeyHash(_) => 12,
+            VKey(_) => 13,
+            VSignature(_) => 14,
+            VChainId(_) => 15,
+            VMap(_) => 16,
+            VBigMap(_) => 17,
+            VList(_) => 18,
+            VSet(_) => 19,
+            VLambda(_) => 20,
         }
     }
 }
 
+// Values in base58check form are compared through their binary form, as the
+// protocol does. Values that cannot be decoded are not accepted by the
+// typechecker, so they are only ordered to keep the order total.
+fn compare_encoded<T: Ord>(s1: &str, s2: &str, binary: impl Fn(&str) -> Option<T>) -> Ordering {
+    binary(s1).cmp(&binary(s2)).then_with(|| s1.cmp(s2))
+}
+
 impl Ord for MValue {
     fn cmp(&self, m2: &Self) -> Ordering {
-        match self.partial_cmp(m2) {
-            Some(x) => x,
-            None => panic!("Uncomparable types!"),
+        match (self, m2) {
+            (VUnit, VUnit) | (VNone, VNone) => Ordering::Equal,
+            (VNat(u1), VNat(u2)) => u1.cmp(u2),
+            (VMutez(m1), VMutez(m2)) => m1.cmp(m2),
+            (VInt(i1), VInt(i2)) => i1.cmp(i2),
+            (VBool(b1), VBool(b2)) => b1.cmp(b2),
+            (VString(s1), VString(s2)) => s1.cmp(s2),
+            (VBytes(b1), VBytes(b2)) => b1.cmp(b2),
+            (VTimestamp(t1), VTimestamp(t2)) => t1.cmp(t2),
+            (VAddress(a1), VAddress(a2)) => compare_encoded(a1, a2, address_to_bytes),
+            (VKeyHash(k1), VKeyHash(k2)) => compare_encoded(k1, k2, key_hash_to_bytes),
+            (VKey(k1), VKey(k2)) => compare_encoded(k1, k2, key_to_bytes),
+            (VSignature(s1), VSignature(s2)) => compare_encoded(s1, s2, signature_to_bytes),
+            (VChainId(c1), VChainId(c2)) => compare_encoded(c1, c2, chain_id_to_bytes),
+            (VPair(s1), VPair(s2)) => s1.0.cmp(&s2.0).then_with(|| s1.1.cmp(&s2.1)),
+            (VLeft(v1), VLeft(v2)) | (VRight(v1), VRight(v2)) | (VSome(v1), VSome(v2)) => {
+                v1.cmp(v2)
+            }
+            // The remaining types are not comparable in Michelson. They are
+            // ordered structurally only so that the order stays total.
+            (VList(l1), VList(l2)) => l1.cmp(l2),
+            (VSet(s1), VSet(s2)) => s1.cmp(s2),
+            (VMap(m1), VMap(m2)) | (VBigMap(m1), VBigMap(m2)) => m1.cmp(m2),
+            (VLambda(_), VLambda(_)) => Ordering::Equal,
+            _ => self.rank().cmp(&m2.rank()),
         }
     }
 }
@@ -122,33 +305,37 @@ impl Ord for MValue {
 pub struct Contract<T> {
     pub parameter: ConcreteType,
     pub storage: ConcreteType,
-    pub code: Vec<CompoundInstruction<T>>,
+    pub code: Vec<LocatedInstruction<T>>,
+    // Annotations of the parameter and storage types as written in the source.
+    pub parameter_annotations: TypeAnnotations,
+    pub storage_annotations: TypeAnnotations,
 }
 
-pub type SomeKeyValue = (SomeValue, SomeValue);
-
-#[derive(Debug, Clone)]
-pub enum SeqItem {
-    SqValue(Vec<SomeValue>),
-    SqInstr(Vec<CompoundInstruction<SomeValue>>),
+/// The annotations of a type, which `ConcreteType` does not keep, in the
+/// shape of the type: those of the type itself, and then those of each of its
+/// arguments. `location` is the offset of the type in the source.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct TypeAnnotations {
+    pub location: usize,
+    pub annotations: Vec<String>,
+    pub args: Vec<TypeAnnotations>,
 }
 
-use SeqItem::*;
-
-impl SeqItem {
-    pub fn len(&self) -> usize {
-        match self {
-            SqValue(s) => s.len(),
-            SqInstr(s) => s.len(),
-        }
-    }
-}
+pub type SomeKeyValue = (SomeValue, SomeValue);
 
+// Sequences, `{ ... }`, are read as what the type of the value says they
+// hold; see `elaborate_typed_value`.
 #[derive(Debug, Clone)]
 pub enum CompositeValue {
     CVPair(SomeValue, SomeValue),
-    CVSeq(SeqItem),
+    // The elements of a list or a set, or of a pair written `{ a ; b }`.
+    CVSeq(Vec<SomeValue>),
+    // The code of a lambda.
+    CVLambda(Vec<LocatedInstruction<SomeValue>>),
     CKVList(Vec<SomeKeyValue>),
+    // A sequence read where the type of the value was not known, as it was
+    // read, until the typechecker gives it one.
+    CVRaw(Node),
     CVLeft(SomeValue),
     CVRight(SomeValue),
     CVSome(SomeValue),
@@ -163,43 +350,215 @@ pub enum ArgValue<T> {
 
 #[derive(Debug, Clone)]
 pub struct Instruction<T> {
-    pub location: usize,
     pub name: String,
     pub args: Vec<ArgValue<T>>,
 }
 
 #[derive(Debug, Clone)]
 pub enum CompoundInstruction<T> {
-    IF(Vec<CompoundInstruction<T>>, Vec<CompoundInstruction<T>>),
-    IF_CONS(Vec<CompoundInstruction<T>>, Vec<CompoundInstruction<T>>),
-    IF_SOME(Vec<CompoundInstruction<T>>, Vec<CompoundInstruction<T>>),
-    IF_NONE(Vec<CompoundInstruction<T>>, Vec<CompoundInstruction<T>>),
-    IF_LEFT(Vec<CompoundInstruction<T>>, Vec<CompoundInstruction<T>>),
+    IF(Vec<LocatedInstruction<T>>, Vec<LocatedInstruction<T>>),
+    IF_CONS(Vec<LocatedInstruction<T>>, Vec<LocatedInstruction<T>>),
+    IF_SOME(Vec<LocatedInstruction<T>>, Vec<LocatedInstruction<T>>),
+    IF_NONE(Vec<LocatedInstruction<T>>, Vec<LocatedInstruction<T>>),
+    IF_LEFT(Vec<LocatedInstruction<T>>, Vec<LocatedInstruction<T>>),
     PAIR(usize),
     UNPAIR(usize),
-    DIP(usize, Vec<CompoundInstruction<T>>),
+    DIP(usize, Vec<LocatedInstruction<T>>),
     DUP(usize),
     DIG(usize),
     DUG(usize),
     DROP(usize),
     GET(usize),
     UPDATE(usize),
-    MAP(Vec<CompoundInstruction<T>>),
-    ITER(Vec<CompoundInstruction<T>>),
-    LOOP(Vec<CompoundInstruction<T>>),
-    LOOP_LEFT(Vec<CompoundInstruction<T>>),
-    LAMBDA_REC(ConcreteType, ConcreteType, Vec<CompoundInstruction<T>>),
+    MAP(Vec<LocatedInstruction<T>>),
+    ITER(Vec<LocatedInstruction<T>>),
+    LOOP(Vec<LocatedInstruction<T>>),
+    LOOP_LEFT(Vec<LocatedInstruction<T>>),
+    LAMBDA_REC(ConcreteType, ConcreteType, Vec<LocatedInstruction<T>>),
+    // A block nested in a block, `{ ... }`.
+    SEQ(Vec<LocatedInstruction<T>>),
     SELF,
     FAIL,
     FAILWITH,
     Other(Instruction<T>),
 }
 
-pub struct TcEnv {
-    pub self_type: ConcreteType,
+// An instruction of a block, with the byte offset of its first token in the
+// source.
+#[derive(Debug, Clone)]
+pub struct LocatedInstruction<T> {
+    pub location: usize,
+    pub instruction: CompoundInstruction<T>,
+}
+
+impl<T> CompoundInstruction<T> {
+    pub fn name(&self) -> &str {
+        match self {
+            IF(_, _) => "IF",
+            IF_CONS(_, _) => "IF_CONS",
+            IF_SOME(_, _) => "IF_SOME",
+            IF_NONE(_, _) => "IF_NONE",
+            IF_LEFT(_, _) => "IF_LEFT",
+            PAIR(_) => "PAIR",
+            UNPAIR(_) => "UNPAIR",
+            DIP(_, _) => "DIP",
+            DUP(_) => "DUP",
+            DIG(_) => "DIG",
+            DUG(_) => "DUG",
+            DROP(_) => "DROP",
+            GET(_) => "GET",
+            UPDATE(_) => "UPDATE",
+            MAP(_) => "MAP",
+            ITER(_) => "ITER",
+            LOOP(_) => "LOOP",
+            LOOP_LEFT(_) => "LOOP_LEFT",
+            LAMBDA_REC(_, _, _) => "LAMBDA_REC",
+            SEQ(_) => "SEQ",
+            SELF => "SELF",
+            FAIL => "FAIL",
+            FAILWITH => "FAILWITH",
+            Other(i) => &i.name,
+        }
+    }
 }
 
-#[derive(Debug)]
+use CompoundInstruction::*;
+
+/// All instructions of `code`, including the ones in nested blocks and in
+/// lambda values, in the order they appear in the source, with their block
+/// nesting depth.
+pub fn instructions_in_source_order(
+    code: &Vec<LocatedInstruction<SomeValue>>,
+) -> Vec<(usize, &LocatedInstruction<SomeValue>)> {
+    fn value<'a>(
+        v: &'a SomeValue,
+        depth: usize,
+        out: &mut Vec<(usize, &'a LocatedInstruction<SomeValue>)>,
+    ) {
+        if let SomeValue::Composite(cv) = v {
+            match cv.as_ref() {
+                CompositeValue::CVPair(a, b) => {
+                    value(a, depth, out);
+                    value(b, depth, out);
+                }
+                CompositeValue::CVSeq(vs) => vs.iter().for_each(|v| value(v, depth, out)),
+                CompositeValue::CVLambda(is) => block(is, depth + 1, out),
+                CompositeValue::CKVList(kvs) => kvs.iter().for_each(|(k, v)| {
+                    value(k, depth, out);
+                    value(v, depth, out);
+                }),
+                CompositeValue::CVLeft(v)
+                | CompositeValue::CVRight(v)
+                | CompositeValue::CVSome(v) => value(v, depth, out),
+                CompositeValue::CVNone | CompositeValue::CVRaw(_) => {}
+            }
+        }
+    }
+    fn block<'a>(
+        code: &'a Vec<LocatedInstruction<SomeValue>>,
+        depth: usize,
+        out: &mut Vec<(usize, &'a LocatedInstruction<SomeValue>)>,
+    ) {
+        for instruction in code {
+            out.push((depth, instruction));
+            match &instruction.instruction {
+                Other(i) => i.args.iter().for_each(|a| {
+                    if let ArgValue::ValueArg(v) = a {
+                        value(v, depth, out)
+                    }
+                }),
+                IF(a, b) | IF_CONS(a, b) | IF_SOME(a, b) | IF_NONE(a, b) | IF_LEFT(a, b) => {
+                    block(a, depth + 1, out);
+                    block(b, depth + 1, out);
+                }
+                DIP(_, b)
+                | MAP(b)
+                | ITER(b)
+                | LOOP(b)
+                | LOOP_LEFT(b)
+                | LAMBDA_REC(_, _, b)
+                | SEQ(b) => block(b, depth + 1, out),
+                _ => {}
+            }
+        }
+    }
+    let mut out = vec![];
+    block(code, 0, &mut out);
+    out
+}
+
+pub struct TcEnv<'a> {
+    // Parameter type of the contract the code belongs to, if any. `SELF` is
+    // rejected in code that is not part of a contract, such as lambdas in
+    // values given on their own.
+    pub self_type: Option<ConcreteType>,
+    // Signatures of the instructions that are not typechecked on their own,
+    // usually `&MICHELSON_INSTRUCTIONS`.
+    pub instructions: &'a InstructionRegistry,
+    // Protocol whose rules apply to the instructions and types that are not
+    // in the table.
+    pub protocol: Protocol,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Diagnostic {
+    pub location: Option<usize>, // Byte offset of the offending instruction, when known.
+    pub kind: ErrorKind,
+    pub message: String,
+}
+
+/// What a diagnostic is about.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum ErrorKind {
+    Syntax,
+    Type,
+    // A limit on the resources of the session, which stops there.
+    LimitExceeded(Limit),
+    // A bug of the typechecker, such as a panic caught by a batch.
+    Internal,
+}
+
+impl std::fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ErrorKind::Syntax => write!(f, "syntax error"),
+            ErrorKind::Type => write!(f, "type error"),
+            ErrorKind::LimitExceeded(l) => write!(f, "{} limit exceeded", l.name()),
+            ErrorKind::Internal => write!(f, "internal error"),
+        }
+    }
+}
+
+/// Mutable state of a single typechecking run.
+#[derive(Debug, Default)]
+pub struct TcSession {
+    pub diagnostics: Vec<Diagnostic>,
+    // When set, the stack after each instruction is recorded here, keyed by
+    // the location of the instruction.
+    pub stack_trace: Option<Vec<(usize, ConcreteStack)>>,
+    pub limits: Limits,
+    pub usage: Usage,
+    pub memo: BlockMemo,
+}
+
+/// How code that typechecks relates to the output stack it was declared with.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Conformance {
+    Conforms,
+    // Every path ends in a failure, so the code has no output stack at all.
+    AlwaysFails,
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.location {
+            Some(l) => write!(f, "At {}: {}", l, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub enum ArgConstraint {
     CAtomic(MAtomic),
     CWarg(char, Vec<Attribute>),    // An type variable.
@@ -207,21 +566,51 @@ pub enum ArgConstraint {
     CTypeArgRef(char), // A argument that accept a value of a type referred by previously encountered TypeArg.
 }
 
+impl std::fmt::Display for ArgConstraint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CAtomic(a) => write!(f, "{}", a),
+            CTypeArgRef(c) => write!(f, "'{}", c),
+            CWarg(c, atrs) | CTypeArg(c, atrs) => {
+                if atrs.is_empty() {
+                    write!(f, "'{}", c)
+                } else {
+                    let atrs: Vec<String> = atrs.iter().map(|a| format!("{:?}", a)).collect();
+                    write!(f, "'{}<{}>", c, atrs.join("+").to_lowercase())
+                }
+            }
+        }
+    }
+}
+
+use ArgConstraint::*;
+
 pub type Constraint = MType<ArgConstraint>;
 
 pub type StackArg = Constraint;
 
 pub type StackResult = MType<StackResultElem>;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum StackResultElem {
     TRef(char),
     ElemType(MAtomic),
 }
 
+impl std::fmt::Display for StackResultElem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TRef(c) => write!(f, "'{}", c),
+            ElemType(a) => write!(f, "{}", a),
+        }
+    }
+}
+
+use StackResultElem::*;
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum StackState<T> {
-    LiveStack(VecDeque<MType<T>>),
+    LiveStack(SharedStack<MType<T>>),
     FailedStack,
 }
 
@@ -312,13 +701,10 @@ impl<T: Eq + Clone> StackState<T> {
     }
     pub fn get_index(&self, i: usize) -> StackDerived<Result<&MType<T>, String>> {
         match self {
-            LiveStack(v) => {
-                if v.is_empty() {
-                    SdOk(Result::Err("Stack too short..".to_string()))
-                } else {
-                    SdOk(Result::Ok(&v[i]))
-                }
-            }
+            LiveStack(v) => match v.get(i) {
+                Some(t) => SdOk(Result::Ok(t)),
+                None => SdOk(Result::Err("Stack too short..".to_string())),
+            },
             FailedStack => SdFailed,
         }
     }
@@ -339,25 +725,22 @@ impl<T: Eq + Clone> StackState<T> {
         }
     }
 
-    pub fn move_element(&mut self, f: usize, t: usize) {
+    pub fn move_element(&mut self, f: usize, t: usize) -> Result<(), String> {
         match self {
             LiveStack(v) => match v.remove(f) {
                 Some(a) => {
                     v.insert(t, a);
+                    Result::Ok(())
                 }
-                None => {
-                    panic!("Failed to move stack element!");
-                }
+                None => Result::Err(format!("Stack has no element {} to move", f)),
             },
-            FailedStack => {}
+            FailedStack => Result::Ok(()),
         }
     }
 
     pub fn replace_index(&mut self, i: usize, t: MType<T>) {
         match self {
-            LiveStack(v) => {
-                v[i] = t;
-            }
+            LiveStack(v) => v.set(i, t),
             FailedStack => {}
         }
     }
@@ -366,14 +749,14 @@ impl<T: Eq + Clone> StackState<T> {
         match self {
             LiveStack(v) => match src {
                 LiveStack(v1) => v.append(v1),
-                FailedStack => {}
+                FailedStack => self.fail(),
             },
             FailedStack => {}
         }
     }
 
     pub fn new() -> Self {
-        LiveStack(VecDeque::new())
+        LiveStack(SharedStack::new())
     }
 
     pub fn fail(&mut self) {
@@ -381,56 +764,24 @@ impl<T: Eq + Clone> StackState<T> {
     }
 
     pub fn from(v: Vec<MType<T>>) -> Self {
-        LiveStack(VecDeque::from(v))
+        LiveStack(SharedStack::from(v))
     }
 
-    pub fn compare<'a>(&'a self, s: &'a Self) -> StackCompResult {
-        match self {
-            FailedStack => match s {
-                FailedStack => BothFailed,
-                _ => LeftFailed,
-            },
-            LiveStack(v) => match s {
-                LiveStack(s_) => {
-                    if *s_ == *v {
-                        Match
-                    } else {
-                        NoMatch
-                    }
-                }
-                FailedStack => RightFailed,
-            },
-        }
-    }
-
-    pub fn compare_singleton(&self, s: &MType<T>) -> bool {
-        match self {
-            FailedStack => true,
-            LiveStack(v) => v[0] == *s,
-        }
-    }
-
-    pub fn clone_tail(&mut self) -> Self {
-        match self {
-            LiveStack(v) => {
-                let mut slice = v.clone();
-                slice.pop_front();
-                LiveStack(slice)
-            }
-            FailedStack => FailedStack,
-        }
+    // The stack below the top element, sharing it with `self`.
+    pub fn clone_tail(&self) -> Self {
+        self.clone_tail_at(1)
     }
 
     pub fn clone_tail_at(&self, l: usize) -> Self {
         match self {
-            LiveStack(v) => LiveStack(v.iter().skip(l).cloned().collect()),
+            LiveStack(v) => LiveStack(v.skip(l)),
             FailedStack => FailedStack,
         }
     }
 
     pub fn clone_head_till(&self, l: usize) -> Self {
         match self {
-            LiveStack(v) => LiveStack(v.iter().take(l).cloned().collect()),
+            LiveStack(v) => LiveStack(v.take(l)),
             FailedStack => FailedStack,
         }
     }
@@ -455,14 +806,62 @@ impl<T: Eq + Clone> StackState<T> {
     }
 }
 
-#[derive(Debug)]
+impl StackState<MAtomic> {
+    // Stacks match when they have the same types where both are known.
+    pub fn compare<'a>(&'a self, s: &'a Self) -> StackCompResult {
+        match self {
+            FailedStack => match s {
+                FailedStack => BothFailed,
+                _ => LeftFailed,
+            },
+            LiveStack(v) => match s {
+                LiveStack(s_) => {
+                    if s_.len() == v.len() && s_.iter().zip(v.iter()).all(|(a, b)| a.agrees_with(b))
+                    {
+                        Match
+                    } else {
+                        NoMatch
+                    }
+                }
+                FailedStack => RightFailed,
+            },
+        }
+    }
+
+    pub fn compare_singleton(&self, s: &MType<MAtomic>) -> bool {
+        match self {
+            FailedStack => true,
+            LiveStack(v) => v.len() == 1 && v.get(0).is_some_and(|t| t.agrees_with(s)),
+        }
+    }
+}
+
+impl<T: std::fmt::Display> std::fmt::Display for StackState<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LiveStack(v) => {
+                let items: Vec<String> = v.iter().map(|t| t.to_string()).collect();
+                write!(f, "[ {} ]", items.join(" : "))
+            }
+            FailedStack => write!(f, "[ FAILED ]"),
+        }
+    }
+}
+
+impl<T: Eq + Clone> Default for StackState<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct InstructionDef {
     pub args: Vec<Constraint>,
     pub input_stack: Vec<StackArg>,
     pub output_stack: Vec<StackResult>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Attribute {
     Comparable,
     Passable,
@@ -473,22 +872,12 @@ pub enum Attribute {
     Duplicable,
 }
 
-// Parser helpers
-
-#[derive(Debug, Clone, Eq, PartialEq)]
-pub enum DynMType {
-    DMAtomic(MAtomic),
-    DMDyn(String),
-}
-
-use DynMType::*;
-
 pub fn map_mtype_boxed_pair<T, H, F: Fn(&T) -> H>(
     b: &(MType<T>, MType<T>),
     cb: &F,
-) -> Box<(MType<H>, MType<H>)> {
+) -> Arc<(MType<H>, MType<H>)> {
     let (f, s) = b;
-    Box::new((map_mtype(f, cb), map_mtype(s, cb)))
+    Arc::new((map_mtype(f, cb), map_mtype(s, cb)))
 }
 
 pub fn update_n_pair<A: Clone>(
@@ -502,7 +891,7 @@ pub fn update_n_pair<A: Clone>(
         if cb {
             match cn {
                 MPair(b) => {
-                    cn = &mut b.1;
+                    cn = &mut Arc::make_mut(b).1;
                     cb = false;
                 }
                 _ => {
@@ -518,7 +907,7 @@ pub fn update_n_pair<A: Clone>(
     if cb {
         match cn {
             MPair(ref mut b) => {
-                let (f, _) = b.as_mut();
+                let (f, _) = Arc::make_mut(b);
                 *f = src.clone();
                 Result::Ok(f.clone())
             }
@@ -567,15 +956,22 @@ pub fn mk_pair<A: Clone + Eq>(
     tl: &mut StackState<A>,
     n: usize,
 ) -> StackDerived<Result<MType<A>, String>> {
-    if n == 2 {
-        let i1 = get_stack_derived_result!(tl.pop_front());
-        let i2 = get_stack_derived_result!(tl.pop_front());
-        SdOk(Result::Ok(MPair(Box::new((i1, i2)))))
-    } else {
-        let i1 = get_stack_derived_result!(tl.pop_front());
-        let l2 = get_stack_derived_result!(mk_pair(tl, n - 1));
-        SdOk(Result::Ok(MPair(Box::new((i1, l2)))))
+    if n < 2 {
+        return SdOk(Result::Err("PAIR needs at least two elements".to_string()));
     }
+    let mut elems = Vec::with_capacity(n);
+    for _ in 0..n {
+        elems.push(get_stack_derived_result!(tl.pop_front()));
+    }
+    let mut elems = elems.into_iter().rev();
+    let mut pair = match elems.next() {
+        Some(last) => last,
+        None => return SdOk(Result::Err("Stack too small!".to_string())),
+    };
+    for elem in elems {
+        pair = MPair(Arc::new((elem, pair)));
+    }
+    SdOk(Result::Ok(pair))
 }
 
 pub fn unmk_pair<A: Eq + Clone>(
@@ -583,44 +979,77 @@ pub fn unmk_pair<A: Eq + Clone>(
     n: usize,
     stack: &mut StackState<A>,
 ) -> Result<(), String> {
-    if n == 1 {
-        stack.push(t.clone());
-        Result::Ok(())
-    } else {
-        match t {
+    let mut elems = Vec::with_capacity(n);
+    let mut rest = t;
+    for _ in 1..n {
+        match rest {
             MPair(b) => {
-                let (le, rp) = b.as_ref();
-                let r = unmk_pair(rp, n - 1, stack);
-                stack.push(le.clone());
-                r
+                elems.push(&b.0);
+                rest = &b.1;
+            }
+            _ => {
+                return Result::Err(
+                    "Expecting a pair for UNPAIR but got something else".to_string(),
+                )
             }
-            _ => Result::Err("Expecting a pair for UNPAIR but got something else".to_string()),
         }
     }
+    stack.push(rest.clone());
+    for elem in elems.into_iter().rev() {
+        stack.push(elem.clone());
+    }
+    Result::Ok(())
 }
 
+// Arguments are mapped before the types that hold them, from an explicit work
+// stack so that deeply nested types cannot overflow the call stack.
 pub fn map_mtype<T, H, F: Fn(&T) -> H>(ct: &MType<T>, cb: &F) -> MType<H> {
-    match ct {
-        MPair(b) => MPair(map_mtype_boxed_pair(b, cb)),
-        MOr(b) => MOr(map_mtype_boxed_pair(b, cb)),
-        MLambda(b) => MLambda(map_mtype_boxed_pair(b, cb)),
-        MList(l) => MList(Box::new(map_mtype(l, cb))),
-        MTicket(l) => MTicket(Box::new(map_mtype(l, cb))),
-        MContract(l) => MContract(Box::new(map_mtype(l, cb))),
-        MOption(l) => MOption(Box::new(map_mtype(l, cb))),
-        MSet(l) => MSet(Box::new(map_mtype(l, cb))),
-        MMap(b) => MMap(map_mtype_boxed_pair(b, cb)),
-        MBigMap(b) => MBigMap(map_mtype_boxed_pair(b, cb)),
-        MWrapped(w) => MWrapped(cb(w)),
+    enum Visit<'a, T> {
+        Enter(&'a MType<T>),
+        Build(&'a MType<T>),
     }
-}
-pub fn mdyn_to_concrete(m: &MType<DynMType>) -> ConcreteType {
-    map_mtype(m, &|x| dynm_to_matomic(x.clone()))
-}
-
-fn dynm_to_matomic(d: DynMType) -> MAtomic {
-    match d {
-        DMAtomic(a) => a,
-        DMDyn(_) => panic!("Unexpected enum variant!"),
+    let mut todo = vec![Visit::Enter(ct)];
+    let mut done: Vec<MType<H>> = vec![];
+    let arg = |done: &mut Vec<MType<H>>| done.pop().expect("map_mtype: missing argument");
+    while let Some(visit) = todo.pop() {
+        match visit {
+            Visit::Enter(MWrapped(w)) => done.push(MWrapped(cb(w))),
+            Visit::Enter(t) => {
+                todo.push(Visit::Build(t));
+                match t {
+                    MPair(b) | MOr(b) | MLambda(b) | MMap(b) | MBigMap(b) => {
+                        todo.push(Visit::Enter(&b.1));
+                        todo.push(Visit::Enter(&b.0));
+                    }
+                    MList(l) | MTicket(l) | MContract(l) | MOption(l) | MSet(l) => {
+                        todo.push(Visit::Enter(l))
+                    }
+                    MWrapped(_) => {}
+                }
+            }
+            Visit::Build(t) => {
+                let mapped = match t {
+                    MPair(_) | MOr(_) | MLambda(_) | MMap(_) | MBigMap(_) => {
+                        let s = arg(&mut done);
+                        let b = Arc::new((arg(&mut done), s));
+                        match t {
+                            MPair(_) => MPair(b),
+                            MOr(_) => MOr(b),
+                            MLambda(_) => MLambda(b),
+                            MMap(_) => MMap(b),
+                            _ => MBigMap(b),
+                        }
+                    }
+                    MList(_) => MList(Arc::new(arg(&mut done))),
+                    MTicket(_) => MTicket(Arc::new(arg(&mut done))),
+                    MContract(_) => MContract(Arc::new(arg(&mut done))),
+                    MOption(_) => MOption(Arc::new(arg(&mut done))),
+                    MSet(_) => MSet(Arc::new(arg(&mut done))),
+                    MWrapped(w) => MWrapped(cb(w)),
+                };
+                done.push(mapped);
+            }
+        }
     }
+    arg(&mut done)
 }
@@ -0,0 +1,347 @@
+use crate::elaborate::elaborate_block;
+use crate::elaborate::elaborate_number;
+use crate::elaborate::elaborate_string;
+use crate::elaborate::elaborate_type;
+use crate::elaborate::elaborate_typed_value;
+use crate::elaborate::elaborate_value;
+use crate::instructions::MICHELSON_INSTRUCTIONS;
+use crate::interpreter::*;
+use crate::limits::Limits;
+use crate::micheline::Micheline;
+use crate::micheline::Node;
+use crate::parsers::parse_micheline;
+use crate::printer::print_value;
+use crate::protocol::Protocol;
+use crate::typechecker::typecheck_all;
+use crate::typechecker::typecheck_data;
+use crate::types::MAtomic::*;
+use crate::types::MType::*;
+use crate::types::StackCompResult::*;
+use crate::types::*;
+use num_bigint::BigInt;
+
+// Support for the `.tzt` unit test format shared by Michelson
+// implementations: a block of code, an input stack of typed values, and the
+// expected output stack or failure.
+
+#[derive(Debug, Clone)]
+pub enum TztOutput {
+    Stack(Vec<(ConcreteType, SomeValue)>),
+    Failed(SomeValue),
+    // An overflow or underflow error, with the operands it happened on.
+    Overflow(OverflowKind, BigInt, BigInt),
+}
+
+#[derive(Debug, Clone)]
+pub enum TztField {
+    TzCode(Vec<LocatedInstruction<SomeValue>>),
+    TzInput(Vec<(ConcreteType, SomeValue)>),
+    TzOutput(TztOutput),
+    TzAmount(BigInt),
+    TzBalance(BigInt),
+    TzLevel(BigInt),
+    TzNow(SomeValue),
+    TzSelf(String),
+    TzSource(String),
+    TzSender(String),
+    TzChainId(String),
+    TzParameter(ConcreteType),
+}
+
+use TztField::*;
+
+#[derive(Debug, Clone)]
+pub struct TztTest {
+    pub code: Vec<LocatedInstruction<SomeValue>>,
+    pub input: Vec<(ConcreteType, SomeValue)>,
+    pub output: TztOutput,
+    pub amount: i64,
+    pub balance: i64,
+    pub level: u32,
+    pub now: Option<SomeValue>,
+    pub self_address: Option<String>,
+    pub source: Option<String>,
+    pub sender: Option<String>,
+    pub chain_id: Option<String>,
+    // Type of the parameter of the contract the code is run in.
+    pub parameter: ConcreteType,
+}
+
+fn field_name(field: &TztField) -> &'static str {
+    match field {
+        TzCode(_) => "code",
+        TzInput(_) => "input",
+        TzOutput(_) => "output",
+        TzAmount(_) => "amount",
+        TzBalance(_) => "balance",
+        TzLevel(_) => "level",
+        TzNow(_) => "now",
+        TzSelf(_) => "self",
+        TzSource(_) => "source",
+        TzSender(_) => "sender",
+        TzChainId(_) => "chain_id",
+        TzParameter(_) => "parameter",
+    }
+}
+
+fn to_u32(n: BigInt, location: usize, name: &str) -> Result<u32, Diagnostic> {
+    u32::try_from(n).map_err(|_| field_range_error(location, name))
+}
+
+fn to_mutez(n: BigInt, location: usize, name: &str) -> Result<i64, Diagnostic> {
+    match i64::try_from(n) {
+        Result::Ok(n @ 0..=MAX_MUTEZ) => Result::Ok(n),
+        _ => Result::Err(field_range_error(location, name)),
+    }
+}
+
+fn field_range_error(location: usize, name: &str) -> Diagnostic {
+    Diagnostic {
+        location: Some(location),
+        kind: ErrorKind::Syntax,
+        message: format!("Field {} is out of range", name),
+    }
+}
+
+fn elaborate_stack(node: &Node) -> Result<Vec<(ConcreteType, SomeValue)>, Diagnostic> {
+    let Micheline::Seq(items) = &node.expr else {
+        return Result::Err(syntax_error(
+            node,
+            format!("Expecting a stack but found {}", node),
+        ));
+    };
+    let mut stack = vec![];
+    for item in items {
+        match &item.expr {
+            Micheline::Prim { name, args, .. } if name == "Stack_elt" && args.len() == 2 => {
+                let t = elaborate_type(&args[0])?;
+                let v = elaborate_typed_value(&args[1], &t)?;
+                stack.push((t, v))
+            }
+            _ => {
+                return Result::Err(syntax_error(
+                    item,
+                    format!("Expecting Stack_elt but found {}", item),
+                ))
+            }
+        }
+    }
+    Result::Ok(stack)
+}
+
+fn elaborate_output(node: &Node) -> Result<TztOutput, Diagnostic> {
+    match &node.expr {
+        Micheline::Seq(_) => Result::Ok(TztOutput::Stack(elaborate_stack(node)?)),
+        Micheline::Prim { name, args, .. } => match (name.as_str(), &args[..]) {
+            ("Failed", [v]) => Result::Ok(TztOutput::Failed(elaborate_value(v)?)),
+            ("MutezOverflow", [x, y]) => overflow(OverflowKind::MutezOverflow, x, y),
+            ("MutezUnderflow", [x, y]) => overflow(OverflowKind::MutezUnderflow, x, y),
+            ("GeneralOverflow", [x, y]) => overflow(OverflowKind::GeneralOverflow, x, y),
+            _ => Result::Err(syntax_error(node, format!("Unexpected output {}", node))),
+        },
+        _ => Result::Err(syntax_error(node, format!("Unexpected output {}", node))),
+    }
+}
+
+fn overflow(kind: OverflowKind, x: &Node, y: &Node) -> Result<TztOutput, Diagnostic> {
+    Result::Ok(TztOutput::Overflow(
+        kind,
+        elaborate_number(x)?,
+        elaborate_number(y)?,
+    ))
+}
+
+fn elaborate_field(node: &Node) -> Result<TztField, Diagnostic> {
+    let field = match &node.expr {
+        Micheline::Prim { name, args, .. } => match (name.as_str(), &args[..]) {
+            ("code", [c]) => Some(TzCode(elaborate_block(c)?)),
+            ("input", [s]) => Some(TzInput(elaborate_stack(s)?)),
+            ("output", [o]) => Some(TzOutput(elaborate_output(o)?)),
+            ("amount", [n]) => Some(TzAmount(elaborate_number(n)?)),
+            ("balance", [n]) => Some(TzBalance(elaborate_number(n)?)),
+            ("level", [n]) => Some(TzLevel(elaborate_number(n)?)),
+            ("now", [v]) => Some(TzNow(elaborate_value(v)?)),
+            ("self", [s]) => Some(TzSelf(elaborate_string(s)?)),
+            ("source", [s]) => Some(TzSource(elaborate_string(s)?)),
+            ("sender", [s]) => Some(TzSender(elaborate_string(s)?)),
+            ("chain_id", [s]) => Some(TzChainId(elaborate_string(s)?)),
+            ("parameter", [t]) => Some(TzParameter(elaborate_type(t)?)),
+            _ => None,
+        },
+        _ => None,
+    };
+    field.ok_or_else(|| syntax_error(node, format!("Unexpected field {}", node)))
+}
+
+fn syntax_error(node: &Node, message: String) -> Diagnostic {
+    Diagnostic {
+        location: Some(node.location),
+        kind: ErrorKind::Syntax,
+        message,
+    }
+}
+
+pub fn parse_tzt(src: &str) -> Result<TztTest, Diagnostic> {
+    let mut fields = vec![];
+    for node in parse_micheline(src, &Limits::default())? {
+        fields.push((node.location, elaborate_field(&node)?));
+    }
+    let mut seen: Vec<&str> = vec![];
+    let (mut code, mut input, mut output) = (None, None, None);
+    let mut test = TztTest {
+        code: vec![],
+        input: vec![],
+        output: TztOutput::Stack(vec![]),
+        amount: 0,
+        balance: 0,
+        level: 0,
+        now: None,
+        self_address: None,
+        source: None,
+        sender: None,
+        chain_id: None,
+        parameter: MWrapped(MUnit),
+    };
+    for (location, field) in fields {
+        let name = field_name(&field);
+        if seen.contains(&name) {
+            return Result::Err(Diagnostic {
+                location: Some(location),
+                kind: ErrorKind::Syntax,
+                message: format!("Duplicate field {}", name),
+            });
+        }
+        seen.push(name);
+        match field {
+            TzCode(c) => code = Some(c),
+            TzInput(i) => input = Some(i),
+            TzOutput(o) => output = Some(o),
+            TzAmount(n) => test.amount = to_mutez(n, location, name)?,
+            TzBalance(n) => test.balance = to_mutez(n, location, name)?,
+            TzLevel(n) => test.level = to_u32(n, location, name)?,
+            TzNow(v) => test.now = Some(v),
+            TzSelf(s) => test.self_address = Some(s),
+            TzSource(s) => test.source = Some(s),
+            TzSender(s) => test.sender = Some(s),
+            TzChainId(s) => test.chain_id = Some(s),
+            TzParameter(t) => test.parameter = t,
+        }
+    }
+    match (code, input, output) {
+        (Some(c), Some(i), Some(o)) => {
+            test.code = c;
+            test.input = i;
+            test.output = o;
+            Result::Ok(test)
+        }
+        _ => Result::Err(Diagnostic {
+            location: None,
+            kind: ErrorKind::Syntax,
+            message: String::from("A test needs code, input and output fields"),
+        }),
+    }
+}
+
+fn typecheck_stack(
+    tcenv: &TcEnv,
+    elements: &[(ConcreteType, SomeValue)],
+) -> Result<(ConcreteStack, Vec<MValue>), String> {
+    let mut values = vec![];
+    for (t, v) in elements {
+        values.push(typecheck_data(tcenv, v, t)?);
+    }
+    let types = elements.iter().map(|(t, _)| t.clone()).collect();
+    Result::Ok((StackState::from(types), values))
+}
+
+fn print_stack(values: &[MValue]) -> String {
+    let items: Vec<String> = values.iter().map(print_value).collect();
+    format!("{{ {} }}", items.join(" ; "))
+}
+
+/// Typechecks and runs a test, and returns a description of the mismatch if
+/// the result is not the expected one.
+pub fn run_tzt(test: &TztTest) -> Result<(), String> {
+    let tcenv = TcEnv {
+        self_type: Some(test.parameter.clone()),
+        instructions: &MICHELSON_INSTRUCTIONS,
+        protocol: Protocol::LATEST,
+    };
+    let (mut stack, values) = typecheck_stack(&tcenv, &test.input)?;
+    let code = typecheck_all(&tcenv, &test.code, &mut stack).map_err(|ds| {
+        ds.iter()
+            .map(|d| d.to_string())
+            .collect::<Vec<_>>()
+            .join("\n")
+    })?;
+    let now = match &test.now {
+        Some(v) => match typecheck_data(&tcenv, v, &MWrapped(MTimestamp))? {
+            MValue::VTimestamp(t) => t,
+            _ => return Result::Err(String::from("Field now should be a timestamp")),
+        },
+        None => 0,
+    };
+    let mut ctx = RunContext {
+        amount: test.amount,
+        balance: test.balance,
+        level: test.level,
+        now,
+        self_address: test.self_address.clone(),
+        source: test.source.clone(),
+        sender: test.sender.clone(),
+        chain_id: test.chain_id.clone(),
+        ..RunContext::default()
+    };
+    // The interpreter keeps the top of the stack last.
+    let mut run_stack: Vec<MValue> = values.into_iter().rev().collect();
+    let result = run_block(&code, &mut run_stack, &mut ctx);
+    match (&test.output, result) {
+        (TztOutput::Stack(expected), Result::Ok(())) => {
+            let (expected_stack, expected_values) = typecheck_stack(&tcenv, expected)?;
+            match stack.compare(&expected_stack) {
+                Match | LeftFailed | BothFailed => {}
+                _ => {
+                    return Result::Err(format!(
+                        "Code has the output stack type {} while {} is expected",
+                        stack, expected_stack
+                    ))
+                }
+            }
+            run_stack.reverse();
+            // Values are compared through their printed form, since not all
+            // of them can be compared directly.
+            let (got, want) = (print_stack(&run_stack), print_stack(&expected_values));
+            if got == want {
+                Result::Ok(())
+            } else {
+                Result::Err(format!("Expected the stack {} but got {}", want, got))
+            }
+        }
+        (TztOutput::Failed(expected), Result::Err(RuntimeError::Failed(v))) => {
+            let (got, want) = (print_value(&v), print_value(expected));
+            if got == want {
+                Result::Ok(())
+            } else {
+                Result::Err(format!("Expected a failure with {} but got {}", want, got))
+            }
+        }
+        (TztOutput::Overflow(kind, x, y), Result::Err(RuntimeError::Overflow(k, a, b))) => {
+            if (*kind, x, y) == (k, &a, &b) {
+                Result::Ok(())
+            } else {
+                Result::Err(format!(
+                    "Expected {} {} {} but got {} {} {}",
+                    kind, x, y, k, a, b
+                ))
+            }
+        }
+        (_, Result::Err(e)) => Result::Err(e.to_string()),
+        (_, Result::Ok(())) => {
+            run_stack.reverse();
+            Result::Err(format!(
+                "Expected a failure but the code returned {}",
+                print_stack(&run_stack)
+            ))
+        }
+    }
+}
@@ -0,0 +1,439 @@
+use std::sync::Arc;
+
+use crate::types::ArgValue::*;
+use crate::types::CompositeValue::*;
+use crate::types::CompoundInstruction;
+use crate::types::CompoundInstruction::*;
+use crate::types::ConcreteType;
+use crate::types::LocatedInstruction;
+use crate::types::MType::*;
+use crate::types::MValue;
+use crate::types::MValue::*;
+use crate::types::SomeValue;
+use crate::types::SomeValue::*;
+
+// Traversals of code, whether untyped (`SomeValue`) or typed (`MValue`), of
+// the types it mentions and of the values it pushes, lambdas included. Each
+// method of a visitor walks into its children by default, through the
+// matching `walk_*` function, so that an analysis only overrides the methods
+// of what it looks at, and calls the `walk_*` function to keep going. Walks
+// are recursive, as sources nest at most `MAX_NESTING_DEPTH` levels deep.
+
+/// Values that can be walked into, for the code of lambdas among others.
+pub trait Walk: Sized {
+    fn walk<V: Visitor<Self> + ?Sized>(visitor: &mut V, value: &Self);
+    fn walk_mut<V: VisitorMut<Self> + ?Sized>(visitor: &mut V, value: &mut Self);
+    fn walk_fold<F: Fold<Self> + ?Sized>(folder: &mut F, value: Self) -> Self;
+}
+
+/// Looks at code, types and values, with values of type `T`.
+pub trait Visitor<T: Walk> {
+    fn visit_block(&mut self, block: &[LocatedInstruction<T>]) {
+        walk_block(self, block)
+    }
+
+    fn visit_instruction(&mut self, instruction: &CompoundInstruction<T>) {
+        walk_instruction(self, instruction)
+    }
+
+    fn visit_type(&mut self, t: &ConcreteType) {
+        walk_type(self, t)
+    }
+
+    fn visit_value(&mut self, value: &T) {
+        T::walk(self, value)
+    }
+}
+
+/// Like `Visitor`, but may change what it visits in place.
+pub trait VisitorMut<T: Walk> {
+    fn visit_block_mut(&mut self, block: &mut Vec<LocatedInstruction<T>>) {
+        walk_block_mut(self, block)
+    }
+
+    fn visit_instruction_mut(&mut self, instruction: &mut CompoundInstruction<T>) {
+        walk_instruction_mut(self, instruction)
+    }
+
+    fn visit_type_mut(&mut self, t: &mut ConcreteType) {
+        walk_type_mut(self, t)
+    }
+
+    fn visit_value_mut(&mut self, value: &mut T) {
+        T::walk_mut(self, value)
+    }
+}
+
+/// Rebuilds code, types and values from what each method returns.
+pub trait Fold<T: Walk> {
+    fn fold_block(&mut self, block: Vec<LocatedInstruction<T>>) -> Vec<LocatedInstruction<T>> {
+        walk_block_fold(self, block)
+    }
+
+    fn fold_instruction(&mut self, instruction: CompoundInstruction<T>) -> CompoundInstruction<T> {
+        walk_instruction_fold(self, instruction)
+    }
+
+    fn fold_type(&mut self, t: ConcreteType) -> ConcreteType {
+        walk_type_fold(self, t)
+    }
+
+    fn fold_value(&mut self, value: T) -> T {
+        T::walk_fold(self, value)
+    }
+}
+
+pub fn walk_block<T: Walk, V: Visitor<T> + ?Sized>(
+    visitor: &mut V,
+    block: &[LocatedInstruction<T>],
+) {
+    for located in block {
+        visitor.visit_instruction(&located.instruction);
+    }
+}
+
+pub fn walk_instruction<T: Walk, V: Visitor<T> + ?Sized>(
+    visitor: &mut V,
+    instruction: &CompoundInstruction<T>,
+) {
+    match instruction {
+        IF(t, f) | IF_CONS(t, f) | IF_SOME(t, f) | IF_NONE(t, f) | IF_LEFT(t, f) => {
+            visitor.visit_block(t);
+            visitor.visit_block(f);
+        }
+        SEQ(b) | DIP(_, b) | MAP(b) | ITER(b) | LOOP(b) | LOOP_LEFT(b) => visitor.visit_block(b),
+        LAMBDA_REC(input, output, b) => {
+            visitor.visit_type(input);
+            visitor.visit_type(output);
+            visitor.visit_block(b);
+        }
+        Other(i) => {
+            for arg in &i.args {
+                match arg {
+                    TypeArg(t) => visitor.visit_type(t),
+                    ValueArg(v) => visitor.visit_value(v),
+                }
+            }
+        }
+        PAIR(_) | UNPAIR(_) | DUP(_) | DIG(_) | DUG(_) | DROP(_) | GET(_) | UPDATE(_) | SELF
+        | FAIL | FAILWITH => {}
+    }
+}
+
+pub fn walk_type<T: Walk, V: Visitor<T> + ?Sized>(visitor: &mut V, t: &ConcreteType) {
+    match t {
+        MPair(b) | MOr(b) | MLambda(b) | MMap(b) | MBigMap(b) => {
+            visitor.visit_type(&b.0);
+            visitor.visit_type(&b.1);
+        }
+        MList(t) | MSet(t) | MTicket(t) | MOption(t) | MContract(t) => visitor.visit_type(t),
+        MWrapped(_) => {}
+    }
+}
+
+pub fn walk_block_mut<T: Walk, V: VisitorMut<T> + ?Sized>(
+    visitor: &mut V,
+    block: &mut Vec<LocatedInstruction<T>>,
+) {
+    for located in block {
+        visitor.visit_instruction_mut(&mut located.instruction);
+    }
+}
+
+pub fn walk_instruction_mut<T: Walk, V: VisitorMut<T> + ?Sized>(
+    visitor: &mut V,
+    instruction: &mut CompoundInstruction<T>,
+) {
+    match instruction {
+        IF(t, f) | IF_CONS(t, f) | IF_SOME(t, f) | IF_NONE(t, f) | IF_LEFT(t, f) => {
+            visitor.visit_block_mut(t);
+            visitor.visit_block_mut(f);
+        }
+        SEQ(b) | DIP(_, b) | MAP(b) | ITER(b) | LOOP(b) | LOOP_LEFT(b) => {
+            visitor.visit_block_mut(b)
+        }
+        LAMBDA_REC(input, output, b) => {
+            visitor.visit_type_mut(input);
+            visitor.visit_type_mut(output);
+            visitor.visit_block_mut(b);
+        }
+        Other(i) => {
+            for arg in &mut i.args {
+                match arg {
+                    TypeArg(t) => visitor.visit_type_mut(t),
+                    ValueArg(v) => visitor.visit_value_mut(v),
+                }
+            }
+        }
+        PAIR(_) | UNPAIR(_) | DUP(_) | DIG(_) | DUG(_) | DROP(_) | GET(_) | UPDATE(_) | SELF
+        | FAIL | FAILWITH => {}
+    }
+}
+
+// Arguments shared with other types are copied before they are changed.
+pub fn walk_type_mut<T: Walk, V: VisitorMut<T> + ?Sized>(visitor: &mut V, t: &mut ConcreteType) {
+    match t {
+        MPair(b) | MOr(b) | MLambda(b) | MMap(b) | MBigMap(b) => {
+            let (t1, t2) = Arc::make_mut(b);
+            visitor.visit_type_mut(t1);
+            visitor.visit_type_mut(t2);
+        }
+        MList(t) | MSet(t) | MTicket(t) | MOption(t) | MContract(t) => {
+            visitor.visit_type_mut(Arc::make_mut(t))
+        }
+        MWrapped(_) => {}
+    }
+}
+
+pub fn walk_block_fold<T: Walk, F: Fold<T> + ?Sized>(
+    folder: &mut F,
+    block: Vec<LocatedInstruction<T>>,
+) -> Vec<LocatedInstruction<T>> {
+    block
+        .into_iter()
+        .map(
+            |LocatedInstruction {
+                 location,
+                 instruction,
+             }| LocatedInstruction {
+                location,
+                instruction: folder.fold_instruction(instruction),
+            },
+        )
+        .collect()
+}
+
+pub fn walk_instruction_fold<T: Walk, F: Fold<T> + ?Sized>(
+    folder: &mut F,
+    instruction: CompoundInstruction<T>,
+) -> CompoundInstruction<T> {
+    let mut branches = |t, f| (folder.fold_block(t), folder.fold_block(f));
+    match instruction {
+        IF(t, f) => {
+            let (t, f) = branches(t, f);
+            IF(t, f)
+        }
+        IF_CONS(t, f) => {
+            let (t, f) = branches(t, f);
+            IF_CONS(t, f)
+        }
+        IF_SOME(t, f) => {
+            let (t, f) = branches(t, f);
+            IF_SOME(t, f)
+        }
+        IF_NONE(t, f) => {
+            let (t, f) = branches(t, f);
+            IF_NONE(t, f)
+        }
+        IF_LEFT(t, f) => {
+            let (t, f) = branches(t, f);
+            IF_LEFT(t, f)
+        }
+        SEQ(b) => SEQ(folder.fold_block(b)),
+        DIP(n, b) => DIP(n, folder.fold_block(b)),
+        MAP(b) => MAP(folder.fold_block(b)),
+        ITER(b) => ITER(folder.fold_block(b)),
+        LOOP(b) => LOOP(folder.fold_block(b)),
+        LOOP_LEFT(b) => LOOP_LEFT(folder.fold_block(b)),
+        LAMBDA_REC(input, output, b) => LAMBDA_REC(
+            folder.fold_type(input),
+            folder.fold_type(output),
+            folder.fold_block(b),
+        ),
+        Other(mut i) => {
+            i.args = i
+                .args
+                .into_iter()
+                .map(|arg| match arg {
+                    TypeArg(t) => TypeArg(folder.fold_type(t)),
+                    ValueArg(v) => ValueArg(folder.fold_value(v)),
+                })
+                .collect();
+            Other(i)
+        }
+        i @ (PAIR(_) | UNPAIR(_) | DUP(_) | DIG(_) | DUG(_) | DROP(_) | GET(_) | UPDATE(_)
+        | SELF | FAIL | FAILWITH) => i,
+    }
+}
+
+pub fn walk_type_fold<T: Walk, F: Fold<T> + ?Sized>(
+    folder: &mut F,
+    t: ConcreteType,
+) -> ConcreteType {
+    let mut pair = |b: Arc<(ConcreteType, ConcreteType)>| {
+        let (t1, t2) = Arc::unwrap_or_clone(b);
+        Arc::new((folder.fold_type(t1), folder.fold_type(t2)))
+    };
+    match t {
+        MPair(b) => MPair(pair(b)),
+        MOr(b) => MOr(pair(b)),
+        MLambda(b) => MLambda(pair(b)),
+        MMap(b) => MMap(pair(b)),
+        MBigMap(b) => MBigMap(pair(b)),
+        MList(t) => MList(Arc::new(folder.fold_type(Arc::unwrap_or_clone(t)))),
+        MSet(t) => MSet(Arc::new(folder.fold_type(Arc::unwrap_or_clone(t)))),
+        MTicket(t) => MTicket(Arc::new(folder.fold_type(Arc::unwrap_or_clone(t)))),
+        MOption(t) => MOption(Arc::new(folder.fold_type(Arc::unwrap_or_clone(t)))),
+        MContract(t) => MContract(Arc::new(folder.fold_type(Arc::unwrap_or_clone(t)))),
+        t @ MWrapped(_) => t,
+    }
+}
+
+// Untyped values hold code in lambdas; sequences whose type is not known yet
+// are left alone.
+impl Walk for SomeValue {
+    fn walk<V: Visitor<Self> + ?Sized>(visitor: &mut V, value: &Self) {
+        let Composite(c) = value else {
+            return;
+        };
+        match &**c {
+            CVPair(a, b) => {
+                visitor.visit_value(a);
+                visitor.visit_value(b);
+            }
+            CVSeq(values) => values.iter().for_each(|v| visitor.visit_value(v)),
+            CVLambda(block) => visitor.visit_block(block),
+            CKVList(elts) => {
+                for (k, v) in elts {
+                    visitor.visit_value(k);
+                    visitor.visit_value(v);
+                }
+            }
+            CVLeft(v) | CVRight(v) | CVSome(v) => visitor.visit_value(v),
+            CVNone | CVRaw(_) => {}
+        }
+    }
+
+    fn walk_mut<V: VisitorMut<Self> + ?Sized>(visitor: &mut V, value: &mut Self) {
+        let Composite(c) = value else {
+            return;
+        };
+        match &mut **c {
+            CVPair(a, b) => {
+                visitor.visit_value_mut(a);
+                visitor.visit_value_mut(b);
+            }
+            CVSeq(values) => values.iter_mut().for_each(|v| visitor.visit_value_mut(v)),
+            CVLambda(block) => visitor.visit_block_mut(block),
+            CKVList(elts) => {
+                for (k, v) in elts {
+                    visitor.visit_value_mut(k);
+                    visitor.visit_value_mut(v);
+                }
+            }
+            CVLeft(v) | CVRight(v) | CVSome(v) => visitor.visit_value_mut(v),
+            CVNone | CVRaw(_) => {}
+        }
+    }
+
+    fn walk_fold<F: Fold<Self> + ?Sized>(folder: &mut F, value: Self) -> Self {
+        let c = match value {
+            Composite(c) => *c,
+            atomic => return atomic,
+        };
+        let folded = match c {
+            CVPair(a, b) => CVPair(folder.fold_value(a), folder.fold_value(b)),
+            CVSeq(values) => CVSeq(values.into_iter().map(|v| folder.fold_value(v)).collect()),
+            CVLambda(block) => CVLambda(folder.fold_block(block)),
+            CKVList(elts) => CKVList(
+                elts.into_iter()
+                    .map(|(k, v)| (folder.fold_value(k), folder.fold_value(v)))
+                    .collect(),
+            ),
+            CVLeft(v) => CVLeft(folder.fold_value(v)),
+            CVRight(v) => CVRight(folder.fold_value(v)),
+            CVSome(v) => CVSome(folder.fold_value(v)),
+            CVNone => CVNone,
+            CVRaw(node) => CVRaw(node),
+        };
+        Composite(Box::new(folded))
+    }
+}
+
+// Typed values hold code in lambdas. Keys of maps and elements of sets are
+// ordered, so they are taken out and put back when they may change.
+impl Walk for MValue {
+    fn walk<V: Visitor<Self> + ?Sized>(visitor: &mut V, value: &Self) {
+        match value {
+            VPair(b) => {
+                visitor.visit_value(&b.0);
+                visitor.visit_value(&b.1);
+            }
+            VLeft(v) | VRight(v) | VSome(v) => visitor.visit_value(v),
+            VMap(m) | VBigMap(m) => {
+                for (k, v) in m {
+                    visitor.visit_value(k);
+                    visitor.visit_value(v);
+                }
+            }
+            VList(l) => l.iter().for_each(|v| visitor.visit_value(v)),
+            VSet(s) => s.iter().for_each(|v| visitor.visit_value(v)),
+            VLambda(block) => visitor.visit_block(block),
+            VUnit | VNat(_) | VInt(_) | VBool(_) | VString(_) | VNone | VMutez(_) | VBytes(_)
+            | VTimestamp(_) | VAddress(_) | VKeyHash(_) | VKey(_) | VSignature(_) | VChainId(_) => {
+            }
+        }
+    }
+
+    fn walk_mut<V: VisitorMut<Self> + ?Sized>(visitor: &mut V, value: &mut Self) {
+        match value {
+            VPair(b) => {
+                visitor.visit_value_mut(&mut b.0);
+                visitor.visit_value_mut(&mut b.1);
+            }
+            VLeft(v) | VRight(v) | VSome(v) => visitor.visit_value_mut(v),
+            VMap(m) | VBigMap(m) => {
+                *m = std::mem::take(m)
+                    .into_iter()
+                    .map(|(mut k, mut v)| {
+                        visitor.visit_value_mut(&mut k);
+                        visitor.visit_value_mut(&mut v);
+                        (k, v)
+                    })
+                    .collect();
+            }
+            VList(l) => l.iter_mut().for_each(|v| visitor.visit_value_mut(v)),
+            VSet(s) => {
+                *s = std::mem::take(s)
+                    .into_iter()
+                    .map(|mut v| {
+                        visitor.visit_value_mut(&mut v);
+                        v
+                    })
+                    .collect();
+            }
+            VLambda(block) => visitor.visit_block_mut(block),
+            VUnit | VNat(_) | VInt(_) | VBool(_) | VString(_) | VNone | VMutez(_) | VBytes(_)
+            | VTimestamp(_) | VAddress(_) | VKeyHash(_) | VKey(_) | VSignature(_) | VChainId(_) => {
+            }
+        }
+    }
+
+    fn walk_fold<F: Fold<Self> + ?Sized>(folder: &mut F, value: Self) -> Self {
+        match value {
+            VPair(b) => {
+                let (a, b) = *b;
+                VPair(Box::new((folder.fold_value(a), folder.fold_value(b))))
+            }
+            VLeft(v) => VLeft(Box::new(folder.fold_value(*v))),
+            VRight(v) => VRight(Box::new(folder.fold_value(*v))),
+            VSome(v) => VSome(Box::new(folder.fold_value(*v))),
+            VMap(m) => VMap(
+                m.into_iter()
+                    .map(|(k, v)| (folder.fold_value(k), folder.fold_value(v)))
+                    .collect(),
+            ),
+            VBigMap(m) => VBigMap(
+                m.into_iter()
+                    .map(|(k, v)| (folder.fold_value(k), folder.fold_value(v)))
+                    .collect(),
+            ),
+            VList(l) => VList(l.into_iter().map(|v| folder.fold_value(v)).collect()),
+            VSet(s) => VSet(s.into_iter().map(|v| folder.fold_value(v)).collect()),
+            VLambda(block) => VLambda(folder.fold_block(block)),
+            v @ (VUnit | VNat(_) | VInt(_) | VBool(_) | VString(_) | VNone | VMutez(_)
+            | VBytes(_) | VTimestamp(_) | VAddress(_) | VKeyHash(_) | VKey(_)
+            | VSignature(_) | VChainId(_)) => v,
+        }
+    }
+}
@@ -0,0 +1,336 @@
+use ::typechecker::*;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+// Result of parsing and typechecking one document. All locations are byte
+// offsets into `text`.
+pub struct Analysis {
+    pub text: String,
+    pub diagnostics: Vec<Diagnostic>,
+    pub start_stack: Option<ConcreteStack>,
+    // Every instruction in source order, with the stack after it when the
+    // typechecker got that far.
+    pub instructions: Vec<(usize, Option<ConcreteStack>)>,
+    // Pairs of (location of an EXEC/APPLY, location of the lambda it uses).
+    pub definitions: Vec<(usize, usize)>,
+}
+
+impl Analysis {
+    pub fn new(text: String) -> Self {
+        let mut analysis = Analysis {
+            text,
+            diagnostics: vec![],
+            start_stack: None,
+            instructions: vec![],
+            definitions: vec![],
+        };
+        match try_parse_contract(&analysis.text) {
+            Result::Ok(contract) => {
+                let mut session = TcSession {
+                    stack_trace: Some(vec![]),
+                    ..TcSession::default()
+                };
+                typecheck_contract_in(&contract, &mut session);
+                analysis.diagnostics = session.diagnostics;
+                let stacks: HashMap<_, _> = session
+                    .stack_trace
+                    .unwrap_or_default()
+                    .into_iter()
+                    .collect();
+                analysis.instructions = instructions_in_source_order(&contract.code)
+                    .into_iter()
+                    .map(|(_, i)| (i.location, stacks.get(&i.location).cloned()))
+                    .collect();
+                analysis.start_stack = Some(StackState::from(vec![MPair(Arc::new((
+                    contract.parameter.clone(),
+                    contract.storage.clone(),
+                )))]));
+                let mut origins = Some(vec![None]);
+                track_lambdas(&contract.code, &mut origins, &mut analysis.definitions);
+            }
+            Result::Err(d) => analysis.diagnostics.push(d),
+        }
+        analysis
+    }
+
+    /// Span of the instruction name starting at `location`.
+    pub fn token_end(&self, location: usize) -> usize {
+        location
+            + self.text[location..]
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(self.text.len() - location)
+    }
+
+    /// The instruction whose name is under `offset`, with the stack after it.
+    pub fn instruction_at(&self, offset: usize) -> Option<(usize, &ConcreteStack)> {
+        self.instructions
+            .iter()
+            .find(|(l, _)| *l <= offset && offset < self.token_end(*l))
+            .and_then(|(l, s)| s.as_ref().map(|s| (*l, s)))
+    }
+
+    /// The stack right before `offset`, if it can be known: that is the stack
+    /// after the previous instruction of the same block, or the parameter and
+    /// storage pair at the start of the code.
+    pub fn stack_before(&self, offset: usize) -> Option<&ConcreteStack> {
+        for (l, s) in self.instructions.iter().rev() {
+            if *l >= offset {
+                continue;
+            }
+            match block_change(&self.text[self.token_end(*l).min(offset)..offset]) {
+                (0, 0) => return s.as_ref(),
+                (min, _) if min < 0 => continue,
+                _ => return None,
+            }
+        }
+        match block_change(&self.text[..offset]) {
+            (_, 1) if self.text[..offset].trim_end().ends_with('{') => self.start_stack.as_ref(),
+            _ => None,
+        }
+    }
+
+    pub fn definition_at(&self, offset: usize) -> Option<usize> {
+        self.definitions
+            .iter()
+            .find(|(l, _)| *l <= offset && offset < self.token_end(*l))
+            .map(|(_, d)| *d)
+    }
+}
+
+// The lowest and the final block depth reached in `text`, relative to its
+// start.
+fn block_change(text: &str) -> (i64, i64) {
+    let mut depth = 0;
+    let mut min = 0;
+    let mut in_string = false;
+    for c in text.chars() {
+        match c {
+            '"' => in_string = !in_string,
+            '{' if !in_string => depth += 1,
+            '}' if !in_string => {
+                depth -= 1;
+                min = min.min(depth);
+            }
+            _ => {}
+        }
+    }
+    (min, depth)
+}
+
+// Where each stack slot came from, top first. `Some(l)` marks a lambda
+// created at location `l`; `None` for a slot means its origin is unknown,
+// and `None` for the whole stack means the stack shape itself is unknown.
+type Origins = Option<Vec<Option<usize>>>;
+
+fn merge_origins(a: Origins, b: Origins) -> Origins {
+    match (a, b) {
+        (Some(a), Some(b)) => {
+            if a.len() == b.len() {
+                Some(
+                    a.iter()
+                        .zip(b.iter())
+                        .map(|(x, y)| if x == y { *x } else { None })
+                        .collect(),
+                )
+            } else {
+                None
+            }
+        }
+        (None, b) => b,
+        (a, None) => a,
+    }
+}
+
+fn pop_n(origins: &mut Origins, n: usize) -> Option<Vec<Option<usize>>> {
+    match origins {
+        Some(o) if o.len() >= n => Some(o.drain(0..n).collect()),
+        _ => {
+            *origins = None;
+            None
+        }
+    }
+}
+
+fn push_unknown(origins: &mut Origins, n: usize) {
+    if let Some(o) = origins {
+        for _ in 0..n {
+            o.insert(0, None);
+        }
+    }
+}
+
+fn track_branch(
+    instructions: &Vec<LocatedInstruction<SomeValue>>,
+    mut origins: Origins,
+    pushed: usize,
+    definitions: &mut Vec<(usize, usize)>,
+) -> Origins {
+    push_unknown(&mut origins, pushed);
+    track_lambdas(instructions, &mut origins, definitions);
+    origins
+}
+
+fn track_value_lambdas(args: &[ArgValue<SomeValue>], definitions: &mut Vec<(usize, usize)>) {
+    for arg in args {
+        if let ArgValue::ValueArg(Composite(cv)) = arg {
+            if let CVLambda(instructions) = cv.as_ref() {
+                track_lambdas(instructions, &mut Some(vec![None]), definitions);
+            }
+        }
+    }
+}
+
+// A small abstract interpretation that follows lambdas through stack
+// manipulations, so that an EXEC can be linked to the LAMBDA it runs even when
+// the lambda was hidden with DIP or moved around with DIG/DUG.
+pub fn track_lambdas(
+    instructions: &Vec<LocatedInstruction<SomeValue>>,
+    origins: &mut Origins,
+    definitions: &mut Vec<(usize, usize)>,
+) {
+    for LocatedInstruction {
+        location,
+        instruction,
+    } in instructions
+    {
+        match instruction {
+            Other(i) => {
+                track_value_lambdas(&i.args, definitions);
+                match i.name.as_str() {
+                    "LAMBDA" => {
+                        if let Some(o) = origins {
+                            o.insert(0, Some(*location));
+                        }
+                    }
+                    "PUSH" => match i.args.first() {
+                        Some(ArgValue::TypeArg(MLambda(_))) => {
+                            if let Some(o) = origins {
+                                o.insert(0, Some(*location));
+                            }
+                        }
+                        _ => push_unknown(origins, 1),
+                    },
+                    "SWAP" => match origins {
+                        Some(o) if o.len() >= 2 => o.swap(0, 1),
+                        _ => *origins = None,
+                    },
+                    "EXEC" | "APPLY" => {
+                        if let Some(popped) = pop_n(origins, 2) {
+                            if let Some(d) = popped[1] {
+                                definitions.push((*location, d));
+                            }
+                            if let Some(o) = origins {
+                                o.insert(0, if i.name == "APPLY" { popped[1] } else { None });
+                            }
+                        }
+                    }
+                    name => match MICHELSON_INSTRUCTIONS.get(name).and_then(|v| v.first()) {
+                        Some(def) => {
+                            pop_n(origins, def.input_stack.len());
+                            push_unknown(origins, def.output_stack.len());
+                        }
+                        None => *origins = None,
+                    },
+                }
+            }
+            SEQ(body) => track_lambdas(body, origins, definitions),
+            DIP(n, body) => {
+                if let Some(top) = pop_n(origins, *n) {
+                    track_lambdas(body, origins, definitions);
+                    if let Some(o) = origins {
+                        o.splice(0..0, top);
+                    }
+                } else {
+                    track_lambdas(body, &mut None, definitions);
+                }
+            }
+            DUP(n) => match origins {
+                Some(o) if *n > 0 && o.len() >= *n => o.insert(0, o[n - 1]),
+                _ => *origins = None,
+            },
+            DIG(n) => match origins {
+                Some(o) if o.len() > *n => {
+                    let x = o.remove(*n);
+                    o.insert(0, x);
+                }
+                _ => *origins = None,
+            },
+            DUG(n) => match origins {
+                Some(o) if o.len() > *n => {
+                    let x = o.remove(0);
+                    o.insert(*n, x);
+                }
+                _ => *origins = None,
+            },
+            DROP(n) => {
+                pop_n(origins, *n);
+            }
+            PAIR(n) => {
+                pop_n(origins, *n);
+                push_unknown(origins, 1);
+            }
+            UNPAIR(n) => {
+                pop_n(origins, 1);
+                push_unknown(origins, *n);
+            }
+            GET(_) => {
+                pop_n(origins, 1);
+                push_unknown(origins, 1);
+            }
+            UPDATE(_) => {
+                pop_n(origins, 2);
+                push_unknown(origins, 1);
+            }
+            SELF => push_unknown(origins, 1),
+            FAIL | FAILWITH => *origins = None,
+            IF(t, f) => {
+                pop_n(origins, 1);
+                let a = track_branch(t, origins.clone(), 0, definitions);
+                let b = track_branch(f, origins.clone(), 0, definitions);
+                *origins = merge_origins(a, b);
+            }
+            IF_CONS(c, n) => {
+                pop_n(origins, 1);
+                let a = track_branch(c, origins.clone(), 2, definitions);
+                let b = track_branch(n, origins.clone(), 0, definitions);
+                *origins = merge_origins(a, b);
+            }
+            IF_NONE(n, s) | IF_SOME(s, n) => {
+                pop_n(origins, 1);
+                let a = track_branch(n, origins.clone(), 0, definitions);
+                let b = track_branch(s, origins.clone(), 1, definitions);
+                *origins = merge_origins(a, b);
+            }
+            IF_LEFT(l, r) => {
+                pop_n(origins, 1);
+                let a = track_branch(l, origins.clone(), 1, definitions);
+                let b = track_branch(r, origins.clone(), 1, definitions);
+                *origins = merge_origins(a, b);
+            }
+            LOOP(body) => {
+                pop_n(origins, 1);
+                let mut after = track_branch(body, origins.clone(), 0, definitions);
+                pop_n(&mut after, 1);
+                *origins = merge_origins(origins.clone(), after);
+            }
+            ITER(body) => {
+                pop_n(origins, 1);
+                let after = track_branch(body, origins.clone(), 1, definitions);
+                *origins = merge_origins(origins.clone(), after);
+            }
+            LOOP_LEFT(body) | MAP(body) => {
+                pop_n(origins, 1);
+                let mut after = track_branch(body, origins.clone(), 1, definitions);
+                pop_n(&mut after, 1);
+                *origins = merge_origins(origins.clone(), after);
+                push_unknown(origins, 1);
+            }
+            LAMBDA_REC(_, _, body) => {
+                track_lambdas(body, &mut Some(vec![None, None]), definitions);
+                if let Some(o) = origins {
+                    o.insert(0, Some(*location));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,245 @@
+mod analysis;
+mod rpc;
+
+use serde_json::json;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::io;
+
+use analysis::Analysis;
+use rpc::*;
+
+// Compound instructions are not in `MICHELSON_INSTRUCTIONS`, so they are
+// offered for completion whenever the head of the stack allows them.
+const COMPOUND_INSTRUCTIONS: [&str; 21] = [
+    "DIP",
+    "DUP",
+    "DIG",
+    "DUG",
+    "DROP",
+    "PAIR",
+    "UNPAIR",
+    "GET",
+    "UPDATE",
+    "IF",
+    "IF_CONS",
+    "IF_SOME",
+    "IF_NONE",
+    "IF_LEFT",
+    "LOOP",
+    "LOOP_LEFT",
+    "MAP",
+    "ITER",
+    "LAMBDA_REC",
+    "FAILWITH",
+    "SELF",
+];
+
+fn offset_to_position(text: &str, offset: usize) -> Value {
+    let before = &text[..offset.min(text.len())];
+    let line = before.matches('\n').count();
+    let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let character: usize = before[line_start..].chars().map(|c| c.len_utf16()).sum();
+    json!({ "line": line, "character": character })
+}
+
+fn position_to_offset(text: &str, position: &Value) -> usize {
+    let line = position["line"].as_u64().unwrap_or(0) as usize;
+    let character = position["character"].as_u64().unwrap_or(0) as usize;
+    let mut offset = 0;
+    for (i, l) in text.split_inclusive('\n').enumerate() {
+        if i == line {
+            let mut units = 0;
+            for (j, c) in l.char_indices() {
+                if units >= character || c == '\n' {
+                    return offset + j;
+                }
+                units += c.len_utf16();
+            }
+            return offset + l.len();
+        }
+        offset += l.len();
+    }
+    text.len()
+}
+
+fn range(analysis: &Analysis, location: usize) -> Value {
+    json!({
+        "start": offset_to_position(&analysis.text, location),
+        "end": offset_to_position(&analysis.text, analysis.token_end(location)),
+    })
+}
+
+// Diagnostics that are not about a single instruction, such as an unexpected
+// final stack, have no location and are shown at the start of the document.
+fn diagnostic_range(analysis: &Analysis, location: Option<usize>) -> Value {
+    match location {
+        Some(location) => range(analysis, location),
+        None => json!({
+            "start": offset_to_position(&analysis.text, 0),
+            "end": offset_to_position(&analysis.text, 0),
+        }),
+    }
+}
+
+fn publish_diagnostics(uri: &Value, analysis: &Analysis) -> Value {
+    let diagnostics: Vec<Value> = analysis
+        .diagnostics
+        .iter()
+        .map(|d| {
+            json!({
+                "range": diagnostic_range(analysis, d.location),
+                "severity": 1,
+                "source": "michelson",
+                "message": d.message,
+            })
+        })
+        .collect();
+    notification(
+        "textDocument/publishDiagnostics",
+        json!({ "uri": uri, "diagnostics": diagnostics }),
+    )
+}
+
+fn hover(analysis: &Analysis, offset: usize) -> Value {
+    match analysis.instruction_at(offset) {
+        Some((location, stack)) => json!({
+            "contents": { "kind": "plaintext", "value": stack.to_string() },
+            "range": range(analysis, location),
+        }),
+        None => Value::Null,
+    }
+}
+
+fn definition(uri: &Value, analysis: &Analysis, offset: usize) -> Value {
+    match analysis.definition_at(offset) {
+        Some(location) => json!({ "uri": uri, "range": range(analysis, location) }),
+        None => Value::Null,
+    }
+}
+
+fn compound_applicable(name: &str, stack: &typechecker::ConcreteStack) -> bool {
+    use ::typechecker::*;
+    let head = match stack.get_index(0) {
+        SdOk(Result::Ok(head)) => head,
+        SdOk(Result::Err(_)) => return matches!(name, "LAMBDA_REC" | "SELF"),
+        SdFailed => return true,
+    };
+    match name {
+        "IF" | "LOOP" => matches!(head, MWrapped(MBool)),
+        "IF_CONS" => matches!(head, MList(_)),
+        "IF_SOME" | "IF_NONE" => matches!(head, MOption(_)),
+        "IF_LEFT" | "LOOP_LEFT" => matches!(head, MOr(_)),
+        "MAP" => matches!(head, MList(_) | MOption(_) | MMap(_)),
+        "ITER" => matches!(head, MList(_) | MSet(_) | MMap(_)),
+        "UNPAIR" | "GET" => matches!(head, MPair(_)),
+        "PAIR" | "UPDATE" => matches!(stack.ensure_stack_atleast(2), SdOk(true)),
+        _ => true,
+    }
+}
+
+fn completion(analysis: &Analysis, offset: usize) -> Value {
+    // When the stack at the cursor is unknown every instruction is offered.
+    let stack = analysis
+        .stack_before(offset)
+        .cloned()
+        .unwrap_or(typechecker::StackState::FailedStack);
+    let mut names: Vec<&str> = typechecker::applicable_instructions(&stack);
+    names.extend(
+        COMPOUND_INSTRUCTIONS
+            .iter()
+            .filter(|n| compound_applicable(n, &stack)),
+    );
+    names.sort();
+    names.dedup();
+    Value::Array(
+        names
+            .iter()
+            .map(|n| json!({ "label": n, "kind": 14 }))
+            .collect(),
+    )
+}
+
+fn main() {
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    let mut output = io::stdout();
+    let mut documents: BTreeMap<String, Analysis> = BTreeMap::new();
+    loop {
+        let message = match read_message(&mut input) {
+            Result::Ok(Some(m)) => m,
+            Result::Ok(None) => break,
+            Result::Err(e) => {
+                eprintln!("{}", e);
+                break;
+            }
+        };
+        let method = message["method"].as_str().unwrap_or("");
+        let params = &message["params"];
+        let id = &message["id"];
+        let uri = &params["textDocument"]["uri"];
+        let uri_key = uri.as_str().unwrap_or("").to_string();
+        let offset = documents
+            .get(&uri_key)
+            .map(|a| (a, position_to_offset(&a.text, &params["position"])));
+        let reply = match method {
+            "initialize" => Some(response(
+                id,
+                json!({
+                    "capabilities": {
+                        "textDocumentSync": 1,
+                        "hoverProvider": true,
+                        "definitionProvider": true,
+                        "completionProvider": {},
+                    },
+                    "serverInfo": { "name": "michelson-lsp" },
+                }),
+            )),
+            "textDocument/didOpen" | "textDocument/didChange" => {
+                let text = if method == "textDocument/didOpen" {
+                    params["textDocument"]["text"].as_str()
+                } else {
+                    params["contentChanges"]
+                        .as_array()
+                        .and_then(|c| c.last())
+                        .and_then(|c| c["text"].as_str())
+                };
+                let analysis = Analysis::new(text.unwrap_or("").to_string());
+                let n = publish_diagnostics(uri, &analysis);
+                documents.insert(uri_key.clone(), analysis);
+                Some(n)
+            }
+            "textDocument/didClose" => {
+                documents.remove(&uri_key);
+                None
+            }
+            "textDocument/hover" => Some(response(
+                id,
+                offset.map_or(Value::Null, |(a, o)| hover(a, o)),
+            )),
+            "textDocument/definition" => Some(response(
+                id,
+                offset.map_or(Value::Null, |(a, o)| definition(uri, a, o)),
+            )),
+            "textDocument/completion" => Some(response(
+                id,
+                offset.map_or(json!([]), |(a, o)| completion(a, o)),
+            )),
+            "shutdown" => Some(response(id, Value::Null)),
+            "exit" => break,
+            _ => {
+                if id.is_null() {
+                    None
+                } else {
+                    Some(error_response(id, -32601, "Method not found"))
+                }
+            }
+        };
+        if let Some(r) = reply {
+            if let Result::Err(e) = write_message(&mut output, &r) {
+                eprintln!("{}", e);
+                break;
+            }
+        }
+    }
+}
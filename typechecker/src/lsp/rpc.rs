@@ -0,0 +1,49 @@
+use serde_json::json;
+use serde_json::Value;
+use std::io::BufRead;
+use std::io::Write;
+
+// JSON-RPC messages over stdio, framed with a `Content-Length` header as
+// described by the Language Server Protocol.
+
+pub fn read_message(input: &mut impl BufRead) -> Result<Option<Value>, String> {
+    let mut content_length: Option<usize> = None;
+    loop {
+        let mut line = String::new();
+        let n = input.read_line(&mut line).map_err(|e| e.to_string())?;
+        if n == 0 {
+            return Result::Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some(l) = line.strip_prefix("Content-Length:") {
+            content_length = Some(l.trim().parse().map_err(|_| "Bad Content-Length")?);
+        }
+    }
+    let length = content_length.ok_or("Missing Content-Length header")?;
+    let mut body = vec![0; length];
+    input.read_exact(&mut body).map_err(|e| e.to_string())?;
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(|e| e.to_string())
+}
+
+pub fn write_message(output: &mut impl Write, message: &Value) -> Result<(), String> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body).map_err(|e| e.to_string())?;
+    output.flush().map_err(|e| e.to_string())
+}
+
+pub fn response(id: &Value, result: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+pub fn error_response(id: &Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+pub fn notification(method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "method": method, "params": params })
+}
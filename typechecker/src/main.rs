@@ -1,34 +1,751 @@
+use serde_json::json;
+use serde_json::Value;
+use std::collections::HashMap;
 use std::io;
-use std::time::Instant;
+use std::process::exit;
+use std::sync::Arc;
+use std::time::Duration;
 
+use typechecker::batch::*;
+use typechecker::cache::*;
+use typechecker::elaborate::*;
+use typechecker::encoding::*;
 use typechecker::instructions::*;
+use typechecker::interpreter::*;
+use typechecker::limits::*;
+use typechecker::micheline::*;
 use typechecker::parsers::*;
+use typechecker::printer::*;
+use typechecker::protocol::*;
 use typechecker::typechecker::*;
+use typechecker::types::MAtomic::*;
+use typechecker::types::MType::*;
+use typechecker::types::*;
+use typechecker::tzt::*;
 
-fn main() {
-    // Force the evaluation of instructions.
-    let _ = MICHELSON_INSTRUCTIONS.get("PUSH");
-    match std::io::read_to_string(io::stdin()) {
-        Result::Ok(i) => {
-            let mut start_time = Instant::now();
-            let contract = parse_contract(&i);
-            println!(
-                "Parsed in {} mills..",
-                Instant::now().duration_since(start_time).as_millis()
-            );
-            start_time = Instant::now();
-            match typecheck_contract(contract) {
-                Result::Ok(_) => {
-                    println!(
-                        "Successful typecheck in {} millis..",
-                        Instant::now().duration_since(start_time).as_millis()
-                    );
+const USAGE: &str = "Usage: typechecker <command> [options] [file...]
+
+Commands:
+  typecheck [file...]   Typecheck contracts
+      [--input <stack> --output <stack>]
+                        Typecheck code, such as the body of a lambda, against
+                        declared stacks written like `nat; pair int bool`
+      [--protocol <name>]
+                        Typecheck with the instructions and types of an
+                        earlier protocol, such as `edo` (default: oxford)
+      [--upgrade-to <name>]
+                        Also report what changes for the contracts when
+                        moving to another protocol
+      [--cache <dir>]   Keep verdicts in a directory, and skip the contracts
+                        that were already typechecked there
+  batch <path...>       Typecheck many contracts in parallel: the .tz files
+                        under directories and the scripts of .jsonl files,
+                        one JSON object per line with a `script` field
+      [--threads <n>] [--fuel <n>] [--timeout <seconds>] [--protocol <name>]
+      [--cache <dir>]
+  parse [file...]       Check the syntax of contracts
+  fmt [file]            Print a contract in canonical layout
+  run [file]            Run a contract
+      --parameter <value> --storage <value> [--amount <mutez>] [--level <nat>]
+  convert [file]        Convert a contract between Michelson and Micheline JSON
+      --to json|michelson
+      [--from michelson|json|binary]
+                        Format of the contract (default: Michelson, or JSON
+                        when converting to Michelson); binary contracts are
+                        written in hexadecimal
+  explain <INSTRUCTION> Print the signatures of an instruction
+  test [file...]        Run unit tests written in the .tzt format
+
+Options:
+  --format human|json   Output format of results and diagnostics
+  --stack-trace         Print the stack after every instruction (typecheck,
+                        except with --input and --output)
+
+Files default to the standard input, which can also be given as `-`.
+
+Exit codes: 0 on success, 1 when a contract is rejected or fails, 2 on usage
+errors and 3 when a file cannot be read.";
+
+const EXIT_FAILURE: i32 = 1;
+const EXIT_USAGE: i32 = 2;
+const EXIT_IO: i32 = 3;
+
+#[derive(PartialEq)]
+enum Format {
+    Human,
+    Json,
+}
+
+struct Options {
+    command: String,
+    files: Vec<String>,
+    format: Format,
+    stack_trace: bool,
+    named: HashMap<String, String>,
+}
+
+fn usage_error(message: &str) -> ! {
+    eprintln!("{}\n\n{}", message, USAGE);
+    exit(EXIT_USAGE)
+}
+
+fn parse_options(args: Vec<String>) -> Options {
+    let mut args = args.into_iter();
+    let command = match args.next() {
+        Some(c) if c == "--help" || c == "-h" => {
+            println!("{}", USAGE);
+            exit(0)
+        }
+        Some(c) => c,
+        None => usage_error("Missing command"),
+    };
+    let mut options = Options {
+        command,
+        files: vec![],
+        format: Format::Human,
+        stack_trace: false,
+        named: HashMap::new(),
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--stack-trace" => options.stack_trace = true,
+            "--format" | "--to" | "--from" | "--parameter" | "--storage" | "--amount"
+            | "--level" | "--input" | "--output" | "--protocol" | "--upgrade-to" | "--threads"
+            | "--fuel" | "--timeout" | "--cache" => {
+                let value = match args.next() {
+                    Some(v) => v,
+                    None => usage_error(&format!("Missing value for {}", arg)),
+                };
+                if arg == "--format" {
+                    options.format = match value.as_str() {
+                        "human" => Format::Human,
+                        "json" => Format::Json,
+                        _ => usage_error(&format!("Unknown format {}", value)),
+                    };
+                } else {
+                    options.named.insert(arg[2..].to_string(), value);
+                }
+            }
+            a if a.starts_with("--") => usage_error(&format!("Unknown option {}", a)),
+            _ => options.files.push(arg),
+        }
+    }
+    if options.files.is_empty() {
+        options.files.push("-".to_string());
+    }
+    options
+}
+
+fn read_source(file: &str) -> String {
+    let result = if file == "-" {
+        io::read_to_string(io::stdin())
+    } else {
+        std::fs::read_to_string(file)
+    };
+    match result {
+        Result::Ok(s) => s,
+        Result::Err(e) => {
+            eprintln!("{}: {}", file, e);
+            exit(EXIT_IO)
+        }
+    }
+}
+
+fn single_file(options: &Options) -> (String, String) {
+    if options.files.len() > 1 {
+        usage_error(&format!("{} takes a single file", options.command));
+    }
+    let file = options.files[0].clone();
+    let source = read_source(&file);
+    (file, source)
+}
+
+fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map(|i| i + 1).unwrap_or(0) + 1;
+    (line, column)
+}
+
+fn diagnostic_json(source: &str, d: &Diagnostic) -> Value {
+    match d.location {
+        Some(l) => {
+            let (line, column) = line_column(source, l);
+            json!({
+                "offset": l,
+                "line": line,
+                "column": column,
+                "kind": d.kind.to_string(),
+                "message": d.message,
+            })
+        }
+        None => json!({ "kind": d.kind.to_string(), "message": d.message }),
+    }
+}
+
+fn print_diagnostics(file: &str, source: &str, diagnostics: &[Diagnostic]) {
+    for d in diagnostics {
+        match d.location {
+            Some(l) => {
+                let (line, column) = line_column(source, l);
+                println!("{}:{}:{}: {}", file, line, column, d.message);
+            }
+            None => println!("{}: {}", file, d.message),
+        }
+    }
+}
+
+fn parse_or_report(options: &Options, file: &str, source: &str) -> Contract<SomeValue> {
+    match try_parse_contract(source) {
+        Result::Ok(c) => c,
+        Result::Err(d) => {
+            report_diagnostics(options, file, source, &[d]);
+            exit(EXIT_FAILURE)
+        }
+    }
+}
+
+fn report_diagnostics(options: &Options, file: &str, source: &str, diagnostics: &[Diagnostic]) {
+    if options.format == Format::Json {
+        let ds: Vec<Value> = diagnostics
+            .iter()
+            .map(|d| diagnostic_json(source, d))
+            .collect();
+        println!(
+            "{}",
+            json!({ "file": file, "ok": false, "diagnostics": ds })
+        );
+    } else {
+        print_diagnostics(file, source, diagnostics);
+    }
+}
+
+fn protocol_option(options: &Options, name: &str) -> Option<Protocol> {
+    options
+        .named
+        .get(name)
+        .map(|p| match p.parse::<Protocol>() {
+            Result::Ok(p) => p,
+            Result::Err(e) => usage_error(&e),
+        })
+}
+
+fn cache_option(
+    options: &Options,
+    protocol: Protocol,
+    instructions: &InstructionRegistry,
+) -> Option<Arc<TcCache>> {
+    let dir = options.named.get("cache")?;
+    match TcCache::open(dir, protocol, instructions) {
+        Result::Ok(cache) => Some(Arc::new(cache)),
+        Result::Err(e) => {
+            eprintln!("{}", e);
+            exit(EXIT_IO)
+        }
+    }
+}
+
+fn typecheck_files(options: &Options) -> i32 {
+    let protocol = protocol_option(options, "protocol").unwrap_or(Protocol::LATEST);
+    let upgrade_to = protocol_option(options, "upgrade-to");
+    let instructions = InstructionRegistry::for_protocol(protocol);
+    // Stack traces need every instruction to be typechecked.
+    let cache = if options.stack_trace {
+        None
+    } else {
+        cache_option(options, protocol, &instructions)
+    };
+    let mut code = 0;
+    let mut results = vec![];
+    for file in &options.files {
+        let source = read_source(file);
+        let mut session = TcSession {
+            stack_trace: if options.stack_trace {
+                Some(vec![])
+            } else {
+                None
+            },
+            ..TcSession::default()
+        };
+        let mut trace = vec![];
+        match try_parse_contract(&source) {
+            Result::Ok(contract) => {
+                match &cache {
+                    Some(cache) => typecheck_contract_cached(
+                        cache,
+                        &instructions,
+                        &contract,
+                        &source,
+                        &mut session,
+                    ),
+                    None => {
+                        typecheck_contract_with(protocol, &instructions, &contract, &mut session);
+                    }
                 }
-                Result::Err(s) => {
-                    println!("{}", s);
+                if let Some(to) = upgrade_to {
+                    let report = upgrade_report(&contract, protocol, to);
+                    session.diagnostics.extend(report);
                 }
+                let stacks: HashMap<_, _> = session
+                    .stack_trace
+                    .take()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .collect();
+                if options.stack_trace {
+                    for (depth, i) in instructions_in_source_order(&contract.code) {
+                        let stack = stacks.get(&i.location).map(|s| s.to_string());
+                        trace.push((depth, i.instruction.name().to_string(), stack));
+                    }
+                }
+            }
+            Result::Err(d) => session.diagnostics.push(d),
+        }
+        if !session.diagnostics.is_empty() {
+            code = EXIT_FAILURE;
+        }
+        if options.format == Format::Json {
+            let ds: Vec<Value> = session
+                .diagnostics
+                .iter()
+                .map(|d| diagnostic_json(&source, d))
+                .collect();
+            let mut result = json!({
+                "file": file,
+                "ok": session.diagnostics.is_empty(),
+                "diagnostics": ds,
+            });
+            if options.stack_trace {
+                result["stack_trace"] = trace
+                    .iter()
+                    .map(|(depth, name, stack)| {
+                        json!({ "depth": depth, "instruction": name, "stack": stack })
+                    })
+                    .collect();
+            }
+            results.push(result);
+        } else {
+            for (depth, name, stack) in &trace {
+                println!(
+                    "{}{} :: {}",
+                    "  ".repeat(*depth),
+                    name,
+                    stack.as_deref().unwrap_or("?")
+                );
+            }
+            if session.diagnostics.is_empty() {
+                println!("{}: ok", file);
+            } else {
+                print_diagnostics(file, &source, &session.diagnostics);
             }
         }
-        Result::Err(s) => panic!("{}", s),
     }
+    if options.format == Format::Json {
+        println!("{}", Value::Array(results));
+    }
+    code
+}
+
+fn stack_option(options: &Options, name: &str) -> ConcreteStack {
+    match try_parse_stack(options.named.get(name).map_or("", |s| s.as_str())) {
+        Result::Ok(s) => s,
+        Result::Err(e) => usage_error(&format!("Invalid --{} stack: {}", name, e)),
+    }
+}
+
+fn typecheck_code_files(options: &Options) -> i32 {
+    if options.stack_trace {
+        usage_error("--stack-trace cannot be used with --input and --output");
+    }
+    let input = stack_option(options, "input");
+    let output = stack_option(options, "output");
+    let protocol = protocol_option(options, "protocol").unwrap_or(Protocol::LATEST);
+    let instructions = InstructionRegistry::for_protocol(protocol);
+    let tcenv = TcEnv {
+        self_type: Some(MWrapped(MUnit)),
+        instructions: &instructions,
+        protocol,
+    };
+    let mut code = 0;
+    let mut results = vec![];
+    for file in &options.files {
+        let source = read_source(file);
+        let result = try_parse_code(&source)
+            .map_err(|d| vec![d])
+            .and_then(|c| typecheck_against(&tcenv, &c, &input, &output));
+        let (diagnostics, always_fails) = match result {
+            Result::Ok((_, conformance)) => (vec![], conformance == Conformance::AlwaysFails),
+            Result::Err(ds) => {
+                code = EXIT_FAILURE;
+                (ds, false)
+            }
+        };
+        if options.format == Format::Json {
+            let ds: Vec<Value> = diagnostics
+                .iter()
+                .map(|d| diagnostic_json(&source, d))
+                .collect();
+            results.push(json!({
+                "file": file,
+                "ok": diagnostics.is_empty(),
+                "always_fails": always_fails,
+                "diagnostics": ds,
+            }));
+        } else if !diagnostics.is_empty() {
+            print_diagnostics(file, &source, &diagnostics);
+        } else if always_fails {
+            println!("{}: ok, always fails", file);
+        } else {
+            println!("{}: ok", file);
+        }
+    }
+    if options.format == Format::Json {
+        println!("{}", Value::Array(results));
+    }
+    code
+}
+
+fn batch_files(options: &Options) -> i32 {
+    if options.files == ["-"] {
+        usage_error("batch takes files or directories");
+    }
+    let protocol = protocol_option(options, "protocol").unwrap_or(Protocol::LATEST);
+    let threads = match options.named.get("threads") {
+        Some(_) => numeric_option(options, "threads"),
+        None => std::thread::available_parallelism().map_or(1, |n| n.get()),
+    };
+    let limits = Limits {
+        fuel: match options.named.get("fuel") {
+            Some(_) => numeric_option(options, "fuel"),
+            None => u64::MAX,
+        },
+        timeout: options
+            .named
+            .get("timeout")
+            .map(|_| Duration::from_secs(numeric_option(options, "timeout"))),
+        ..Limits::default()
+    };
+    let scripts = match collect_scripts(&options.files) {
+        Result::Ok(s) => s,
+        Result::Err(e) => {
+            eprintln!("{}", e);
+            return EXIT_IO;
+        }
+    };
+    let cache = cache_option(
+        options,
+        protocol,
+        &InstructionRegistry::for_protocol(protocol),
+    );
+    let results = typecheck_batch(protocol, &limits, cache.as_ref(), threads, &scripts);
+    let summary = summarize(&results);
+    if options.format == Format::Json {
+        let rs: Vec<Value> = scripts
+            .iter()
+            .zip(&results)
+            .map(|(script, r)| {
+                let ds: Vec<Value> = r
+                    .diagnostics
+                    .iter()
+                    .map(|d| diagnostic_json(&script.source, d))
+                    .collect();
+                json!({ "file": r.name, "ok": ds.is_empty(), "diagnostics": ds })
+            })
+            .collect();
+        let by_kind: serde_json::Map<String, Value> = summary
+            .by_kind
+            .iter()
+            .map(|(kind, n)| (kind.to_string(), json!(n)))
+            .collect();
+        println!(
+            "{}",
+            json!({
+                "results": rs,
+                "summary": {
+                    "scripts": summary.scripts,
+                    "rejected": summary.rejected,
+                    "by_kind": by_kind,
+                },
+            })
+        );
+    } else {
+        for (script, r) in scripts.iter().zip(&results) {
+            if r.diagnostics.is_empty() {
+                println!("{}: ok", r.name);
+            } else {
+                print_diagnostics(&r.name, &script.source, &r.diagnostics);
+            }
+        }
+        println!("{} scripts, {} rejected", summary.scripts, summary.rejected);
+        for (kind, n) in &summary.by_kind {
+            println!("  {}: {}", kind, n);
+        }
+    }
+    if summary.rejected > 0 {
+        EXIT_FAILURE
+    } else {
+        0
+    }
+}
+
+fn parse_files(options: &Options) -> i32 {
+    let mut code = 0;
+    for file in &options.files {
+        let source = read_source(file);
+        match try_parse_contract(&source) {
+            Result::Ok(_) => {
+                if options.format == Format::Json {
+                    println!("{}", json!({ "file": file, "ok": true, "diagnostics": [] }));
+                } else {
+                    println!("{}: ok", file);
+                }
+            }
+            Result::Err(d) => {
+                code = EXIT_FAILURE;
+                report_diagnostics(options, file, &source, &[d]);
+            }
+        }
+    }
+    code
+}
+
+fn fmt_file(options: &Options) -> i32 {
+    let (file, source) = single_file(options);
+    let contract = parse_or_report(options, &file, &source);
+    print!("{}", print_contract(&contract));
+    0
+}
+
+// Reads a contract in the format of `--from`: Michelson, Micheline JSON, or
+// the binary encoding of Micheline in hexadecimal. Diagnostics of binary
+// contracts are located in the hexadecimal text.
+fn read_contract(options: &Options, file: &str, source: &str, from: &str) -> Contract<SomeValue> {
+    let hex = source.trim();
+    let prefix = if hex.starts_with("0x") { 2 } else { 0 };
+    let node = match from {
+        "michelson" => return parse_or_report(options, file, source),
+        "json" => serde_json::from_str::<Value>(source)
+            .map_err(|e| e.to_string())
+            .and_then(|v| Node::from_json(&v)),
+        "binary" => hex_to_bytes(&hex[prefix..])
+            .ok_or_else(|| String::from("Invalid hexadecimal"))
+            .and_then(|b| Node::from_binary(&b)),
+        _ => usage_error(&format!("Cannot convert from {}", from)),
+    };
+    let contract = node
+        .map_err(|message| Diagnostic {
+            location: None,
+            kind: ErrorKind::Syntax,
+            message,
+        })
+        .and_then(|node| {
+            check_depth(&node)?;
+            match node.expr {
+                Micheline::Seq(sections) => elaborate_contract(&sections),
+                _ => elaborate_contract(&[node]),
+            }
+        });
+    match contract {
+        Result::Ok(c) => c,
+        Result::Err(mut d) => {
+            d.location = d
+                .location
+                .filter(|_| from == "binary")
+                .map(|l| source.len() - source.trim_start().len() + prefix + 2 * l);
+            report_diagnostics(options, file, source, &[d]);
+            exit(EXIT_FAILURE)
+        }
+    }
+}
+
+fn convert_file(options: &Options) -> i32 {
+    let (file, source) = single_file(options);
+    let to = match options.named.get("to").map(|s| s.as_str()) {
+        Some(t @ ("json" | "michelson")) => t,
+        Some(t) => usage_error(&format!("Cannot convert to {}", t)),
+        None => usage_error("convert requires --to json|michelson"),
+    };
+    let from = match options.named.get("from") {
+        Some(f) => f.as_str(),
+        None if to == "json" => "michelson",
+        None => "json",
+    };
+    let contract = read_contract(options, &file, &source, from);
+    if to == "json" {
+        println!("{}", contract_to_json(&contract));
+    } else {
+        print!("{}", print_contract(&contract));
+    }
+    0
+}
+
+fn parse_value_option(options: &Options, name: &str, t: &ConcreteType) -> Result<MValue, String> {
+    let src = match options.named.get(name) {
+        Some(s) => s,
+        None => usage_error(&format!("run requires --{}", name)),
+    };
+    parse_value(src, t).map_err(|e| format!("Invalid {}: {}", name, e))
+}
+
+fn numeric_option<T: std::str::FromStr + Default>(options: &Options, name: &str) -> T {
+    match options.named.get(name).map(|s| s.parse()) {
+        None => T::default(),
+        Some(Result::Ok(n)) => n,
+        Some(Result::Err(_)) => usage_error(&format!("--{} expects a natural number", name)),
+    }
+}
+
+fn run_file(options: &Options) -> i32 {
+    let (file, source) = single_file(options);
+    let contract = parse_or_report(options, &file, &source);
+    let tcontract = match typecheck_contract_all(contract.clone()) {
+        Result::Ok(c) => c,
+        Result::Err(ds) => {
+            report_diagnostics(options, &file, &source, &ds);
+            return EXIT_FAILURE;
+        }
+    };
+    let mut ctx = RunContext {
+        amount: i64::try_from(numeric_option::<u64>(options, "amount"))
+            .unwrap_or_else(|_| usage_error("--amount exceeds the largest amount of mutez")),
+        level: numeric_option(options, "level"),
+        ..RunContext::default()
+    };
+    let result = parse_value_option(options, "parameter", &contract.parameter)
+        .and_then(|p| parse_value_option(options, "storage", &contract.storage).map(|s| (p, s)))
+        .and_then(|(p, s)| run_contract(&tcontract, p, s, &mut ctx).map_err(|e| e.to_string()));
+    match (result, &options.format) {
+        (Result::Ok(v), Format::Json) => {
+            println!(
+                "{}",
+                json!({ "file": file, "ok": true, "result": v.to_json() })
+            );
+            0
+        }
+        (Result::Ok(v), Format::Human) => {
+            println!("{}", print_value(&v));
+            0
+        }
+        (Result::Err(e), Format::Json) => {
+            println!(
+                "{}",
+                json!({ "file": file, "ok": false, "diagnostics": [{ "message": e }] })
+            );
+            EXIT_FAILURE
+        }
+        (Result::Err(e), Format::Human) => {
+            println!("{}: {}", file, e);
+            EXIT_FAILURE
+        }
+    }
+}
+
+fn test_files(options: &Options) -> i32 {
+    let mut code = 0;
+    let mut results = vec![];
+    for file in &options.files {
+        let source = read_source(file);
+        let result = parse_tzt(&source)
+            .map_err(|d| match d.location {
+                Some(l) => {
+                    let (line, column) = line_column(&source, l);
+                    format!("{}:{}: {}", line, column, d.message)
+                }
+                None => d.message,
+            })
+            .and_then(|t| run_tzt(&t));
+        if result.is_err() {
+            code = EXIT_FAILURE;
+        }
+        match (&options.format, result) {
+            (Format::Json, r) => results.push(json!({
+                "file": file,
+                "ok": r.is_ok(),
+                "message": r.err(),
+            })),
+            (Format::Human, Result::Ok(())) => println!("{}: passed", file),
+            (Format::Human, Result::Err(e)) => println!("{}: failed: {}", file, e),
+        }
+    }
+    if options.format == Format::Json {
+        println!("{}", Value::Array(results));
+    }
+    code
+}
+
+fn explain(options: &Options) -> i32 {
+    let name = match options.files.as_slice() {
+        [name] if name != "-" => name,
+        _ => usage_error("explain takes a single instruction name"),
+    };
+    let defs = match MICHELSON_INSTRUCTIONS.get(name) {
+        Some(defs) => defs,
+        None => {
+            eprintln!(
+                "{} is not in the instruction table; it is either unknown or a \
+                 compound instruction typechecked on its own",
+                name
+            );
+            return EXIT_FAILURE;
+        }
+    };
+    let signatures: Vec<(String, String, String)> = defs
+        .iter()
+        .map(|d| {
+            let show = |items: Vec<String>| {
+                items
+                    .into_iter()
+                    .chain(std::iter::once("'S".to_string()))
+                    .collect::<Vec<_>>()
+                    .join(" : ")
+            };
+            (
+                d.args
+                    .iter()
+                    .map(|a| a.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                show(d.input_stack.iter().map(|a| a.to_string()).collect()),
+                show(d.output_stack.iter().map(|a| a.to_string()).collect()),
+            )
+        })
+        .collect();
+    if options.format == Format::Json {
+        let s: Vec<Value> = signatures
+            .iter()
+            .map(|(a, i, o)| json!({ "args": a, "input": i, "output": o }))
+            .collect();
+        println!("{}", json!({ "instruction": name, "signatures": s }));
+    } else {
+        for (a, i, o) in signatures {
+            let head = if a.is_empty() {
+                name.to_string()
+            } else {
+                format!("{} {}", name, a)
+            };
+            println!("{} :: {} -> {}", head, i, o);
+        }
+    }
+    0
+}
+
+fn main() {
+    let options = parse_options(std::env::args().skip(1).collect());
+    let code = match options.command.as_str() {
+        "typecheck"
+            if options.named.contains_key("input") || options.named.contains_key("output") =>
+        {
+            typecheck_code_files(&options)
+        }
+        "typecheck" => typecheck_files(&options),
+        "batch" => batch_files(&options),
+        "parse" => parse_files(&options),
+        "fmt" => fmt_file(&options),
+        "run" => run_file(&options),
+        "convert" => convert_file(&options),
+        "explain" => explain(&options),
+        "test" => test_files(&options),
+        c => usage_error(&format!("Unknown command {}", c)),
+    };
+    exit(code)
 }
@@ -1,18 +1,21 @@
+use ::typechecker::encoding::hex_to_bytes;
 use ::typechecker::*;
+use std::sync::Arc;
 
 fn typecheck_(
-    instructions: &Vec<CompoundInstruction<SomeValue>>,
+    instructions: &Vec<LocatedInstruction<SomeValue>>,
 ) -> Result<StackState<MAtomic>, String> {
     let mut stack = StackState::new();
     let tcenv: TcEnv = TcEnv {
-        self_type: MWrapped(MUnit),
+        self_type: Some(MWrapped(MUnit)),
+        instructions: &MICHELSON_INSTRUCTIONS,
+        protocol: Protocol::LATEST,
     };
     typecheck(&tcenv, instructions, &mut stack)?;
     Result::Ok(stack)
 }
-fn parse(src: &str) -> Vec<CompoundInstruction<SomeValue>> {
-    let p = InstructionListParser::new();
-    match p.parse(src) {
+fn parse(src: &str) -> Vec<LocatedInstruction<SomeValue>> {
+    match try_parse_code(src) {
         Ok(s) => s,
         Err(e) => panic!("Parse failed {}", e),
     }
@@ -223,13 +226,13 @@ fn test_paring_behavior() {
     assert_eq!(parse_type("nat"), MWrapped(MNat));
     assert_eq!(
         parse_type("pair nat int"),
-        MPair(Box::new((MWrapped(MNat), MWrapped(MInt))))
+        MPair(Arc::new((MWrapped(MNat), MWrapped(MInt))))
     );
     assert_eq!(
         parse_type("(pair nat int string)"),
-        MPair(Box::new((
+        MPair(Arc::new((
             MWrapped(MNat),
-            MPair(Box::new((MWrapped(MInt), MWrapped(MString))))
+            MPair(Arc::new((MWrapped(MInt), MWrapped(MString))))
         )))
     );
     parse("PUSH nat 5");
@@ -250,17 +253,17 @@ fn test_paring_behavior() {
         parse_type(
             r#"(pair (pair (set address) (list address)) (lambda (pair (set address) address) (set address)))"#
         ),
-        MPair(Box::new((
-            MPair(Box::new((
-                MSet(Box::new(MWrapped(MAddress))),
-                MList(Box::new(MWrapped(MAddress)))
+        MPair(Arc::new((
+            MPair(Arc::new((
+                MSet(Arc::new(MWrapped(MAddress))),
+                MList(Arc::new(MWrapped(MAddress)))
             ))),
-            MLambda(Box::new((
-                MPair(Box::new((
-                    MSet(Box::new(MWrapped(MAddress))),
+            MLambda(Arc::new((
+                MPair(Arc::new((
+                    MSet(Arc::new(MWrapped(MAddress))),
                     MWrapped(MAddress)
                 ))),
-                MSet(Box::new(MWrapped(MAddress)))
+                MSet(Arc::new(MWrapped(MAddress)))
             )))
         )))
     );
@@ -605,3 +608,1820 @@ fn test_type_checking_simple() {
         parse_stack("list int; nat")
     );
 }
+
+fn typecheck_all_(instructions: &Vec<LocatedInstruction<SomeValue>>) -> Vec<Diagnostic> {
+    let mut stack = StackState::new();
+    let tcenv: TcEnv = TcEnv {
+        self_type: Some(MWrapped(MUnit)),
+        instructions: &MICHELSON_INSTRUCTIONS,
+        protocol: Protocol::LATEST,
+    };
+    match typecheck_all(&tcenv, instructions, &mut stack) {
+        Ok(_) => vec![],
+        Err(d) => d,
+    }
+}
+
+#[test]
+fn test_error_recovery() {
+    assert_eq!(typecheck_all_(&parse("PUSH nat 5; PUSH nat 5;ADD")), vec![]);
+
+    // Errors after the first one are still reported.
+    assert_eq!(
+        typecheck_all_(&parse(
+            r#"PUSH nat 1; PUSH string "a"; ADD; FOO; PUSH nat "x""#
+        ))
+        .len(),
+        3
+    );
+
+    // Overloads report why each variant failed, with the stack they were
+    // tried on.
+    let diagnostics = typecheck_all_(&parse(r#"PUSH string "a"; PUSH nat 1; ADD"#));
+    let message = &diagnostics[0].message;
+    assert!(
+        message.starts_with(
+            "None of the instruction variants of ADD matched the stack [ nat : string ]: "
+        ),
+        "{}",
+        message
+    );
+    assert!(!message.contains(";\n"), "{}", message);
+    assert!(!message.contains("MWrapped"), "{}", message);
+    assert_eq!(
+        typecheck_all_(&parse("PUSH nat 1; IF_NONE {} {}"))[0].message,
+        "IF_NONE requires an option, but found nat"
+    );
+
+    // Sibling branches are both checked.
+    assert_eq!(
+        typecheck_all_(&parse(
+            r#"PUSH bool True; IF { PUSH nat "a" } { PUSH int "b" }; PUSH nat 1; ADD"#
+        ))
+        .len(),
+        2
+    );
+
+    // Errors that are a consequence of an earlier one are not reported.
+    assert_eq!(
+        typecheck_all_(&parse(
+            r#"PUSH string "a"; PUSH nat 1; ADD; PUSH nat 1; ADD; CAR; IF {} {}"#
+        ))
+        .len(),
+        1
+    );
+
+    // The inputs of a failed instruction are dropped and its outputs left
+    // unknown, so that later errors that do not follow from it are reported.
+    let src = r#"PUSH string "a"; PUSH nat 1; ADD; DROP; PUSH nat 1; PUSH int 2; COMPARE"#;
+    let diagnostics = typecheck_all_(&parse(src));
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[1].location, src.find("COMPARE"));
+    assert_eq!(
+        typecheck_all_(&parse(r#"PUSH string "a"; PUSH nat 1; ADD; DIP { DROP }"#)).len(),
+        2
+    );
+
+    // Bodies are still checked after a failure, with the types on the stack
+    // unknown.
+    let failed = r#"PUSH string "a"; PUSH nat 1; ADD; "#;
+    for body in [
+        r#"IF_LEFT { PUSH nat "x" } {}"#,
+        r#"IF_NONE {} { PUSH nat "x" }"#,
+        r#"IF_CONS { PUSH nat "x" } {}"#,
+        r#"IF {} { PUSH nat "x" }"#,
+        r#"DIP { PUSH nat "x" }"#,
+        r#"ITER { PUSH nat "x" }"#,
+        r#"MAP { PUSH nat "x" }"#,
+        r#"LOOP { PUSH nat "x" }"#,
+    ] {
+        let src = format!("{}{}", failed, body);
+        let diagnostics = typecheck_all_(&parse(&src));
+        assert_eq!(diagnostics.len(), 2, "{}", body);
+        assert_eq!(
+            diagnostics[1].location,
+            src.find("PUSH nat \"x\""),
+            "{}",
+            body
+        );
+    }
+
+    // Errors inside lambda values are reported with their location.
+    let src = r#"PUSH (lambda nat nat) { PUSH string "a"; ADD }; PUSH nat 1; EXEC; PUSH int "b""#;
+    let diagnostics = typecheck_all_(&parse(src));
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0].location, src.find("ADD"));
+}
+
+#[test]
+fn test_contract_diagnostics() {
+    let contract = parse_contract(
+        r#"parameter nat; storage int; code { CDR; NIL operation; PAIR; PUSH nat 1 }"#,
+    );
+    let diagnostics = typecheck_contract_all(contract).err().unwrap();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].location, None);
+
+    // Elements left unknown by an error can be of any type in the final
+    // stack, which is still checked against the storage.
+    let contract = parse_contract(
+        r#"parameter nat; storage int; code { CDR; PUSH nat "a"; ADD; NIL operation; PAIR }"#,
+    );
+    assert_eq!(typecheck_contract_all(contract).err().unwrap().len(), 1);
+    let contract = parse_contract(
+        r#"parameter nat; storage int; code { CDR; PUSH nat "a"; ADD; NIL operation; PAIR; PUSH nat 1 }"#,
+    );
+    assert_eq!(typecheck_contract_all(contract).err().unwrap().len(), 2);
+}
+
+#[test]
+fn test_editor_support() {
+    assert_eq!(
+        parse_type("pair (list nat) (or unit (lambda int string))").to_string(),
+        "pair (list nat) (or unit (lambda int string))"
+    );
+    assert_eq!(parse_stack("nat; bool").to_string(), "[ nat : bool ]");
+
+    let applicable = applicable_instructions(&parse_stack("nat; nat"));
+    assert!(applicable.contains(&"ADD"));
+    assert!(applicable.contains(&"PUSH"));
+    assert!(!applicable.contains(&"CAR"));
+    assert!(!applicable.contains(&"EXEC"));
+
+    // Comments do not shift the locations reported by the parser.
+    let src = "parameter unit; # a comment\nstorage unit; code { CDR; NIL operation; PAIR; ADD }";
+    let contract = try_parse_contract(src).unwrap();
+    let diagnostics = typecheck_contract_all(contract).err().unwrap();
+    assert_eq!(diagnostics[0].location, src.find("ADD"));
+    assert_eq!(
+        try_parse_contract("parameter unit; storage unit; code { CDR; }; ")
+            .err()
+            .unwrap()
+            .location,
+        Some(44)
+    );
+
+    // Compound instructions have locations too.
+    let src = "parameter unit; storage nat; code { CDR; IF {} {}; NIL operation; PAIR }";
+    let diagnostics = typecheck_contract_all(parse_contract(src)).err().unwrap();
+    assert_eq!(diagnostics[0].location, src.find("IF"));
+
+    let src = "parameter unit; storage nat; code { CDR; NIL operation; PAIR }";
+    let contract = parse_contract(src);
+    let mut session = TcSession {
+        stack_trace: Some(vec![]),
+        ..TcSession::default()
+    };
+    typecheck_contract_in(&contract, &mut session);
+    let trace = session.stack_trace.unwrap();
+    assert_eq!(trace.len(), 3);
+    assert_eq!(Some(trace[1].0), src.find("NIL"));
+    assert_eq!(trace[1].1, parse_stack("list operation; nat"));
+}
+
+#[test]
+fn test_printing_and_running() {
+    let src = "parameter (or int int); storage int;
+               code { UNPAIR; IF_LEFT { ADD } { SWAP; SUB }; NIL operation; PAIR }";
+    let contract = parse_contract(src);
+    let printed = print_contract(&contract);
+    assert_eq!(
+        printed,
+        "parameter (or int int) ;
+storage int ;
+code { UNPAIR ;
+       IF_LEFT { ADD }
+               { SWAP ;
+                 SUB } ;
+       NIL operation ;
+       PAIR }
+"
+    );
+    assert_eq!(print_contract(&parse_contract(&printed)), printed);
+    let converted = micheline_to_source(&contract_to_json(&contract)).unwrap();
+    assert_eq!(print_contract(&parse_contract(&converted)), printed);
+
+    let tcontract = typecheck_contract(contract).unwrap();
+    let mut ctx = RunContext::default();
+    let result = run_contract(
+        &tcontract,
+        VRight(Box::new(VInt(5.into()))),
+        VInt(3.into()),
+        &mut ctx,
+    )
+    .unwrap();
+    assert_eq!(print_value(&result), "Pair {} -2");
+
+    let tcontract = typecheck_contract(parse_contract(
+        "parameter nat; storage nat; code { CAR; PUSH nat 0; SUB; ISNAT; IF_NONE { PUSH string \"neg\"; FAILWITH } {}; NIL operation; PAIR }",
+    ))
+    .unwrap();
+    assert_eq!(
+        run_contract(&tcontract, VNat(1u32.into()), VNat(0u32.into()), &mut ctx),
+        Result::Err(RuntimeError::Failed(VString("neg".to_string())))
+    );
+
+    // Blocks nest in blocks.
+    let contract = parse_contract(
+        "parameter int; storage int; code { { UNPAIR } ; { {} ; ADD } ; NIL operation ; PAIR }",
+    );
+    let printed = print_contract(&contract);
+    assert!(printed.starts_with("parameter int ;\nstorage int ;\ncode { { UNPAIR } ;\n"));
+    assert_eq!(print_contract(&parse_contract(&printed)), printed);
+    let converted = micheline_to_source(&contract_to_json(&contract)).unwrap();
+    assert_eq!(print_contract(&parse_contract(&converted)), printed);
+    let tcontract = typecheck_contract(contract).unwrap();
+    let result = run_contract(&tcontract, VInt(2.into()), VInt(3.into()), &mut ctx).unwrap();
+    assert_eq!(print_value(&result), "Pair {} 5");
+
+    // Mutez go up to 2^63 - 1, past which arithmetic overflows.
+    let tcontract = typecheck_contract(parse_contract(
+        "parameter mutez; storage mutez; code { UNPAIR; ADD; NIL operation; PAIR }",
+    ))
+    .unwrap();
+    let big = VMutez(MAX_MUTEZ - 1);
+    let result = run_contract(&tcontract, big.clone(), VMutez(1), &mut ctx).unwrap();
+    assert_eq!(print_value(&result), "Pair {} 9223372036854775807");
+    assert_eq!(
+        run_contract(&tcontract, big, VMutez(2), &mut ctx),
+        Result::Err(RuntimeError::Overflow(
+            OverflowKind::MutezOverflow,
+            (MAX_MUTEZ - 1).into(),
+            2.into()
+        ))
+    );
+    assert!(parse_value("9223372036854775807", &parse_type("mutez")).is_ok());
+    assert!(parse_value("-1", &parse_type("mutez")).is_err());
+    assert!(parse_value("9223372036854775808", &parse_type("mutez")).is_err());
+}
+
+#[test]
+fn test_typecheck_against() {
+    let tcenv: TcEnv = TcEnv {
+        self_type: Some(MWrapped(MUnit)),
+        instructions: &MICHELSON_INSTRUCTIONS,
+        protocol: Protocol::LATEST,
+    };
+    let check = |src: &str, input: &str, output: &str| {
+        typecheck_against(
+            &tcenv,
+            &try_parse_code(src).unwrap(),
+            &try_parse_stack(input).unwrap(),
+            &try_parse_stack(output).unwrap(),
+        )
+        .map(|(_, c)| c)
+    };
+    assert_eq!(
+        check("{ UNPAIR; ADD }", "pair nat nat", "nat"),
+        Result::Ok(Conformance::Conforms)
+    );
+    assert_eq!(
+        check("UNPAIR; ADD", "pair nat nat; bool", "nat; bool"),
+        Result::Ok(Conformance::Conforms)
+    );
+    assert_eq!(
+        check("{ DROP; PUSH string \"no\"; FAILWITH }", "nat", "int"),
+        Result::Ok(Conformance::AlwaysFails)
+    );
+    assert_eq!(
+        check("{ IF { PUSH nat 1 } { UNIT; FAILWITH } }", "bool", "nat"),
+        Result::Ok(Conformance::Conforms)
+    );
+    assert!(check("{ UNPAIR; ADD }", "pair nat nat", "int").is_err());
+    assert!(check("{ ADD }", "nat", "nat").is_err());
+    assert!(try_parse_stack("nat; (").is_err());
+    assert_eq!(try_parse_stack("").unwrap(), StackState::new());
+}
+
+#[test]
+fn test_tzt() {
+    let run = |src: &str| run_tzt(&parse_tzt(src).unwrap());
+    assert_eq!(
+        run("code { ADD } ; input { Stack_elt nat 5 ; Stack_elt nat 3 } ; output { Stack_elt nat 8 }"),
+        Result::Ok(())
+    );
+    assert_eq!(
+        run("amount 10 ; code { DROP ; AMOUNT } ; input { Stack_elt unit Unit } ; output { Stack_elt mutez 10 } ;"),
+        Result::Ok(())
+    );
+    assert_eq!(
+        run(
+            "balance 20 ; now \"1970-01-01T00:01:00Z\" ; chain_id \"NetXdQprcVkpaWU\" ; \
+             sender \"tz1KqTpEZ7Yob7QbPE4Hy4Wo8fHG8LhKxZSx\" ; \
+             code { DROP ; SENDER ; CHAIN_ID ; NOW ; BALANCE } ; input { Stack_elt unit Unit } ; \
+             output { Stack_elt mutez 20 ; Stack_elt timestamp 60 ; \
+             Stack_elt chain_id \"NetXdQprcVkpaWU\" ; \
+             Stack_elt address \"tz1KqTpEZ7Yob7QbPE4Hy4Wo8fHG8LhKxZSx\" }"
+        ),
+        Result::Ok(())
+    );
+    assert!(
+        run("code { DROP ; SOURCE } ; input { Stack_elt unit Unit } ; output { Stack_elt address \"tz1KqTpEZ7Yob7QbPE4Hy4Wo8fHG8LhKxZSx\" }")
+            .is_err()
+    );
+    assert_eq!(
+        run("code { MAP { PUSH int 1 ; ADD } } ; input { Stack_elt (list int) { 1 ; 2 } } ; output { Stack_elt (list int) { 2 ; 3 } }"),
+        Result::Ok(())
+    );
+    assert_eq!(
+        run("code { PUSH string \"boom\" ; FAILWITH } ; input {} ; output (Failed \"boom\")"),
+        Result::Ok(())
+    );
+    assert_eq!(
+        run("code { SUB_MUTEZ } ; input { Stack_elt mutez 1 ; Stack_elt mutez 2 } ; output { Stack_elt (option mutez) None }"),
+        Result::Ok(())
+    );
+    assert_eq!(
+        run("code { MUL } ; input { Stack_elt mutez 4611686018427387904 ; Stack_elt nat 2 } ; output (MutezOverflow 4611686018427387904 2)"),
+        Result::Ok(())
+    );
+    assert_eq!(
+        run("code { PUSH bool True ; LOOP { PUSH int 1 ; ADD ; DUP ; PUSH int 5 ; COMPARE ; GT } } ; input { Stack_elt int 0 } ; output { Stack_elt int 5 }"),
+        Result::Ok(())
+    );
+    assert!(run(
+        "code { MUL } ; input { Stack_elt mutez 2147483647 ; Stack_elt nat 3 } ; output (MutezOverflow 2147483647 2)"
+    )
+    .is_err());
+    assert!(run(
+        "code { MUL } ; input { Stack_elt mutez 2147483647 ; Stack_elt nat 3 } ; output (GeneralOverflow 2147483647 3)"
+    )
+    .is_err());
+    assert!(run(
+        "code { ADD } ; input { Stack_elt nat 5 ; Stack_elt nat 3 } ; output { Stack_elt nat 9 }"
+    )
+    .is_err());
+    assert!(run(
+        "code { ADD } ; input { Stack_elt nat 5 ; Stack_elt nat 3 } ; output { Stack_elt int 8 }"
+    )
+    .is_err());
+    assert!(run(
+        "code { ADD } ; input { Stack_elt nat 5 ; Stack_elt nat 3 } ; output (Failed Unit)"
+    )
+    .is_err());
+    assert!(run("code { ADD } ; input { Stack_elt nat 5 } ; output { Stack_elt nat 5 }").is_err());
+
+    assert!(parse_tzt("code {} ; input {}").is_err());
+    assert_eq!(
+        parse_tzt("code {} ; input {} ; code {} ; output {}")
+            .err()
+            .unwrap()
+            .location,
+        Some(21)
+    );
+    assert!(parse_tzt("amount 99999999999999999999 ; code {} ; input {} ; output {}").is_err());
+
+    // Numbers are unbounded.
+    assert_eq!(
+        run("code { MUL ; PUSH int -1 ; ADD } ; input { Stack_elt nat 99999999999999999999 ; Stack_elt nat 10 } ; output { Stack_elt int 999999999999999999989 }"),
+        Result::Ok(())
+    );
+    for (src, ty) in [
+        ("123456789012345678901234567890", "nat"),
+        ("-123456789012345678901234567890", "int"),
+        ("Pair 18446744073709551616 -1", "pair nat int"),
+    ] {
+        let value = parse_value(src, &parse_type(ty)).unwrap();
+        assert_eq!(print_value(&value), src);
+    }
+    assert!(parse_value("-1", &parse_type("nat")).is_err());
+}
+
+#[test]
+fn test_run_limits() {
+    let run = |src: &str, input: MValue, ctx: &mut RunContext| {
+        let tcenv: TcEnv = TcEnv {
+            self_type: Some(MWrapped(MUnit)),
+            instructions: &MICHELSON_INSTRUCTIONS,
+            protocol: Protocol::LATEST,
+        };
+        let mut stack = parse_stack("nat");
+        let code = typecheck_all(&tcenv, &parse(src), &mut stack).unwrap();
+        let mut run_stack = vec![input];
+        run_block(&code, &mut run_stack, ctx).map(|_| run_stack)
+    };
+    assert_eq!(
+        run(
+            "PUSH bool True; LOOP { PUSH bool True }",
+            VNat(1u32.into()),
+            &mut RunContext {
+                fuel: 10_000,
+                ..RunContext::default()
+            }
+        ),
+        Result::Err(RuntimeError::Exhausted("fuel".to_string()))
+    );
+    assert_eq!(
+        run(
+            "LEFT unit; LOOP_LEFT { LEFT unit }",
+            VNat(1u32.into()),
+            &mut RunContext {
+                fuel: 10_000,
+                ..RunContext::default()
+            }
+        ),
+        Result::Err(RuntimeError::Exhausted("fuel".to_string()))
+    );
+    assert_eq!(
+        run(
+            "LAMBDA_REC nat nat { EXEC }; SWAP; EXEC",
+            VNat(1u32.into()),
+            &mut RunContext::default()
+        ),
+        Result::Err(RuntimeError::Exhausted("depth".to_string()))
+    );
+    let mut ctx = RunContext {
+        fuel: 3,
+        ..RunContext::default()
+    };
+    assert_eq!(
+        run("PUSH nat 1; ADD", VNat(1u32.into()), &mut ctx),
+        Result::Ok(vec![VNat(2u32.into())])
+    );
+    assert_eq!(ctx.fuel, 0);
+    assert_eq!(
+        run("PUSH nat 1; ADD", VNat(1u32.into()), &mut ctx),
+        Result::Err(RuntimeError::Exhausted("fuel".to_string()))
+    );
+}
+
+#[test]
+fn test_compare_ordering() {
+    let tcenv: TcEnv = TcEnv {
+        self_type: Some(MWrapped(MUnit)),
+        instructions: &MICHELSON_INSTRUCTIONS,
+        protocol: Protocol::LATEST,
+    };
+    let sorted = |ty: &str, src: &str| {
+        let value = try_parse_some_value(src).unwrap();
+        match typecheck_data(&tcenv, &value, &parse_type(&format!("list ({})", ty))).unwrap() {
+            VList(mut l) => {
+                l.sort();
+                l.dedup();
+                l.iter().map(print_value).collect::<Vec<_>>().join(" ; ")
+            }
+            _ => panic!("Expecting a list"),
+        }
+    };
+    assert_eq!(
+        sorted("pair nat int", "{ Pair 2 0 ; Pair 1 5 ; Pair 1 -3 }"),
+        "Pair 1 -3 ; Pair 1 5 ; Pair 2 0"
+    );
+    assert_eq!(
+        sorted("or nat string", "{ Right \"a\" ; Left 7 ; Left 3 }"),
+        "Left 3 ; Left 7 ; Right \"a\""
+    );
+    assert_eq!(
+        sorted("option mutez", "{ Some 10 ; Some 2 }"),
+        "Some 2 ; Some 10"
+    );
+    assert!(VNone < VSome(Box::new(VMutez(0))));
+    assert_eq!(sorted("unit", "{ Unit ; Unit }"), "Unit");
+    assert_eq!(sorted("bool", "{ True ; False }"), "False ; True");
+    assert_eq!(
+        sorted("bytes", "{ 0x02 ; 0x0100 ; 0x01 }"),
+        "0x01 ; 0x0100 ; 0x02"
+    );
+    assert_eq!(
+        sorted("timestamp", "{ \"2020-01-01T00:00:00Z\" ; 0 ; 1577836799 }"),
+        "\"1970-01-01T00:00:00Z\" ; \"2019-12-31T23:59:59Z\" ; \"2020-01-01T00:00:00Z\""
+    );
+    // Implicit accounts come before contracts, then tz1 before tz2, then the
+    // hash; entrypoints come last.
+    assert_eq!(
+        sorted(
+            "address",
+            "{ \"KT18amZmM5W7qDWVt2pH6uj7sCEd3kbzLrHT\" ; \"tz28KEfLTo3wg2wGyJZMjC1MaDA1q68s6tz5\" ; \
+               \"tz1iydgEAWLmDA7qqDXwPsXEJRXWa9LZHgXV\" ; \"tz1Ke2h7sDdakHJQh8WX4Z372du1KChsksyU%b\" ; \
+               \"tz1Ke2h7sDdakHJQh8WX4Z372du1KChsksyU\" }"
+        ),
+        "\"tz1Ke2h7sDdakHJQh8WX4Z372du1KChsksyU\" ; \"tz1Ke2h7sDdakHJQh8WX4Z372du1KChsksyU%b\" ; \
+         \"tz1iydgEAWLmDA7qqDXwPsXEJRXWa9LZHgXV\" ; \"tz28KEfLTo3wg2wGyJZMjC1MaDA1q68s6tz5\" ; \
+         \"KT18amZmM5W7qDWVt2pH6uj7sCEd3kbzLrHT\""
+    );
+    assert_eq!(
+        sorted(
+            "key",
+            "{ \"sppk7VR9W7A2YABXrY6bVTVtjoLQKTkYM2dsBq3gnowG16had1yQp5s\" ; \
+               \"edpkvay7XPqDorEGhuAhpwePXaXqxcxcVeEGirhBbzs9CAApvpcB7N\" }"
+        ),
+        "\"edpkvay7XPqDorEGhuAhpwePXaXqxcxcVeEGirhBbzs9CAApvpcB7N\" ; \
+         \"sppk7VR9W7A2YABXrY6bVTVtjoLQKTkYM2dsBq3gnowG16had1yQp5s\""
+    );
+    assert!(typecheck_data(
+        &tcenv,
+        &try_parse_some_value("\"tz1Ke2h7sDdakHJQh8WX4Z372du1KChsksyV\"").unwrap(),
+        &MWrapped(MAddress)
+    )
+    .is_err());
+
+    // A lambda or contract leaving anything but one element is rejected.
+    for src in ["{ DROP }", "{ DUP }"] {
+        let value = try_parse_some_value(src).unwrap();
+        assert!(typecheck_data(&tcenv, &value, &parse_type("lambda nat nat")).is_err());
+    }
+    assert!(typecheck_contract(parse_contract(
+        "parameter unit; storage unit; code { CDR; NIL operation; PAIR; UNIT }"
+    ))
+    .is_err());
+
+    assert_eq!(
+        run_tzt(
+            &parse_tzt(
+                "code { COMPARE } ; input { Stack_elt (pair nat nat) (Pair 1 9) ; Stack_elt (pair nat nat) (Pair 2 0) } ; output { Stack_elt int -1 }"
+            )
+            .unwrap()
+        ),
+        Result::Ok(())
+    );
+}
+
+#[test]
+fn test_value_literals() {
+    let tcenv: TcEnv = TcEnv {
+        self_type: Some(MWrapped(MUnit)),
+        instructions: &MICHELSON_INSTRUCTIONS,
+        protocol: Protocol::LATEST,
+    };
+    let check = |ty: &str, src: &str| {
+        let value = try_parse_some_value(src).unwrap();
+        typecheck_data(&tcenv, &value, &parse_type(ty)).map(|v| print_value(&v))
+    };
+    assert_eq!(
+        check("option nat", "None"),
+        Result::Ok(String::from("None"))
+    );
+    assert_eq!(
+        check("(option (option nat))", "Some None"),
+        Result::Ok(String::from("Some None"))
+    );
+    assert_eq!(check("set nat", "{}"), Result::Ok(String::from("{}")));
+    assert_eq!(
+        check("map nat string", "{}"),
+        Result::Ok(String::from("{}"))
+    );
+    assert_eq!(
+        check("big_map nat string", "{}"),
+        Result::Ok(String::from("{}"))
+    );
+    assert_eq!(
+        check("big_map nat string", "{ Elt 1 \"a\" ; Elt 2 \"b\" }"),
+        Result::Ok(String::from("{ Elt 1 \"a\" ; Elt 2 \"b\" }"))
+    );
+
+    // Right combs, written out, with the `Pair` sugar or as sequences.
+    let comb = Result::Ok(String::from("Pair 1 (Pair 2 3)"));
+    assert_eq!(check("pair nat (pair nat nat)", "Pair 1 (Pair 2 3)"), comb);
+    assert_eq!(check("pair nat (pair nat nat)", "Pair 1 2 3"), comb);
+    assert_eq!(check("pair nat (pair nat nat)", "{ 1 ; 2 ; 3 }"), comb);
+    assert_eq!(check("pair nat (pair nat nat)", "Pair 1 { 2 ; 3 }"), comb);
+    assert_eq!(
+        check(
+            "list (pair nat (pair (option nat) nat))",
+            "{ Pair 1 None 2 ; { 3 ; Some 4 ; 5 } }"
+        ),
+        Result::Ok(String::from(
+            "{ Pair 1 (Pair None 2) ; Pair 3 (Pair (Some 4) 5) }"
+        ))
+    );
+    assert!(check("pair nat nat", "{ 1 }").is_err());
+    assert!(check("pair nat nat", "{ 1 ; 2 ; 3 }").is_err());
+
+    // Elements and keys must be sorted and unique.
+    assert!(check("set nat", "{ 2 ; 1 }").is_err());
+    assert!(check("set nat", "{ 1 ; 1 }").is_err());
+    assert!(check("map nat unit", "{ Elt 2 Unit ; Elt 1 Unit }").is_err());
+    assert!(check("big_map nat unit", "{ Elt 1 Unit ; Elt 1 Unit }").is_err());
+    assert!(check("set (list nat)", "{}").is_err());
+}
+
+#[test]
+fn test_parse_value() {
+    let round_trip = |ty: &str, src: &str| {
+        let t = parse_type(ty);
+        let value = parse_value(src, &t).unwrap();
+        assert_eq!(
+            parse_value(&value.to_string(), &t),
+            Result::Ok(value.clone())
+        );
+        value.to_string()
+    };
+    assert_eq!(round_trip("int", "-5"), "-5");
+    assert_eq!(round_trip("option int", "Some -5"), "Some -5");
+    assert_eq!(
+        round_trip("pair nat (pair string bool)", "{ 1 ; \"a\" ; True }"),
+        "Pair 1 (Pair \"a\" True)"
+    );
+    assert_eq!(
+        round_trip("or (option nat) unit", "Left (Some 3)"),
+        "Left (Some 3)"
+    );
+    assert_eq!(
+        round_trip(
+            "map string (list bytes)",
+            "{ Elt \"a\" { 0x00 } ; Elt \"b\" {} }"
+        ),
+        "{ Elt \"a\" { 0x00 } ; Elt \"b\" {} }"
+    );
+    assert_eq!(
+        round_trip("timestamp", "0 # the epoch"),
+        "\"1970-01-01T00:00:00Z\""
+    );
+    assert_eq!(
+        round_trip(
+            "address",
+            "\"KT18amZmM5W7qDWVt2pH6uj7sCEd3kbzLrHT%default\""
+        ),
+        "\"KT18amZmM5W7qDWVt2pH6uj7sCEd3kbzLrHT%default\""
+    );
+    assert_eq!(
+        round_trip("lambda nat nat", "{ PUSH nat 1 ; ADD }"),
+        "{ PUSH nat 1 ;\n  ADD }"
+    );
+    // Sequences hold what the type says, whatever their first item is.
+    assert_eq!(
+        round_trip("list (list nat)", "{ { 1 } ; {} }"),
+        "{ { 1 } ; {} }"
+    );
+    round_trip("lambda unit unit", "{ { DROP } ; UNIT }");
+    let tcenv = TcEnv {
+        self_type: Some(MWrapped(MUnit)),
+        instructions: &MICHELSON_INSTRUCTIONS,
+        protocol: Protocol::LATEST,
+    };
+    let mut stack = parse_stack("");
+    assert!(typecheck(
+        &tcenv,
+        &parse("PUSH (lambda unit unit) { { DROP } ; UNIT } ; DROP"),
+        &mut stack
+    )
+    .is_ok());
+    // Without a type, a sequence is kept as it was read, and read once the
+    // typechecker gives it one.
+    let untyped = try_parse_some_value("{ { DROP } ; UNIT }").unwrap();
+    assert_eq!(print_value(&untyped), "{ { DROP } ; UNIT }");
+    assert!(typecheck_data(&tcenv, &untyped, &parse_type("lambda unit unit")).is_ok());
+    assert!(typecheck_data(&tcenv, &untyped, &parse_type("list nat")).is_err());
+    assert_eq!(
+        typecheck_data(&tcenv, &untyped, &parse_type("nat")).unwrap_err(),
+        "Expecting a value of type nat but found { { DROP } ; UNIT }"
+    );
+
+    assert_eq!(
+        parse_value("Pair 1", &parse_type("nat"))
+            .unwrap_err()
+            .location,
+        Some(0)
+    );
+    assert_eq!(
+        parse_value("-1", &parse_type("nat")).unwrap_err().location,
+        None
+    );
+    // Values are not part of a contract, so their lambdas cannot use SELF.
+    assert!(parse_value(
+        "{ DROP ; SELF }",
+        &parse_type("lambda unit (contract unit)")
+    )
+    .is_err());
+}
+
+#[test]
+fn test_attribute_constraints() {
+    let tc = |src: &str, input: &str| {
+        let mut stack = parse_stack(input);
+        let tcenv: TcEnv = TcEnv {
+            self_type: Some(MWrapped(MUnit)),
+            instructions: &MICHELSON_INSTRUCTIONS,
+            protocol: Protocol::LATEST,
+        };
+        typecheck(&tcenv, &parse(src), &mut stack)
+    };
+    assert!(tc("COMPARE", "nat;nat").is_ok());
+    assert!(tc("COMPARE", "pair nat string;pair nat string").is_ok());
+    assert!(tc("COMPARE", "lambda nat nat;lambda nat nat")
+        .unwrap_err()
+        .contains("Type lambda nat nat is not comparable"));
+    assert!(tc("COMPARE", "list nat;list nat").is_err());
+    assert!(tc("PACK", "pair nat (lambda unit unit)").is_ok());
+    assert!(tc("PACK", "operation").is_err());
+    assert!(tc("PACK", "big_map nat nat").is_err());
+    assert!(tc("PACK", "ticket nat").is_err());
+    assert!(tc("UNPACK (contract unit)", "bytes").is_ok());
+    assert!(tc("UNPACK operation", "bytes").is_err());
+    assert!(tc("PUSH operation 5", "").is_err());
+    assert!(tc("EMPTY_BIG_MAP nat (big_map nat nat)", "").is_err());
+    assert!(tc("EMPTY_BIG_MAP (list nat) nat", "").is_err());
+    assert!(tc("CONTRACT operation", "address").is_err());
+    assert!(tc("TRANSFER_TOKENS", "ticket nat;mutez;contract (ticket nat)").is_ok());
+    assert!(tc("TRANSFER_TOKENS", "operation;mutez;contract operation").is_err());
+    assert!(tc("APPLY", "operation;lambda (pair operation nat) nat").is_err());
+    assert!(tc("EMPTY_SET nat", "").is_ok());
+    assert!(tc("EMPTY_SET (list nat)", "").is_err());
+    assert!(tc("EMPTY_MAP string (big_map nat nat)", "").is_ok());
+    assert!(tc("EMPTY_MAP (set nat) nat", "").is_err());
+    assert!(tc("FAILWITH", "pair nat string").is_ok());
+    assert!(tc("FAILWITH", "big_map nat nat")
+        .unwrap_err()
+        .contains("Type big_map nat nat is not packable"));
+    assert!(tc("FAILWITH", "operation").is_err());
+    assert!(tc("DUP", "operation").is_ok());
+    assert!(tc("DUP 2", "nat;ticket nat")
+        .unwrap_err()
+        .contains("Type ticket nat is not duplicable"));
+}
+
+#[test]
+fn test_repeated_type_variables() {
+    let tc = |src: &str, input: &str| {
+        let mut stack = parse_stack(input);
+        let tcenv: TcEnv = TcEnv {
+            self_type: Some(MWrapped(MUnit)),
+            instructions: &MICHELSON_INSTRUCTIONS,
+            protocol: Protocol::LATEST,
+        };
+        typecheck(&tcenv, &parse(src), &mut stack).map(|_| stack)
+    };
+    assert!(tc("ASSERT_CMPEQ", "nat;nat").is_ok());
+    assert!(tc("ASSERT_CMPLE", "pair nat int;pair nat int").is_ok());
+    let e = tc("ASSERT_CMPEQ", "nat;int").unwrap_err();
+    assert!(e.contains("At stack element 2: Type variable 'a is bound to nat but int was found"));
+    assert!(tc("ASSERT_CMPLE", "pair nat int;pair nat nat").is_err());
+    assert!(tc("EXEC", "int;lambda nat nat").is_err());
+    assert!(tc("CONS", "option nat;list (option int)").is_err());
+    assert_eq!(
+        tc("CONS", "option nat;list (option nat)").unwrap(),
+        parse_stack("list (option nat)")
+    );
+}
+
+#[test]
+fn test_attribute_matrix() {
+    // Columns: comparable, passable, pushable, storable, packable, big_map
+    // value, duplicable.
+    let attributes = [
+        Comparable,
+        Passable,
+        Pushable,
+        Storable,
+        Packable,
+        BigmapValue,
+        Duplicable,
+    ];
+    let matrix = [
+        ("unit", "CPUSKBD"),
+        ("bool", "CPUSKBD"),
+        ("int", "CPUSKBD"),
+        ("nat", "CPUSKBD"),
+        ("string", "CPUSKBD"),
+        ("bytes", "CPUSKBD"),
+        ("mutez", "CPUSKBD"),
+        ("timestamp", "CPUSKBD"),
+        ("address", "CPUSKBD"),
+        ("key", "CPUSKBD"),
+        ("key_hash", "CPUSKBD"),
+        ("signature", "CPUSKBD"),
+        ("chain_id", "CPUSKBD"),
+        ("operation", "......D"),
+        ("contract unit", ".P..K.D"),
+        ("ticket nat", ".P.S.B."),
+        ("big_map nat nat", ".P.S..D"),
+        ("lambda nat nat", ".PUSKBD"),
+        ("lambda operation (contract unit)", ".PUSKBD"),
+        ("list nat", ".PUSKBD"),
+        ("set nat", ".PUSKBD"),
+        ("map nat nat", ".PUSKBD"),
+        ("option nat", "CPUSKBD"),
+        ("pair nat string", "CPUSKBD"),
+        ("or nat string", "CPUSKBD"),
+        ("option operation", "......D"),
+        ("pair nat (contract unit)", ".P..K.D"),
+        ("or (ticket nat) nat", ".P.S.B."),
+        ("list (big_map nat nat)", ".P.S..D"),
+        ("map nat (ticket nat)", ".P.S.B."),
+        ("big_map nat (contract unit)", ".P....D"),
+        ("big_map nat (ticket nat)", ".P.S..."),
+        ("pair (list nat) nat", ".PUSKBD"),
+        ("option (lambda nat operation)", ".PUSKBD"),
+    ];
+    for (ty, expected) in matrix {
+        let t = parse_type(ty);
+        for (atr, flag) in attributes.iter().zip(expected.chars()) {
+            assert_eq!(check_attribute(atr, &t), flag != '.', "{:?} of {}", atr, ty);
+        }
+    }
+}
+
+#[test]
+fn test_toplevel_types() {
+    let check = |parameter: &str, storage: &str| {
+        let src = format!(
+            "parameter {} ; storage {} ; code {{ CDR ; NIL operation ; PAIR }}",
+            parameter, storage
+        );
+        typecheck_contract_all(try_parse_contract(&src).unwrap())
+            .map(|_| ())
+            .map_err(|ds| ds.into_iter().map(|d| d.message).collect::<Vec<_>>())
+    };
+    assert_eq!(check("unit", "nat"), Result::Ok(()));
+    assert_eq!(
+        check(
+            "(or (nat %add) (or (int %sub) (unit %default)))",
+            "(pair (nat :n %count) string)"
+        ),
+        Result::Ok(())
+    );
+    assert_eq!(
+        check("(or %root (nat %a) (int %b))", "unit"),
+        Result::Ok(())
+    );
+    assert_eq!(check("(or %default (nat %a) int)", "unit"), Result::Ok(()));
+    assert_eq!(
+        check("(or (or %a nat int) (unit %b))", "unit"),
+        Result::Ok(())
+    );
+    assert_eq!(
+        check("(ticket nat)", "(big_map nat (ticket nat))"),
+        Result::Ok(())
+    );
+
+    let errors = |parameter: &str, storage: &str| check(parameter, storage).unwrap_err();
+    assert!(errors("operation", "nat")[0].contains("Parameter type operation is not passable"));
+    assert!(
+        errors("unit", "(contract unit)")[0].contains("Storage type contract unit is not storable")
+    );
+    assert!(errors("unit", "(list operation)")[0].contains("is not storable"));
+    assert_eq!(
+        errors("(or (nat %a) (or (int %b) (unit %a)))", "unit"),
+        vec!["Duplicate entrypoint %a"]
+    );
+    assert_eq!(
+        errors("(or %a (nat %a) int)", "unit"),
+        vec!["Duplicate entrypoint %a"]
+    );
+    assert_eq!(
+        errors(
+            "(or (nat %an_entrypoint_name_that_is_far_too_long) int)",
+            "unit"
+        ),
+        vec![
+            "Entrypoint name an_entrypoint_name_that_is_far_too_long is longer than 31 characters"
+        ]
+    );
+    assert_eq!(
+        errors("(or (nat %default) int)", "unit"),
+        vec!["Branch is unreachable since %default is declared elsewhere"]
+    );
+    assert_eq!(
+        errors("(or (nat %a %b) int)", "unit"),
+        vec!["Unexpected annotation %b, the type is already annotated"]
+    );
+    assert_eq!(
+        errors("unit", "(nat @n)"),
+        vec!["Unexpected annotation @n on a type"]
+    );
+    // Annotations are reported at the type that carries them.
+    let src =
+        "parameter (or (nat %a) (int %a)) ; storage unit ; code { CDR ; NIL operation ; PAIR }";
+    let ds = typecheck_contract_all(try_parse_contract(src).unwrap()).unwrap_err();
+    assert_eq!(ds[0].location, Some(24));
+}
+
+#[test]
+fn test_instruction_registry() {
+    let mut registry = InstructionRegistry::with_defaults();
+    registry
+        .load(
+            "# A preprocessor instruction, and an extra variant of NOT.\n\
+             MAX :: <w|a=>comparable>;<w|a> -> <r|a>\n\
+             \n\
+             NOT :: <a|string> -> <a|string>\n\
+             NOT :: <a|bool> -> <a|bool>\n",
+        )
+        .unwrap();
+    registry.add(
+        "WRAP",
+        InstructionDef {
+            args: try_parse_constraints("<t|a>").unwrap(),
+            input_stack: try_parse_constraints("<w|b>").unwrap(),
+            output_stack: try_parse_stack_results("pair <r|b> (option <r|a>)").unwrap(),
+        },
+    );
+    let tc = |registry: &InstructionRegistry, src: &str, input: &str| {
+        let tcenv = TcEnv {
+            self_type: None,
+            instructions: registry,
+            protocol: Protocol::LATEST,
+        };
+        let mut stack = parse_stack(input);
+        typecheck(&tcenv, &parse(src), &mut stack).map(|_| stack)
+    };
+    assert_eq!(
+        tc(&registry, "MAX", "nat;nat;unit").unwrap(),
+        parse_stack("nat;unit")
+    );
+    assert!(tc(&registry, "MAX", "nat;int").is_err());
+    assert_eq!(
+        tc(&registry, "NOT", "string").unwrap(),
+        parse_stack("string")
+    );
+    // Loaded instructions replace the default variants.
+    assert!(tc(&registry, "NOT", "nat").is_err());
+    assert_eq!(
+        tc(&registry, "WRAP int", "nat").unwrap(),
+        parse_stack("pair nat (option int)")
+    );
+    assert!(tc(&MICHELSON_INSTRUCTIONS, "MAX", "nat;nat").is_err());
+    assert!(tc(&MICHELSON_INSTRUCTIONS, "NOT", "nat").is_ok());
+    // An output can refer to a type variable that nothing binds.
+    let mut loose = registry.clone();
+    loose.load("LOOSE :: <w|a> -> <r|b>").unwrap();
+    assert!(tc(&loose, "LOOSE", "nat").is_err());
+
+    let src = "parameter unit ; storage nat ; code { CDR ; DUP ; MAX ; NIL operation ; PAIR }";
+    let contract = try_parse_contract(src).unwrap();
+    let mut session = TcSession::default();
+    typecheck_contract_with(Protocol::LATEST, &registry, &contract, &mut session);
+    assert!(session.diagnostics.is_empty());
+    assert!(typecheck_contract(contract).is_err());
+
+    assert_eq!(
+        parse_signatures("FOO :: <a|nat> -> <a|nat>\nBAR <a|nat>\n")
+            .unwrap_err()
+            .location,
+        Some(26)
+    );
+    assert!(parse_signatures("FOO :: nat -> <a|nat>").is_err());
+    assert!(parse_signatures("FOO :: <q|a> -> <a|nat>").is_err());
+    assert!(parse_signatures("foo :: <a|nat> -> <a|nat>").is_err());
+}
+
+#[test]
+fn test_protocols() {
+    let tc = |protocol: Protocol, src: &str, input: &str| {
+        let registry = InstructionRegistry::for_protocol(protocol);
+        let tcenv = TcEnv {
+            self_type: None,
+            instructions: &registry,
+            protocol,
+        };
+        let mut stack = parse_stack(input);
+        typecheck(&tcenv, &parse(src), &mut stack).map(|_| stack)
+    };
+    assert!(InstructionRegistry::for_protocol(Protocol::Edo)
+        .get("EMIT")
+        .is_none());
+    assert!(InstructionRegistry::for_protocol(Protocol::Kathmandu)
+        .get("EMIT")
+        .is_some());
+    assert_eq!(
+        tc(Protocol::Lima, "TICKET", "nat;nat").unwrap(),
+        parse_stack("option (ticket nat)")
+    );
+    assert_eq!(
+        tc(Protocol::Kathmandu, "TICKET", "nat;nat").unwrap(),
+        parse_stack("ticket nat")
+    );
+    assert!(tc(Protocol::Delphi, "TICKET", "nat;nat").is_err());
+    assert_eq!(
+        tc(Protocol::Hangzhou, "SUB", "mutez;mutez").unwrap(),
+        parse_stack("mutez")
+    );
+    assert!(tc(Protocol::Ithaca, "SUB", "mutez;mutez").is_err());
+    assert_eq!(
+        tc(Protocol::Ithaca, "SUB_MUTEZ", "mutez;mutez").unwrap(),
+        parse_stack("option mutez")
+    );
+    assert!(tc(Protocol::Kathmandu, "NOT", "bytes").is_err());
+    assert!(tc(Protocol::Mumbai, "NOT", "bytes").is_ok());
+    assert_eq!(
+        tc(Protocol::Kathmandu, "LAMBDA_REC nat nat { DROP }", "").unwrap_err(),
+        "At 0: LAMBDA_REC is not available before lima"
+    );
+    assert!(tc(Protocol::Delphi, "DUP 2", "nat;nat").is_err());
+    assert!(tc(Protocol::Edo, "DUP 2", "nat;nat").is_ok());
+
+    // Code that each protocol makes available, and that the protocol before
+    // it rejects.
+    for (protocol, src, input) in [
+        (Protocol::Babylon, "CHAIN_ID", ""),
+        (Protocol::Babylon, "APPLY", "nat;lambda (pair nat int) unit"),
+        (Protocol::Babylon, "EMPTY_BIG_MAP nat nat", ""),
+        (Protocol::Babylon, "DIG 1", "nat;int"),
+        (Protocol::Babylon, "DUG 1", "nat;int"),
+        (Protocol::Edo, "UNPAIR", "pair nat int"),
+        (Protocol::Edo, "LEVEL", ""),
+        (Protocol::Hangzhou, "VIEW \"v\" nat", "unit;address"),
+        (Protocol::Ithaca, "SUB_MUTEZ", "mutez;mutez"),
+        (Protocol::Kathmandu, "EMIT", "nat"),
+        (Protocol::Lima, "LAMBDA_REC nat nat { DIP { DROP } }", ""),
+        (Protocol::Mumbai, "NOT", "bytes"),
+    ] {
+        let before = Protocol::ALL[Protocol::ALL.iter().position(|p| *p == protocol).unwrap() - 1];
+        assert!(tc(protocol, src, input).is_ok(), "{} in {}", src, protocol);
+        assert!(tc(before, src, input).is_err(), "{} in {}", src, before);
+    }
+
+    let contract =
+        try_parse_contract("parameter unit ; storage (option (ticket nat)) ; code { FAILWITH }")
+            .unwrap();
+    let mut session = TcSession::default();
+    let registry = InstructionRegistry::for_protocol(Protocol::Delphi);
+    typecheck_contract_with(Protocol::Delphi, &registry, &contract, &mut session);
+    assert_eq!(
+        session.diagnostics[0].message,
+        "Type ticket is not available before edo"
+    );
+
+    assert_eq!("Lima".parse::<Protocol>(), Ok(Protocol::Lima));
+    assert!("lisbon".parse::<Protocol>().is_err());
+    assert_eq!(Protocol::LATEST.to_string(), "oxford");
+
+    let contract = try_parse_contract(
+        "parameter unit ; storage unit ; \
+         code { DROP ; PUSH nat 1 ; PUSH nat 2 ; TICKET ; DROP ; UNIT ; NIL operation ; PAIR }",
+    )
+    .unwrap();
+    let messages: Vec<String> = upgrade_report(&contract, Protocol::Kathmandu, Protocol::Lima)
+        .into_iter()
+        .map(|d| d.message)
+        .collect();
+    assert_eq!(messages[0], "TICKET returns an option in lima");
+    assert_eq!(messages.len(), 1);
+    assert!(upgrade_report(&contract, Protocol::Lima, Protocol::Oxford).is_empty());
+}
+
+#[test]
+fn test_overload_resolution() {
+    let tcenv = TcEnv {
+        self_type: None,
+        instructions: &MICHELSON_INSTRUCTIONS,
+        protocol: Protocol::LATEST,
+    };
+    let mut stack = parse_stack("timestamp;int;unit");
+    typecheck(&tcenv, &parse("SUB"), &mut stack).unwrap();
+    assert_eq!(stack, parse_stack("timestamp;unit"));
+
+    // The error explains why each variant was rejected, including those that
+    // were ruled out without being unified.
+    let mut stack = parse_stack("string;nat");
+    let e = typecheck(&tcenv, &parse("ADD"), &mut stack).unwrap_err();
+    let variants = MICHELSON_INSTRUCTIONS.get("ADD").unwrap().len();
+    assert_eq!(e.matches("At stack element 1").count(), variants);
+    assert!(
+        e.contains("None of the instruction variants of ADD matched the stack [ string : nat ]")
+    );
+
+    // The first variant is rejected on an int and a nat, after checking the
+    // lambda, whose diagnostics are only those of the selected variant.
+    let mut registry = MICHELSON_INSTRUCTIONS.clone();
+    registry
+        .load(
+            "TAG <t|a>;<r|a> :: <w|b>;<r|b> -> <r|a>\n\
+             TAG <t|a>;<r|a> :: <w|b> -> <r|a>\n",
+        )
+        .unwrap();
+    let diagnostics = |input: &str| {
+        let src = format!(
+            "parameter unit ; storage unit ; code {{ DROP ; {} ; \
+             TAG (lambda nat nat) {{ PUSH string \"a\" ; ADD }} ; DROP 2 ; UNIT ; NIL operation ; PAIR }}",
+            input
+        );
+        let contract = try_parse_contract(&src).unwrap();
+        let mut session = TcSession::default();
+        typecheck_contract_with(Protocol::LATEST, &registry, &contract, &mut session);
+        session.diagnostics
+    };
+    let ds = diagnostics("PUSH int 1 ; PUSH nat 2");
+    assert_eq!(ds.len(), 1);
+    assert!(ds[0].message.contains("ADD"));
+}
+
+#[test]
+fn test_shared_types() {
+    let t = parse_type("pair (list (pair nat string)) (map address (option bytes))");
+    let copy = t.clone();
+    match (&t, &copy) {
+        (MPair(a), MPair(b)) => assert!(Arc::ptr_eq(a, b)),
+        _ => panic!("Expected a pair"),
+    }
+    assert_eq!(t, copy);
+    assert_eq!(
+        t,
+        parse_type("pair (list (pair nat string)) (map address (option bytes))")
+    );
+    assert_ne!(
+        t,
+        parse_type("pair (list (pair nat string)) (map address (option int))")
+    );
+
+    // DUP shares the type rather than copying it.
+    let tcenv = TcEnv {
+        self_type: None,
+        instructions: &MICHELSON_INSTRUCTIONS,
+        protocol: Protocol::LATEST,
+    };
+    let mut stack = StackState::from(vec![t]);
+    typecheck(&tcenv, &parse("DUP"), &mut stack).unwrap();
+    match (stack.get_index(0), stack.get_index(1)) {
+        (SdOk(Ok(MPair(a))), SdOk(Ok(MPair(b)))) => assert!(Arc::ptr_eq(a, b)),
+        _ => panic!("Expected two pairs"),
+    }
+}
+
+#[test]
+fn test_shared_stack() {
+    let mut stack: SharedStack<u32> = SharedStack::from(vec![1, 2, 3, 4]);
+    let tail = stack.skip(1);
+    assert_eq!(tail, SharedStack::from(vec![2, 3, 4]));
+    stack.set(1, 20);
+    stack.insert(0, 0);
+    assert_eq!(stack.remove(3), Some(3));
+    assert_eq!(stack.iter().copied().collect::<Vec<_>>(), vec![0, 1, 20, 4]);
+    // The stack skipped earlier is not affected by the changes above.
+    assert_eq!(tail.iter().copied().collect::<Vec<_>>(), vec![2, 3, 4]);
+    let mut bottom = stack.skip(2);
+    let mut top = stack.take(2);
+    top.append(&mut bottom);
+    assert_eq!(top, stack);
+    assert!(bottom.is_empty());
+    assert_eq!(stack.pop_front(), Some(0));
+    assert_eq!(stack.len(), 3);
+
+    // A deep stack is dropped without recursion.
+    drop(SharedStack::from(vec![0u8; 1_000_000]));
+
+    // Both branches of an IF_NONE start from the shared tail.
+    let mut stack = StackState::from(vec![MWrapped(MNat); 9_000]);
+    stack.push(parse_type("option int"));
+    let tcenv = TcEnv {
+        self_type: None,
+        instructions: &MICHELSON_INSTRUCTIONS,
+        protocol: Protocol::LATEST,
+    };
+    typecheck(&tcenv, &parse("IF_NONE { PUSH int 0 } {}"), &mut stack).unwrap();
+    assert_eq!(stack.len().unwrap(), 9_001);
+}
+
+#[test]
+fn test_signature_table() {
+    // The code generated by the build script builds the same types as the
+    // library does from the same signatures.
+    let mut registry = InstructionRegistry::default();
+    registry
+        .load(include_str!("../src/lib/instructions.sig"))
+        .unwrap();
+    let show = |r: &InstructionRegistry| format!("{:?}", r.iter().collect::<Vec<_>>());
+    assert_eq!(show(&registry), show(&MICHELSON_INSTRUCTIONS));
+    assert_eq!(MICHELSON_INSTRUCTIONS.get("EDIV").unwrap().len(), 6);
+}
+
+#[test]
+fn test_nesting_depth() {
+    // Sources right at the limit go through every stage on a thread with the
+    // smallest stack threads get by default, and sources one level deeper
+    // are rejected with a location.
+    let run = |code: &str, stack: ConcreteStack, mut values: Vec<MValue>| {
+        let tcenv = TcEnv {
+            self_type: None,
+            instructions: &MICHELSON_INSTRUCTIONS,
+            protocol: Protocol::LATEST,
+        };
+        let mut stack = stack;
+        let typed = typecheck(&tcenv, &try_parse_code(code).unwrap(), &mut stack).unwrap();
+        let mut out = String::new();
+        print_block(&typed, &mut out);
+        run_block(&typed, &mut values, &mut RunContext::default()).unwrap();
+        values
+    };
+    let nested = |open: &str, inner: &str, close: &str, n: usize| {
+        format!("{}{}{}", open.repeat(n), inner, close.repeat(n))
+    };
+    let at_limit = std::thread::Builder::new()
+        .stack_size(2 << 20)
+        .spawn(move || {
+            // Each DIP and each lambda is an instruction and a block.
+            let n = (MAX_NESTING_DEPTH - 1) / 2;
+            let values = vec![parse_value("0", &MWrapped(MNat)).unwrap(); n];
+            let code = nested("DIP { ", "UNIT", " }", n);
+            let values = run(&code, StackState::from(vec![MWrapped(MNat); n]), values);
+            assert_eq!(values.len(), n + 1);
+            let code = nested("LAMBDA unit unit { DROP ; ", "UNIT", " ; DROP ; UNIT }", n);
+            assert_eq!(run(&code, parse_stack(""), vec![]).len(), 1);
+            let code = nested("{ ", "UNIT", " }", MAX_NESTING_DEPTH - 1);
+            assert_eq!(run(&code, parse_stack(""), vec![]).len(), 1);
+
+            let n = MAX_NESTING_DEPTH;
+            let t = (1..n).fold(MWrapped(MNat), |t, _| MPair(Arc::new((MWrapped(MNat), t))));
+            let v = parse_value(&format!("Pair {}", vec!["1"; n].join(" ")), &t).unwrap();
+            assert!(v.to_string().starts_with("Pair 1 (Pair 1 "));
+            let t = parse_type(&nested("option (", "nat", ")", n - 1));
+            let v = parse_value(&nested("Some (", "1", ")", n - 1), &t).unwrap();
+            assert!(v.to_string().starts_with("Some (Some "));
+            assert!(try_parse_stack(&format!("(pair {})", vec!["nat"; n - 1].join(" "))).is_ok());
+        })
+        .unwrap()
+        .join();
+    assert!(at_limit.is_ok());
+
+    let n = MAX_NESTING_DEPTH.div_ceil(2);
+    let e = try_parse_code(&nested("DIP { ", "UNIT", " }", n)).unwrap_err();
+    assert_eq!(e.kind, ErrorKind::LimitExceeded(Limit::Nesting));
+    assert_eq!(e.location, Some(6 * n));
+    let e = try_parse_code(&nested("LAMBDA unit unit { ", "UNIT", " }", n)).unwrap_err();
+    assert_eq!(e.kind, ErrorKind::LimitExceeded(Limit::Nesting));
+    let n = MAX_NESTING_DEPTH + 1;
+    // Combs count as the pairs they stand for, within a single pair of
+    // brackets.
+    let t = (1..n).fold(MWrapped(MNat), |t, _| MPair(Arc::new((MWrapped(MNat), t))));
+    let e = parse_value(&format!("Pair {}", vec!["1"; n].join(" ")), &t).unwrap_err();
+    // The last pair of the comb holds the last two elements.
+    assert_eq!(e.location, Some(5 + 2 * (n - 2)));
+    let t = (2..n).fold(MWrapped(MNat), |t, _| MPair(Arc::new((MWrapped(MNat), t))));
+    let t = MList(Arc::new(t));
+    assert!(parse_value(&format!("{{ Pair {} }}", vec!["1"; n - 1].join(" ")), &t).is_err());
+    assert!(try_parse_stack(&format!("(pair {})", vec!["nat"; n - 1].join(" "))).is_err());
+    assert!(try_parse_stack(&nested("option (", "nat", ")", n)).is_err());
+    // Brackets are counted before parsing, whatever they hold.
+    let e = try_parse_code(&nested("{ ", "", " }", 4 * n)).unwrap_err();
+    assert_eq!(e.location, Some(2 * MAX_NESTING_DEPTH));
+    // Comments and strings do not count.
+    let deep = format!(
+        "PUSH string \"{}\" ; DROP # {}",
+        "{".repeat(2000),
+        "(".repeat(2000)
+    );
+    assert!(try_parse_code(&deep).is_ok());
+}
+
+#[test]
+fn test_limits() {
+    let limited = |limits: Limits, src: &str| {
+        let mut session = TcSession {
+            limits,
+            ..TcSession::default()
+        };
+        let tcenv = TcEnv {
+            self_type: None,
+            instructions: &MICHELSON_INSTRUCTIONS,
+            protocol: Protocol::LATEST,
+        };
+        let mut stack = parse_stack("");
+        typecheck_in(&tcenv, &parse(src), &mut stack, &mut session);
+        session
+            .diagnostics
+            .iter()
+            .map(|d| d.kind)
+            .collect::<Vec<_>>()
+    };
+    let exceeded = |limit| vec![ErrorKind::LimitExceeded(limit)];
+
+    // Sources are checked before they are parsed.
+    let e = try_parse_code_with(
+        "UNIT ; DROP",
+        &Limits {
+            max_source_size: 8,
+            ..Limits::default()
+        },
+    )
+    .unwrap_err();
+    assert_eq!(e.kind, ErrorKind::LimitExceeded(Limit::SourceSize));
+    let e = try_parse_contract_with(
+        "parameter unit; storage string; code { DROP ; PUSH string \"0123456789\" }",
+        &Limits {
+            max_literal_size: 8,
+            ..Limits::default()
+        },
+    )
+    .unwrap_err();
+    assert_eq!(e.kind, ErrorKind::LimitExceeded(Limit::LiteralSize));
+    assert_eq!(e.location, Some(58));
+    assert_eq!(
+        try_parse_code("SOME } ; DROP").unwrap_err().kind,
+        ErrorKind::Syntax
+    );
+
+    // A limit stops the session with a single diagnostic, whatever follows.
+    let code = "UNIT ; DROP ; UNIT ; DROP ; UNIT ; ADD";
+    assert_eq!(limited(Limits::default(), code).len(), 1);
+    let few = Limits {
+        max_instructions: 4,
+        ..Limits::default()
+    };
+    assert_eq!(limited(few, code), exceeded(Limit::Instructions));
+    let shallow = Limits {
+        max_stack_depth: 2,
+        ..Limits::default()
+    };
+    assert_eq!(
+        limited(shallow, "UNIT ; UNIT ; UNIT ; ADD"),
+        exceeded(Limit::StackDepth)
+    );
+    let small = Limits {
+        max_type_size: 4,
+        ..Limits::default()
+    };
+    assert_eq!(
+        limited(small, "UNIT ; DUP ; PAIR ; DUP ; PAIR"),
+        exceeded(Limit::TypeSize)
+    );
+    let lambdas = "LAMBDA unit unit { DROP ; LAMBDA unit unit { DROP ; UNIT } ; DROP ; UNIT }";
+    let starved = Limits {
+        fuel: 5,
+        ..Limits::default()
+    };
+    assert_eq!(limited(Limits::default(), lambdas), vec![]);
+    assert_eq!(limited(starved, lambdas), exceeded(Limit::Fuel));
+    let hurried = Limits {
+        timeout: Some(std::time::Duration::ZERO),
+        ..Limits::default()
+    };
+    assert_eq!(
+        limited(hurried, &"UNIT ; DROP ; ".repeat(200)),
+        exceeded(Limit::Time)
+    );
+
+    // The protocol limits the size of types from Babylon on.
+    let big = format!("PUSH nat 0 ; {}", "DUP ; PAIR ; ".repeat(11));
+    let mut stack = parse_stack("");
+    let mut session = TcSession::default();
+    let mut tcenv = TcEnv {
+        self_type: None,
+        instructions: &MICHELSON_INSTRUCTIONS,
+        protocol: Protocol::LATEST,
+    };
+    typecheck_in(&tcenv, &parse(&big), &mut stack, &mut session);
+    assert_eq!(session.usage.exceeded, Some(Limit::TypeSize));
+    tcenv.protocol = Protocol::Athens;
+    let mut stack = parse_stack("");
+    let mut session = TcSession::default();
+    typecheck_in(&tcenv, &parse(&big), &mut stack, &mut session);
+    assert!(session.diagnostics.is_empty());
+}
+
+#[test]
+fn test_batch() {
+    fn shared<T: Send + Sync>() {}
+    shared::<TcEnv>();
+    shared::<InstructionRegistry>();
+    shared::<Diagnostic>();
+    shared::<Script>();
+
+    let dir = std::env::temp_dir().join(format!("typechecker-batch-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("sub")).unwrap();
+    let write = |name: &str, src: &str| std::fs::write(dir.join(name), src).unwrap();
+    write(
+        "a.tz",
+        "parameter unit; storage unit; code { CDR ; NIL operation ; PAIR }",
+    );
+    write(
+        "sub/b.tz",
+        "parameter unit; storage nat; code { CDR ; NIL operation ; PAIR }",
+    );
+    write("c.tz", "parameter unit; storage unit; code { CDR ; NIL");
+    write("notes.txt", "not a script");
+    write(
+        "d.jsonl",
+        concat!(
+            r#"{"name": "unit", "script": "parameter unit; storage unit; code { CDR ; NIL operation ; PAIR }"}"#,
+            "\n\n",
+            r#"{"script": "parameter unit; storage unit; code { CAR ; NIL operation ; PAIR ; DROP }"}"#,
+            "\n"
+        ),
+    );
+
+    let scripts = collect_scripts(&[dir.display().to_string()]).unwrap();
+    let names: Vec<String> = scripts
+        .iter()
+        .map(|s| {
+            s.name
+                .trim_start_matches(&dir.display().to_string())
+                .to_string()
+        })
+        .collect();
+    assert_eq!(names, ["/a.tz", "/c.tz", "unit", "/d.jsonl:3", "/sub/b.tz"]);
+
+    let results = typecheck_batch(Protocol::LATEST, &Limits::default(), None, 3, &scripts);
+    assert_eq!(
+        typecheck_batch(Protocol::LATEST, &Limits::default(), None, 1, &scripts)
+            .iter()
+            .map(|r| &r.diagnostics)
+            .collect::<Vec<_>>(),
+        results.iter().map(|r| &r.diagnostics).collect::<Vec<_>>()
+    );
+    assert!(results.iter().zip(&scripts).all(|(r, s)| r.name == s.name));
+    let ok: Vec<bool> = results.iter().map(|r| r.diagnostics.is_empty()).collect();
+    assert_eq!(ok, [true, false, true, false, true]);
+    assert_eq!(results[1].diagnostics[0].kind, ErrorKind::Syntax);
+    assert_eq!(results[3].diagnostics[0].kind, ErrorKind::Type);
+    assert_eq!(
+        summarize(&results),
+        BatchSummary {
+            scripts: 5,
+            rejected: 2,
+            by_kind: [(ErrorKind::Syntax, 1), (ErrorKind::Type, 1)].into(),
+        }
+    );
+    assert!(collect_scripts(&[dir.join("missing.tz").display().to_string()]).is_err());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_cache() {
+    let dir = std::env::temp_dir().join(format!("typechecker-cache-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let registry = InstructionRegistry::for_protocol(Protocol::LATEST);
+    let cache = Arc::new(TcCache::open(&dir, Protocol::LATEST, &registry).unwrap());
+    // The diagnostics of a contract, and the fuel spent to find them.
+    let check = |cache: &Arc<TcCache>, src: &str| {
+        let mut session = TcSession::default();
+        let contract = try_parse_contract(src).unwrap();
+        typecheck_contract_cached(cache, &registry, &contract, src, &mut session);
+        (session.diagnostics, session.usage.fuel)
+    };
+    let lambda = "LAMBDA nat nat { PUSH nat 1 ; ADD ; DUP ; MUL }";
+    let good = format!(
+        "parameter unit; storage unit; code {{ CDR ; {} ; DROP ; NIL operation ; PAIR }}",
+        lambda
+    );
+    let (diagnostics, fuel) = check(&cache, &good);
+    assert!(diagnostics.is_empty() && fuel > 0);
+    assert_eq!(check(&cache, &good), (vec![], 0));
+    // The layout of the source does not matter.
+    let relaid = good.replace("; ", ";\n  # comment\n  ");
+    assert_eq!(check(&cache, &relaid), (vec![], 0));
+
+    // Diagnostics are reused where their locations still hold.
+    let bad = "parameter unit; storage unit; code { CDR ; UNIT ; ADD ; NIL operation ; PAIR }";
+    let (diagnostics, fuel) = check(&cache, bad);
+    assert!(!diagnostics.is_empty() && fuel > 0);
+    assert_eq!(check(&cache, bad), (diagnostics.clone(), 0));
+    let (moved, fuel) = check(&cache, &format!("  {}", bad));
+    assert!(fuel > 0);
+    assert_eq!(moved[0].location, diagnostics[0].location.map(|l| l + 2));
+
+    // Other contracts are typechecked in full, whatever lambdas they share.
+    let other = format!(
+        "parameter nat; storage unit; code {{ CDR ; {} ; DROP ; NIL operation ; PAIR }}",
+        lambda
+    );
+    let (_, uncached) = check(
+        &Arc::new(TcCache::open(dir.join("other"), Protocol::LATEST, &registry).unwrap()),
+        &other,
+    );
+    let (diagnostics, fuel) = check(&cache, &other);
+    assert_eq!((diagnostics, fuel), (vec![], uncached));
+
+    // Nothing is shared with another protocol, and unreadable entries are
+    // missing.
+    let edo = InstructionRegistry::for_protocol(Protocol::Edo);
+    let edo_cache = Arc::new(TcCache::open(&dir, Protocol::Edo, &edo).unwrap());
+    let mut session = TcSession::default();
+    let contract = try_parse_contract(&good).unwrap();
+    typecheck_contract_cached(&edo_cache, &edo, &contract, &good, &mut session);
+    assert!(session.usage.fuel > 0);
+    for entry in std::fs::read_dir(&dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_some_and(|e| e == "json") {
+            std::fs::write(path, "{").unwrap();
+        }
+    }
+    let (diagnostics, fuel) = check(&cache, &good);
+    assert!(diagnostics.is_empty() && fuel > 0);
+
+    let scripts = vec![
+        Script {
+            name: String::from("good"),
+            source: good.clone(),
+        },
+        Script {
+            name: String::from("bad"),
+            source: String::from(bad),
+        },
+    ];
+    let diagnostics = |results: Vec<ScriptResult>| -> Vec<Vec<Diagnostic>> {
+        results.into_iter().map(|r| r.diagnostics).collect()
+    };
+    let limits = Limits::default();
+    let uncached = diagnostics(typecheck_batch(
+        Protocol::LATEST,
+        &limits,
+        None,
+        2,
+        &scripts,
+    ));
+    for _ in 0..2 {
+        let cached = typecheck_batch(Protocol::LATEST, &limits, Some(&cache), 2, &scripts);
+        assert_eq!(diagnostics(cached), uncached);
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_memo() {
+    let tcenv = TcEnv {
+        self_type: Some(MWrapped(MUnit)),
+        instructions: &MICHELSON_INSTRUCTIONS,
+        protocol: Protocol::LATEST,
+    };
+    let check =
+        |tcenv: &TcEnv, code: &Vec<LocatedInstruction<SomeValue>>, session: &mut TcSession| {
+            let mut stack = parse_stack("");
+            let tins = typecheck_in(tcenv, code, &mut stack, session);
+            let mut out = String::new();
+            print_block(&tins, &mut out);
+            out
+        };
+
+    // Each body holds two copies of the one below, which are only
+    // typechecked once.
+    let mut body = String::from("DROP ; UNIT");
+    let mut work = vec![];
+    for _ in 0..12 {
+        let lambda = format!("LAMBDA unit unit {{ {} }} ; DROP", body);
+        body = format!("DROP ; {} ; {} ; UNIT", lambda, lambda);
+        let mut session = TcSession::default();
+        check(&tcenv, &parse(&lambda), &mut session);
+        assert!(session.diagnostics.is_empty());
+        work.push(session.usage.instructions);
+    }
+    assert!(work.windows(3).all(|w| w[2] - w[1] == w[1] - w[0]));
+
+    // So are the bodies of other instructions.
+    let mut body = String::from("UNIT ; DROP");
+    let mut work = vec![];
+    for _ in 0..12 {
+        body = format!("UNIT ; DIP {{ {} }} ; DIP {{ {} }} ; DROP", body, body);
+        let mut session = TcSession::default();
+        check(&tcenv, &parse(&body), &mut session);
+        assert!(session.diagnostics.is_empty());
+        work.push(session.usage.instructions);
+    }
+    assert!(work.windows(3).all(|w| w[2] - w[1] == w[1] - w[0]));
+
+    // Repeated lambdas keep their typed code, and their diagnostics are
+    // located where each one is.
+    let lambda = "LAMBDA unit nat { DROP ; PUSH nat 1 }";
+    let src = format!("{} ;\n  {}", lambda, lambda);
+    let mut session = TcSession::default();
+    let typed = check(&tcenv, &parse(&src), &mut session);
+    assert_eq!(typed.matches("PUSH nat 1").count(), 2);
+    let mut stack = parse_stack("");
+    let tins = typecheck_in(&tcenv, &parse(&src), &mut stack, &mut TcSession::default());
+    let body_locations: Vec<Vec<usize>> = tins
+        .iter()
+        .filter_map(|i| match &i.instruction {
+            Other(i) => i.args.iter().find_map(|a| match a {
+                ArgValue::ValueArg(VLambda(body)) => {
+                    Some(body.iter().map(|i| i.location).collect())
+                }
+                _ => None,
+            }),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        body_locations[1],
+        vec![src.rfind("DROP").unwrap(), src.rfind("PUSH").unwrap()]
+    );
+    let wrong = "LAMBDA unit nat { UNIT ; ADD }";
+    let src = format!("{} ;\n    {}", wrong, wrong);
+    let mut session = TcSession::default();
+    check(&tcenv, &parse(&src), &mut session);
+    let locations: Vec<_> = session.diagnostics.iter().map(|d| d.location).collect();
+    assert_eq!(locations, vec![src.find("ADD"), src.rfind("ADD")]);
+    let src = "PUSH bool True ; IF { UNIT ; UNIT ; ADD } {UNIT;UNIT;ADD} ; DROP";
+    let mut session = TcSession::default();
+    check(&tcenv, &parse(src), &mut session);
+    let locations: Vec<_> = session.diagnostics.iter().map(|d| d.location).collect();
+    assert_eq!(locations, vec![src.find("ADD"), src.rfind("ADD")]);
+
+    // The same code met again gives the same diagnostics, unless the
+    // environment differs.
+    let code = parse(&format!("{} ; DROP", wrong));
+    let mut session = TcSession::default();
+    check(&tcenv, &code, &mut session);
+    check(&tcenv, &code, &mut session);
+    assert_eq!(session.diagnostics.len(), 2);
+    assert_eq!(session.diagnostics[0], session.diagnostics[1]);
+    let code = parse("LAMBDA unit address { DROP ; SELF ; ADDRESS } ; DROP");
+    let mut session = TcSession::default();
+    check(&tcenv, &code, &mut session);
+    assert!(session.diagnostics.is_empty());
+    let no_self = TcEnv {
+        self_type: None,
+        ..tcenv
+    };
+    check(&no_self, &code, &mut session);
+    assert_eq!(session.diagnostics.len(), 1);
+    let mut table = InstructionRegistry::with_defaults();
+    table.remove("ADDRESS");
+    let changed = TcEnv {
+        self_type: tcenv.self_type.clone(),
+        instructions: &table,
+        protocol: tcenv.protocol,
+    };
+    check(&changed, &code, &mut session);
+    assert_eq!(session.diagnostics.len(), 2);
+
+    // Bodies that always fail are reused as failing.
+    let fails = "LAMBDA unit nat { FAILWITH }";
+    let mut session = TcSession::default();
+    let typed = check(
+        &tcenv,
+        &parse(&format!("{} ; {}", fails, fails)),
+        &mut session,
+    );
+    assert!(session.diagnostics.is_empty());
+    assert_eq!(typed.matches("FAILWITH").count(), 2);
+
+    // The stack trace has the instructions of every copy.
+    let lambda = "LAMBDA unit unit { DROP ; UNIT } ; DROP";
+    let mut session = TcSession {
+        stack_trace: Some(vec![]),
+        ..TcSession::default()
+    };
+    check(
+        &tcenv,
+        &parse(&format!("{} ; {}", lambda, lambda)),
+        &mut session,
+    );
+    assert_eq!(session.stack_trace.unwrap().len(), 8);
+}
+
+#[test]
+fn test_micheline() {
+    // The same contract as Michelson, Micheline JSON and binary Micheline
+    // elaborates to the same contract.
+    let text = parse_contract(
+        "parameter (or (nat %add) (unit %reset)); storage int;
+         code { UNPAIR ; IF_LEFT { ADD } { DROP 2 ; PUSH int -300 } ; NIL operation ; PAIR }",
+    );
+    let json: serde_json::Value = serde_json::from_str(
+        r#"[
+          {"prim": "parameter", "args": [{"prim": "or", "args": [
+            {"prim": "nat", "annots": ["%add"]}, {"prim": "unit", "annots": ["%reset"]}]}]},
+          {"prim": "storage", "args": [{"prim": "int"}]},
+          {"prim": "code", "args": [[
+            {"prim": "UNPAIR"},
+            {"prim": "IF_LEFT", "args": [[{"prim": "ADD"}],
+              [{"prim": "DROP", "args": [{"int": "2"}]},
+               {"prim": "PUSH", "args": [{"prim": "int"}, {"int": "-300"}]}]]},
+            {"prim": "NIL", "args": [{"prim": "operation"}]},
+            {"prim": "PAIR"}]]}
+        ]"#,
+    )
+    .unwrap();
+    let binary = hex_to_bytes(
+        "02000000460500076404620000000425616464046c000000062572657365740501035b05020200000021037a\
+         072e02000000020312020000000b052000020743035b00ec04053d036d0342",
+    )
+    .unwrap();
+    let elaborate = |node: Node| match node.expr {
+        Micheline::Seq(sections) => elaborate_contract(&sections).unwrap(),
+        _ => panic!("Expecting a sequence"),
+    };
+    for contract in [
+        elaborate(Node::from_json(&json).unwrap()),
+        elaborate(Node::from_binary(&binary).unwrap()),
+    ] {
+        assert_eq!(print_contract(&contract), print_contract(&text));
+        assert_eq!(contract.parameter_annotations.args[0].annotations, ["%add"]);
+        assert!(typecheck_contract(contract).is_ok());
+    }
+    assert_eq!(
+        micheline_to_source(&json).unwrap(),
+        "parameter (or nat %add unit %reset) ;\nstorage int ;\ncode { UNPAIR ; IF_LEFT { ADD } \
+         { DROP 2 ; PUSH int -300 } ; NIL operation ; PAIR }"
+    );
+
+    // Numbers have any size, and only those held in 64 bits are bounded.
+    let big = Node::from_binary(&hex_to_bytes("008080808080808080808002").unwrap()).unwrap();
+    assert_eq!(
+        big.expr,
+        Micheline::Int(String::from("1180591620717411303424"))
+    );
+    let value = elaborate_value(&big).unwrap();
+    let tcenv = TcEnv {
+        self_type: None,
+        instructions: &MICHELSON_INSTRUCTIONS,
+        protocol: Protocol::LATEST,
+    };
+    assert_eq!(
+        print_value(&typecheck_data(&tcenv, &value, &MWrapped(MInt)).unwrap()),
+        "1180591620717411303424"
+    );
+    assert!(typecheck_data(&tcenv, &value, &MWrapped(MMutez)).is_err());
+
+    // Errors are located at the node they are found in, in text as in binary.
+    let src = "parameter unit; storage nat; code { DROP -1 }";
+    assert_eq!(
+        try_parse_contract(src).unwrap_err().location,
+        src.find("-1")
+    );
+    let d = elaborate_type(&Node::from_binary(&hex_to_bytes("05630307").unwrap()).unwrap())
+        .unwrap_err();
+    assert_eq!(
+        (d.location, d.message.as_str()),
+        (Some(2), "Unknown type Pair")
+    );
+    assert!(Node::from_binary(&hex_to_bytes("0507").unwrap()).is_err());
+}
+
+#[test]
+fn test_visitors() {
+    // Counts instructions and FAILWITHs and collects pushed constants, in
+    // branches, lambdas and the values they push.
+    #[derive(Default)]
+    struct Census {
+        instructions: usize,
+        failwiths: usize,
+        pushed: Vec<String>,
+    }
+    impl<T: Walk + PrintValue> Visitor<T> for Census {
+        fn visit_instruction(&mut self, instruction: &CompoundInstruction<T>) {
+            self.instructions += 1;
+            match instruction {
+                FAILWITH => self.failwiths += 1,
+                Other(i) if i.name == "PUSH" => {
+                    if let [_, ArgValue::ValueArg(v)] = &i.args[..] {
+                        self.pushed.push(print_value(v));
+                    }
+                }
+                _ => {}
+            }
+            walk_instruction(self, instruction);
+        }
+    }
+
+    let code = parse(
+        r#"PUSH nat 1 ; DROP ;
+           IF { DIP { PUSH string "a" ; FAILWITH } }
+              { LAMBDA_REC unit unit { PUSH (lambda unit unit) { PUSH nat 2 ; FAILWITH } ;
+                                       DROP 3 ; UNIT } ;
+                DROP }"#,
+    );
+    let mut census = Census::default();
+    census.visit_block(&code);
+    assert_eq!(census.instructions, 13);
+    assert_eq!(census.failwiths, 2);
+    assert_eq!(census.pushed.len(), 4);
+    assert_eq!(census.pushed[3], "2");
+
+    // Typed code holds the pushed lambda as a value.
+    let tcenv = TcEnv {
+        self_type: None,
+        instructions: &MICHELSON_INSTRUCTIONS,
+        protocol: Protocol::LATEST,
+    };
+    let typed = typecheck_all(&tcenv, &code, &mut parse_stack("bool ; unit")).unwrap();
+    let mut typed_census = Census::default();
+    typed_census.visit_block(&typed);
+    assert_eq!(typed_census.instructions, census.instructions);
+    assert_eq!(typed_census.failwiths, census.failwiths);
+    assert_eq!(typed_census.pushed, census.pushed);
+
+    // Types are changed in place, those of pushed lambdas included.
+    struct NatToInt;
+    impl VisitorMut<SomeValue> for NatToInt {
+        fn visit_type_mut(&mut self, t: &mut ConcreteType) {
+            if let MWrapped(MNat) = t {
+                *t = MWrapped(MInt);
+            }
+            walk_type_mut(self, t);
+        }
+    }
+    let mut code = parse("PUSH (pair nat (lambda nat unit)) (Pair 1 { DROP ; UNIT }) ; DROP");
+    NatToInt.visit_block_mut(&mut code);
+    let printed = |code: &[LocatedInstruction<SomeValue>]| {
+        let mut out = String::new();
+        print_block(code, &mut out);
+        out
+    };
+    assert_eq!(
+        printed(&code),
+        printed(&parse(
+            "PUSH (pair int (lambda int unit)) (Pair 1 { DROP ; UNIT }) ; DROP"
+        ))
+    );
+
+    // Every number is rebuilt one larger, in nested values and blocks too.
+    struct Increment;
+    impl Fold<SomeValue> for Increment {
+        fn fold_value(&mut self, value: SomeValue) -> SomeValue {
+            match value {
+                Atomic(AVNumber(n)) => Atomic(AVNumber(n + 1)),
+                v => SomeValue::walk_fold(self, v),
+            }
+        }
+    }
+    let code = Increment.fold_block(parse(
+        "PUSH (list int) { 1 ; 2 } ; IF_CONS { DIP { DROP } ; PUSH int 3 ; ADD } { PUSH int 4 }",
+    ));
+    assert_eq!(
+        printed(&code),
+        printed(&parse(
+            "PUSH (list int) { 2 ; 3 } ; IF_CONS { DIP { DROP } ; PUSH int 4 ; ADD } { PUSH int 5 }"
+        ))
+    );
+}